@@ -0,0 +1,46 @@
+//! Records the commit and time of the build, as shown by `--about`.
+
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+    // Only run again on a new commit, or checkout, instead of on every
+    // change to the package. Missing files would have it run every time.
+    let head = Path::new(".git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed={}", head.display());
+        let branch = fs::read_to_string(head).unwrap_or_default();
+        if let Some(branch) = branch.trim().strip_prefix("ref: ") {
+            let branch = Path::new(".git").join(branch);
+            if branch.exists() {
+                println!("cargo:rerun-if-changed={}", branch.display());
+            }
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    // Builds from crates.io have no repository to ask.
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=ALEPH_TUI_COMMIT={commit}");
+
+    // Reproducible builds pin the time.
+    let time = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|time| time.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs())
+        });
+    println!("cargo:rustc-env=ALEPH_TUI_BUILD_TIME={time}");
+}
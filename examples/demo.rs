@@ -0,0 +1,117 @@
+//! Runs aleph-tui against a mock Aleph serving the fixtures in `testdata/`,
+//! presses a few keys and prints the resulting screen. It fails if the screen
+//! doesn't show what it should, which makes it a quick end-to-end check:
+//!
+//! ```sh
+//! cargo run --example demo
+//! ```
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use aleph_tui::{
+    api,
+    app::{App, Config},
+    ui, update,
+};
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, Terminal};
+use tokio::sync::mpsc;
+
+/// Answers a single request with the fixture for its path.
+fn respond(mut stream: TcpStream) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let fixture = match path.split('?').next() {
+        Some("/api/2/status") => Some("testdata/status.json"),
+        Some("/api/2/metadata") => Some("testdata/metadata.json"),
+        _ => None,
+    };
+    let (status, body) = match fixture {
+        Some(fixture) => ("200 OK", std::fs::read_to_string(fixture)?),
+        None => ("404 Not Found", "{}".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+/// Serves the fixtures on a random local port, returning its URL.
+fn mock_aleph() -> Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}", listener.local_addr()?);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = respond(stream) {
+                eprintln!("mock Aleph failed to respond: {e}");
+            }
+        }
+    });
+    Ok(url)
+}
+
+fn screen(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let url = mock_aleph()?;
+    let config: Config = toml::from_str(&format!(
+        r#"
+        default = "mock"
+        [profiles.mock]
+        url = "{url}"
+        token = "demo-token"
+        "#
+    ))?;
+    let mut app = App::from_config(config)?;
+    let snapshot =
+        api::fetch_snapshot(app.api(), app.current_profile, api::Paging::Follow, None).await;
+    app.apply_snapshot(snapshot);
+
+    let mut terminal = Terminal::new(TestBackend::new(180, 40))?;
+    // Select the first collection, watch it, switch to the tasks tab and only
+    // show collections with failed tasks.
+    let keys = [
+        KeyCode::Down,
+        KeyCode::Up,
+        KeyCode::Char('w'),
+        KeyCode::Char(']'),
+        KeyCode::Char('4'),
+    ];
+    // None of the keys makes a request.
+    let (replies, _) = mpsc::unbounded_channel();
+    for key in keys {
+        update::update(&mut app, KeyEvent::new(key, KeyModifiers::NONE), &replies);
+        terminal.draw(|f| ui::render(&mut app, f))?;
+    }
+
+    let screen = screen(&terminal);
+    println!("{screen}");
+    let expected = [
+        "OCCRP Aleph (mock): 3 jobs running",
+        "Watching [test] Chris 2024",
+        "◉ [test] Chris 2024",
+        "Total: 2 of 3 (failed > 0)",
+    ];
+    for text in expected {
+        if !screen.contains(text) {
+            return Err(eyre!("Expected the screen to show {text:?}"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1101 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, Utc};
+use color_eyre::eyre::eyre;
+use itertools::Itertools;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED, RETRY_AFTER, USER_AGENT,
+    },
+    StatusCode,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    bug_report::SchemaError,
+    models::{Metadata, Status},
+    request_log::{self, RequestLogEntry},
+    schema::{self, Parsing},
+};
+
+/// Connection to the API of one profile. It doesn't borrow from [`App`], so
+/// requests can run in background tasks while the interface stays responsive.
+///
+/// [`App`]: crate::app::App
+#[derive(Clone, Debug)]
+pub struct Api {
+    client: reqwest::Client,
+    url: String,
+    /// Base URLs tried after `url` if it can't be connected to, see
+    /// [`Api::with_fallbacks`].
+    fallbacks: Vec<String>,
+    auth: Auth,
+    version: String,
+    retry: Retry,
+    /// Of the whole request including the response body, if any.
+    timeout: Option<Duration>,
+    /// Where the download of the status is reported, if anywhere.
+    progress: Option<Progress>,
+    parsing: Parsing,
+    /// Bytes of a response body read at most, see [`Api::read_body`].
+    max_body_size: usize,
+}
+
+/// How far the download of the status got. It is shared with the task
+/// fetching it, so that a first fetch which takes a while on a large instance
+/// can be followed.
+#[derive(Clone, Debug, Default)]
+pub struct Progress(Arc<ProgressCounters>);
+
+#[derive(Debug, Default)]
+struct ProgressCounters {
+    received: AtomicU64,
+    /// Sum of the lengths of the responses which sent one.
+    expected: AtomicU64,
+    /// Whether a response didn't send its length.
+    unknown_length: AtomicBool,
+    parsing: AtomicBool,
+}
+
+impl Progress {
+    fn start(&self, length: Option<u64>) {
+        self.0.parsing.store(false, Ordering::Relaxed);
+        match length {
+            Some(length) => {
+                self.0.expected.fetch_add(length, Ordering::Relaxed);
+            }
+            None => self.0.unknown_length.store(true, Ordering::Relaxed),
+        }
+    }
+
+    fn add(&self, bytes: usize) {
+        self.0.received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn parse(&self) {
+        self.0.parsing.store(true, Ordering::Relaxed);
+    }
+
+    /// Bytes of the status received so far.
+    pub fn received(&self) -> u64 {
+        self.0.received.load(Ordering::Relaxed)
+    }
+
+    /// Bytes of the status in total, if the responses said.
+    pub fn expected(&self) -> Option<u64> {
+        match self.0.unknown_length.load(Ordering::Relaxed) {
+            true => None,
+            false => Some(self.0.expected.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Whether the status is received and being parsed.
+    pub fn is_parsing(&self) -> bool {
+        self.0.parsing.load(Ordering::Relaxed)
+    }
+}
+
+/// How requests failing with a transient error, like a 502 from a load
+/// balancer, are retried before the error is reported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Retry {
+    /// Retries after the first attempt.
+    pub count: u32,
+    /// Wait before the first retry, doubled for every further one.
+    pub backoff: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            count: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Whether a request which failed with `result` may succeed when retried.
+/// Timeouts aren't retried, the timeout of the profile is how long the user
+/// is willing to wait already.
+fn is_transient(result: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        ),
+        Err(e) => e.is_connect(),
+    }
+}
+
+/// Largest response body read if `max_response_size` isn't set, 200 MB.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 200_000_000;
+
+/// Timeout of requests to profiles which don't set `timeout_secs`.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How the HTTP client of a profile connects.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientOptions {
+    /// Of every request including the response body.
+    pub timeout: Duration,
+    /// PEM file with the certificate of a private CA, trusted in addition to
+    /// the system's.
+    pub ca_cert: Option<PathBuf>,
+    /// PEM files with the certificate and the PKCS#8 key the client presents,
+    /// for instances which require one.
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+    /// Skips verifying the certificate of the instance altogether.
+    pub accept_invalid_certs: bool,
+    /// Sent with every request, e.g. for a proxy in front of the instance.
+    pub headers: Vec<(String, String)>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            ca_cert: None,
+            client_cert: None,
+            accept_invalid_certs: false,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl ClientOptions {
+    pub fn build(&self) -> color_eyre::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(path) = &self.ca_cert {
+            let pem = std::fs::read(path)
+                .map_err(|e| eyre!("Failed to read ca_cert {}: {e}", path.display()))?;
+            let certificate = reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| eyre!("ca_cert {} is not a PEM certificate: {e}", path.display()))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some((cert_path, key_path)) = &self.client_cert {
+            let read = |path: &PathBuf, name: &str| {
+                std::fs::read(path)
+                    .map_err(|e| eyre!("Failed to read {name} {}: {e}", path.display()))
+            };
+            let identity = reqwest::Identity::from_pkcs8_pem(
+                &read(cert_path, "client_cert")?,
+                &read(key_path, "client_key")?,
+            )
+            .map_err(|e| {
+                eyre!(
+                    "client_cert {} and client_key {} are not a PEM certificate and PKCS#8 key: {e}",
+                    cert_path.display(),
+                    key_path.display()
+                )
+            })?;
+            builder = builder.identity(identity);
+        }
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| eyre!("header {name} is not valid: {e}"))?;
+            let mut value = HeaderValue::from_str(value)
+                .map_err(|e| eyre!("value of header {name} is not valid: {e}"))?;
+            // Likely a credential, so it's kept out of debug output.
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+        Ok(builder.default_headers(headers).build()?)
+    }
+}
+
+/// How a profile authenticates with Aleph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Auth {
+    /// A session token, sent as `Authorization: Bearer ...`.
+    Token(String),
+    /// An API key, sent as `Authorization: ApiKey ...`.
+    ApiKey(String),
+    /// The email of a user who logs in with their password, see
+    /// [`Api::log_in`]. The session token is sent like [`Auth::Token`] once
+    /// logged in.
+    Login {
+        email: String,
+        token: Option<String>,
+    },
+}
+
+impl Auth {
+    fn secret(&self) -> &str {
+        match self {
+            Auth::Token(secret) | Auth::ApiKey(secret) => secret,
+            Auth::Login { token, .. } => token.as_deref().unwrap_or_default(),
+        }
+    }
+
+    fn header(&self) -> Option<String> {
+        match self {
+            Auth::Token(token)
+            | Auth::Login {
+                token: Some(token), ..
+            } => Some(format!("Bearer {token}")),
+            Auth::ApiKey(key) => Some(format!("ApiKey {key}")),
+            Auth::Login { token: None, .. } => None,
+        }
+    }
+}
+
+impl Api {
+    pub fn new(url: &str, auth: Auth, version: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            fallbacks: Vec::new(),
+            auth,
+            version: version.to_string(),
+            retry: Retry::default(),
+            timeout: None,
+            progress: None,
+            parsing: Parsing::default(),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Reports the download of the status to `progress`.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Doesn't read response bodies larger than `bytes` once decompressed,
+    /// so that a misbehaving instance can't exhaust the memory.
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Reads the body of a response, which reqwest decompresses as it
+    /// arrives, reporting how much of it was received to `progress`, if any.
+    /// Stops as soon as the body gets larger than the maximum size.
+    async fn read_body(
+        &self,
+        mut response: reqwest::Response,
+        progress: Option<&Progress>,
+    ) -> color_eyre::Result<Vec<u8>> {
+        let limit = self.max_body_size;
+        let too_large = || {
+            eyre!("response is larger than {limit} bytes, raise max_response_size if that is expected")
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > limit as u64)
+        {
+            return Err(too_large());
+        }
+        if let Some(progress) = progress {
+            progress.start(response.content_length());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| self.error(e))? {
+            if let Some(progress) = progress {
+                progress.add(chunk.len());
+            }
+            body.extend_from_slice(&chunk);
+            if body.len() > limit {
+                return Err(too_large());
+            }
+        }
+        if let Some(progress) = progress {
+            progress.parse();
+        }
+        Ok(body)
+    }
+
+    /// Uses `client`, built from [`ClientOptions`] with `timeout`, instead of
+    /// a default one.
+    pub fn with_client(mut self, client: reqwest::Client, timeout: Duration) -> Self {
+        self.client = client;
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_parsing(mut self, parsing: Parsing) -> Self {
+        self.parsing = parsing;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Falls back to `urls` in order if the status can't be fetched because
+    /// connecting fails, e.g. for an instance reachable under an internal
+    /// and a public URL.
+    pub fn with_fallbacks(mut self, urls: Vec<String>) -> Self {
+        self.fallbacks = urls;
+        self
+    }
+
+    /// Switches to the next fallback URL. Returns whether there was one left.
+    fn fall_back(&mut self) -> bool {
+        if self.fallbacks.is_empty() {
+            return false;
+        }
+        self.url = self.fallbacks.remove(0);
+        true
+    }
+
+    /// Builds an authenticated request to `path` of the API.
+    pub fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.get_url(&format!("{}/api/2/{path}", self.url))
+    }
+
+    /// Builds an authenticated request to a link of the API, like the `next`
+    /// page of a result.
+    pub fn get_url(&self, url: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.client.get(url))
+    }
+
+    /// Builds an authenticated POST request to `path` of the API.
+    pub fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.client.post(format!("{}/api/2/{path}", self.url)))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = request.header(USER_AGENT, format!("aleph-tui/{}", self.version));
+        match self.auth.header() {
+            Some(header) => request.header(AUTHORIZATION, header),
+            None => request,
+        }
+    }
+
+    /// Builds the request creating a session for `email`, whose response has
+    /// the token to use from then on, see [`Auth::Login`].
+    pub fn log_in(&self, email: &str, password: &str) -> reqwest::RequestBuilder {
+        self.client
+            .post(format!("{}/api/2/sessions/login", self.url))
+            .header(USER_AGENT, format!("aleph-tui/{}", self.version))
+            .json(&serde_json::json!({ "email": email, "password": password }))
+    }
+
+    /// Sends the request, recording it in `log`.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        log: &mut Vec<RequestLogEntry>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let method = request.method().to_string();
+        let url = request_log::redact(request.url().as_str(), &[self.auth.secret()]);
+
+        let start = Instant::now();
+        let response = client.execute(request).await;
+        log.push(RequestLogEntry {
+            time: Local::now(),
+            method,
+            url,
+            status: match &response {
+                Ok(response) => Ok(response.status().as_u16()),
+                Err(e) => Err(request_log::redact(&describe(e), &[self.auth.secret()])),
+            },
+            duration: start.elapsed(),
+            body: None,
+            deviations: Vec::new(),
+        });
+        response
+    }
+
+    /// Records the start of `body` with the last request in `log`, whose
+    /// response it is.
+    fn log_body(&self, log: &mut [RequestLogEntry], body: &[u8]) {
+        if let Some(entry) = log.last_mut() {
+            entry.body = Some(request_log::excerpt(body, &[self.auth.secret()]));
+        }
+    }
+
+    /// Sends the request like [`Api::send`], retrying transient failures
+    /// according to the [`Retry`] policy, and checks the status of the final
+    /// response with [`check_status`].
+    pub async fn send_retrying(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        log: &mut Vec<RequestLogEntry>,
+    ) -> color_eyre::Result<reqwest::Response> {
+        let mut backoff = self.retry.backoff;
+        let mut attempt = 0;
+        loop {
+            // Requests with a streaming body can't be cloned, and not retried.
+            let retry = request.try_clone();
+            let result = self.send(request, log).await;
+            match retry {
+                Some(retry) if attempt < self.retry.count && is_transient(&result) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                    request = retry;
+                }
+                _ => return check_status(result.map_err(|e| self.error(e))?),
+            }
+        }
+    }
+
+    /// Turns a failed request into an error for the interface, telling
+    /// timeouts apart from other failures. Reading the response body can time
+    /// out as well.
+    pub fn error(&self, error: reqwest::Error) -> color_eyre::Report {
+        match (error.is_timeout(), self.timeout) {
+            (true, Some(timeout)) => eyre!(
+                "timed out after {}s, raise timeout_secs of this profile if the instance is just slow",
+                timeout.as_secs_f64()
+            ),
+            _ => error.into(),
+        }
+    }
+
+    /// Parses a response body. If it doesn't match the schema, the error
+    /// keeps a redacted excerpt of the body for a bug report. Unknown and
+    /// missing fields are noted with the last request in `log`, unknown ones
+    /// fail with [`Parsing::Strict`].
+    pub fn parse<T: DeserializeOwned>(
+        &self,
+        body: &[u8],
+        log: &mut [RequestLogEntry],
+    ) -> color_eyre::Result<T> {
+        let (value, deviations) = schema::from_slice(body)
+            .map_err(|e| SchemaError::new(&e, body, &[self.auth.secret()]))?;
+        if let Some(entry) = log.last_mut() {
+            entry.deviations = deviations.lines();
+        }
+        match self.parsing {
+            Parsing::Strict if !deviations.unknown.is_empty() => {
+                let unknown = deviations.unknown.iter().join(", ");
+                Err(eyre!("response has unknown fields: {unknown}"))
+            }
+            _ => Ok(value),
+        }
+    }
+}
+
+/// Describes a failed request for the request log: what kind of failure it
+/// was, followed by its causes, which name e.g. the DNS or TLS error behind a
+/// failed connection. The error itself only repeats the URL then.
+fn describe(error: &reqwest::Error) -> String {
+    let kind = if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connect"
+    } else if error.is_redirect() {
+        "redirect"
+    } else if error.is_body() || error.is_decode() {
+        "body"
+    } else {
+        "request"
+    };
+    let Some(mut source) = std::error::Error::source(error) else {
+        return format!("{kind}: {error}");
+    };
+    let mut description = kind.to_string();
+    loop {
+        description.push_str(&format!(": {source}"));
+        match source.source() {
+            Some(cause) => source = cause,
+            None => return description,
+        }
+    }
+}
+
+/// Fails for error responses like [`reqwest::Response::error_for_status`],
+/// spelling out what rejected credentials mean.
+pub fn check_status(response: reqwest::Response) -> color_eyre::Result<reqwest::Response> {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => Err(RateLimited {
+            retry_after: retry_after(response.headers(), Utc::now()),
+        }
+        .into()),
+        StatusCode::UNAUTHORIZED => Err(Unauthorized.into()),
+        StatusCode::FORBIDDEN => Err(eyre!(
+            "403 Forbidden, the token or api_key of this profile isn't allowed to do this"
+        )),
+        _ => Ok(response.error_for_status()?),
+    }
+}
+
+/// Aleph, or a proxy in front of it, answered with 429 Too Many Requests,
+/// e.g. because several people poll the same instance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimited {
+    /// How long to wait before the next request, if the response said.
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("429 Too Many Requests")
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Aleph answered with 401 Unauthorized: the credentials of the profile were
+/// rejected, or its session expired.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unauthorized;
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("401 Unauthorized, check the token or api_key of this profile")
+    }
+}
+
+impl std::error::Error for Unauthorized {}
+
+/// The `Retry-After` header, which is either a number of seconds or a date.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// A page of the status, for instances which support paging it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub limit: u64,
+    pub offset: u64,
+}
+
+/// Which pages of the status to fetch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Paging {
+    /// Whatever Aleph returns, following its `next` links to further pages.
+    Follow,
+    /// Only this page.
+    Page(Page),
+    /// All pages of this size, requested with `limit` and `offset`.
+    All(u64),
+}
+
+/// Stop following pages after this many, in case an instance keeps linking
+/// to further ones.
+const MAX_STATUS_PAGES: usize = 100;
+
+/// Validators of a response, sent along with the next request for the same
+/// resource so that Aleph can answer 304 Not Modified if it didn't change.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchedStatus {
+    /// The status, the size of its response in bytes and its validators.
+    Changed(Status, usize, Validators),
+    /// The status didn't change since the response the validators were sent
+    /// for.
+    NotModified,
+}
+
+/// Status and metadata of a profile, fetched in one go.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Index of the profile the snapshot was fetched from.
+    pub profile: usize,
+    pub status: color_eyre::Result<FetchedStatus>,
+    pub metadata: color_eyre::Result<Metadata>,
+    /// The requests made for the snapshot.
+    pub requests: Vec<RequestLogEntry>,
+    /// The base URL the snapshot was fetched from, see
+    /// [`Api::with_fallbacks`].
+    pub url: String,
+}
+
+/// Fetches one page of the status, `None` if it wasn't modified.
+async fn fetch_status_page(
+    api: &Api,
+    request: reqwest::RequestBuilder,
+    validators: Option<&Validators>,
+    requests: &mut Vec<RequestLogEntry>,
+) -> color_eyre::Result<Option<(Status, usize, Validators)>> {
+    let request = match validators {
+        Some(validators) => validators.apply(request),
+        None => request,
+    };
+    let response = api.send_retrying(request, requests).await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    let validators = Validators::from_headers(response.headers());
+    let body = api.read_body(response, api.progress.as_ref()).await?;
+    api.log_body(requests, &body);
+    let status: Status = api.parse(&body, requests)?;
+    Ok(Some((status, body.len(), validators)))
+}
+
+/// The request for `page` of the status.
+fn page_request(api: &Api, page: Page) -> reqwest::RequestBuilder {
+    api.get("status")
+        .query(&[("limit", page.limit), ("offset", page.offset)])
+}
+
+/// The request for the first page of the status with `paging`.
+fn first_status_page(api: &Api, paging: Paging) -> reqwest::RequestBuilder {
+    match paging {
+        Paging::Follow => api.get("status"),
+        Paging::Page(page) => page_request(api, page),
+        Paging::All(limit) => page_request(api, Page { limit, offset: 0 }),
+    }
+}
+
+/// Fetches the status with `paging`, merging all pages into one status. With
+/// the `validators` of the previous status, it is only transferred if it
+/// changed; that is only checked for statuses of a single page.
+async fn fetch_status(
+    api: &Api,
+    paging: Paging,
+    validators: Option<Validators>,
+    requests: &mut Vec<RequestLogEntry>,
+) -> color_eyre::Result<FetchedStatus> {
+    let first = first_status_page(api, paging);
+    let Some((mut status, mut size, validators)) =
+        fetch_status_page(api, first, validators.as_ref(), requests).await?
+    else {
+        return Ok(FetchedStatus::NotModified);
+    };
+    let mut pages = 1;
+    while pages < MAX_STATUS_PAGES {
+        let fetched = status.results.len() as u64;
+        let request = match (paging, &status.next) {
+            (Paging::Follow, Some(next)) => api.get_url(next),
+            (Paging::All(limit), _)
+                if fetched < status.total && fetched == pages as u64 * limit =>
+            {
+                page_request(
+                    api,
+                    Page {
+                        limit,
+                        offset: fetched,
+                    },
+                )
+            }
+            _ => break,
+        };
+        let Some((page, page_size, _)) = fetch_status_page(api, request, None, requests).await?
+        else {
+            break;
+        };
+        status.results.extend(page.results);
+        status.next = page.next;
+        size += page_size;
+        pages += 1;
+    }
+    // A 304 for the first page says nothing about the others.
+    let validators = match pages {
+        1 => validators,
+        _ => Validators::default(),
+    };
+    Ok(FetchedStatus::Changed(status, size, validators))
+}
+
+/// Whether the request failed without reaching the server, e.g. because its
+/// name doesn't resolve or the connection was refused.
+fn is_unreachable<T>(result: &color_eyre::Result<T>) -> bool {
+    result
+        .as_ref()
+        .err()
+        .and_then(|e| e.downcast_ref::<reqwest::Error>())
+        .is_some_and(reqwest::Error::is_connect)
+}
+
+/// Fetches a snapshot of `profile`, see [`fetch_status`]. The metadata is
+/// fetched from the URL the status could be fetched from.
+pub async fn fetch_snapshot(
+    mut api: Api,
+    profile: usize,
+    paging: Paging,
+    validators: Option<Validators>,
+) -> Snapshot {
+    let mut requests = Vec::new();
+    let mut status = fetch_status(&api, paging, validators.clone(), &mut requests).await;
+    while is_unreachable(&status) && api.fall_back() {
+        status = fetch_status(&api, paging, validators.clone(), &mut requests).await;
+    }
+    let metadata = async {
+        let request = api.get("metadata");
+        let response = api.send_retrying(request, &mut requests).await?;
+        let body = api.read_body(response, None).await?;
+        api.log_body(&mut requests, &body);
+        api.parse(&body, &mut requests)
+    }
+    .await;
+    Snapshot {
+        profile,
+        status,
+        metadata,
+        requests,
+        url: api.url,
+    }
+}
+
+/// How long to wait for a profile to respond when probing it.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The outcome of [`probe`]ing a profile.
+pub struct Probe {
+    /// `None` if the API doesn't respond, otherwise whether any jobs are
+    /// running; that is only checked if asked to and `false` otherwise.
+    pub active: Option<bool>,
+    pub requests: Vec<RequestLogEntry>,
+    /// The URL probed last, which responded unless none did; see
+    /// [`Api::with_fallbacks`].
+    pub url: String,
+}
+
+/// Checks whether the API responds, falling back to the next URL while it
+/// can't be connected to, like [`fetch_snapshot`]. Whether any jobs are
+/// running is only checked if `check_active` is set, on the first page of
+/// the status with `paging`.
+pub async fn probe(mut api: Api, check_active: bool, paging: Paging) -> Probe {
+    let mut requests = Vec::new();
+    let mut result = probe_url(&api, check_active, paging, &mut requests).await;
+    while is_unreachable(&result) && api.fall_back() {
+        result = probe_url(&api, check_active, paging, &mut requests).await;
+    }
+    Probe {
+        active: result.ok(),
+        requests,
+        url: api.url,
+    }
+}
+
+async fn probe_url(
+    api: &Api,
+    check_active: bool,
+    paging: Paging,
+    requests: &mut Vec<RequestLogEntry>,
+) -> color_eyre::Result<bool> {
+    let request = match check_active {
+        true => first_status_page(api, paging),
+        false => api.get("metadata"),
+    };
+    let response = api.send(request.timeout(PROBE_TIMEOUT), requests).await?;
+    let response = check_status(response)?;
+    if !check_active {
+        return Ok(false);
+    }
+    let body = api.read_body(response, None).await?;
+    api.log_body(requests, &body);
+    let status: Status = api.parse(&body, requests)?;
+    Ok(status.results.iter().any(|r| r.is_processing()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, io::Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::test_server::{Request, Response, Server};
+
+    /// Answers like an instance with the status and metadata of `testdata`.
+    fn aleph(request: &Request) -> Response {
+        let path = request.path.split('?').next().unwrap_or_default();
+        match path {
+            "/api/2/status" => Response::json(&read_to_string("testdata/status.json").unwrap()),
+            "/api/2/metadata" => Response::json(&read_to_string("testdata/metadata.json").unwrap()),
+            _ => Response::new(404, "Not found"),
+        }
+    }
+
+    fn api(server: &Server) -> Api {
+        Api::new(&server.url, Auth::Token("token".to_string()), "test")
+    }
+
+    /// The results of the test status from `offset` on, at most `limit`, with
+    /// a link to the `next` page if there is one.
+    fn status_page(offset: usize, limit: usize, next: Option<String>) -> Response {
+        let mut status: serde_json::Value =
+            serde_json::from_str(&read_to_string("testdata/status.json").unwrap()).unwrap();
+        let results = status["results"].as_array().unwrap();
+        let page: Vec<_> = results.iter().skip(offset).take(limit).cloned().collect();
+        status["results"] = page.into();
+        status["next"] = next.into();
+        Response::json(&status.to_string())
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_paging() {
+        let server = Server::start(|request| {
+            let host = request.header("Host").unwrap();
+            match request.path.as_str() {
+                "/api/2/status" => {
+                    status_page(0, 2, Some(format!("http://{host}/api/2/status?page=2")))
+                }
+                "/api/2/status?page=2" => status_page(2, 2, None),
+                "/api/2/status?limit=2&offset=0" => status_page(0, 2, None),
+                "/api/2/status?limit=2&offset=2" => status_page(2, 2, None),
+                _ => aleph(request),
+            }
+        })
+        .await;
+        let fetched_keys = |snapshot: Snapshot| match snapshot.status {
+            Ok(FetchedStatus::Changed(status, _, _)) => status
+                .results
+                .iter()
+                .map(|result| result.key().to_string())
+                .collect::<Vec<_>>(),
+            status => panic!("no status: {status:?}"),
+        };
+
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, None).await;
+        assert_eq!(fetched_keys(snapshot), ["94", "8194", "12"]);
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::All(2), None).await;
+        assert_eq!(fetched_keys(snapshot), ["94", "8194", "12"]);
+        let page = Page {
+            limit: 2,
+            offset: 2,
+        };
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Page(page), None).await;
+        assert_eq!(fetched_keys(snapshot), ["12"]);
+
+        let paths: Vec<_> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .filter(|path| path.starts_with("/api/2/status"))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/api/2/status",
+                "/api/2/status?page=2",
+                "/api/2/status?limit=2&offset=0",
+                "/api/2/status?limit=2&offset=2",
+                "/api/2/status?limit=2&offset=2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe() {
+        let server = Server::start(|request| match request.path.as_str() {
+            // Nothing running on the first page.
+            "/api/2/status?limit=2&offset=0" => status_page(3, 2, None),
+            _ => aleph(request),
+        })
+        .await;
+        let result = probe(api(&server), true, Paging::Follow).await;
+        assert_eq!(result.active, Some(true));
+        assert_eq!(result.url, server.url);
+        let result = probe(api(&server), true, Paging::All(2)).await;
+        assert_eq!(result.active, Some(false));
+        assert_eq!(result.requests.len(), 1);
+        let result = probe(api(&server), false, Paging::Follow).await;
+        assert_eq!(result.active, Some(false));
+
+        let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "/api/2/status",
+                "/api/2/status?limit=2&offset=0",
+                "/api/2/metadata"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validators() {
+        let etag = "\"v1\"";
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let server = Server::start(move |request| match request.path.as_str() {
+            "/api/2/status" if request.header("If-None-Match") == Some(etag) => {
+                Response::new(304, "")
+            }
+            "/api/2/status" => aleph(request)
+                .header("ETag", etag)
+                .header("Last-Modified", last_modified),
+            _ => aleph(request),
+        })
+        .await;
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, None).await;
+        let Ok(FetchedStatus::Changed(_, _, validators)) = snapshot.status else {
+            panic!("no status: {:?}", snapshot.status);
+        };
+        assert_eq!(
+            validators,
+            Validators {
+                etag: Some(etag.to_string()),
+                last_modified: Some(last_modified.to_string()),
+            }
+        );
+
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, Some(validators)).await;
+        assert!(matches!(snapshot.status, Ok(FetchedStatus::NotModified)));
+        assert!(snapshot.metadata.is_ok());
+        let request = &server.requests()[2];
+        assert_eq!(request.header("If-None-Match"), Some(etag));
+        assert_eq!(request.header("If-Modified-Since"), Some(last_modified));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_credentials() {
+        let server = Server::start(|request| match request.header("Authorization") {
+            Some("ApiKey forbidden") => Response::new(403, "Forbidden"),
+            Some("ApiKey key") => aleph(request),
+            _ => Response::new(401, "Unauthorized"),
+        })
+        .await;
+        let api_with = |auth| Api::new(&server.url, auth, "test");
+
+        let snapshot = fetch_snapshot(
+            api_with(Auth::ApiKey("key".to_string())),
+            0,
+            Paging::Follow,
+            None,
+        )
+        .await;
+        assert!(snapshot.status.is_ok() && snapshot.metadata.is_ok());
+
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, None).await;
+        assert!(snapshot.status.unwrap_err().is::<Unauthorized>());
+        assert!(snapshot.metadata.unwrap_err().is::<Unauthorized>());
+
+        let auth = Auth::ApiKey("forbidden".to_string());
+        let snapshot = fetch_snapshot(api_with(auth), 0, Paging::Follow, None).await;
+        let error = snapshot.status.unwrap_err();
+        assert!(error.to_string().starts_with("403 Forbidden"), "{error}");
+
+        // Without a session token nothing is sent before logging in.
+        let login = Auth::Login {
+            email: "user@example.com".to_string(),
+            token: None,
+        };
+        let snapshot = fetch_snapshot(api_with(login), 0, Paging::Follow, None).await;
+        assert!(snapshot.status.unwrap_err().is::<Unauthorized>());
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.header("Authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let server = Server::start(|request| match request.path.starts_with("/api/2/status") {
+            true => Response::new(429, "Slow down").header("Retry-After", "30"),
+            false => aleph(request),
+        })
+        .await;
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, None).await;
+        let error = snapshot.status.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RateLimited>(),
+            Some(&RateLimited {
+                retry_after: Some(Duration::from_secs(30))
+            })
+        );
+        assert!(snapshot.metadata.is_ok());
+        // Not retried, that would only make it worse.
+        let requests = server.requests();
+        let status_requests = requests
+            .iter()
+            .filter(|request| request.path.starts_with("/api/2/status"));
+        assert_eq!(status_requests.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_status() {
+        let status = read_to_string("testdata/status.json").unwrap();
+        let compressed = gzip(status.as_bytes());
+        let server = Server::start(move |request| match request.path.as_str() {
+            "/api/2/status" => Response::new(200, compressed.clone())
+                .header("Content-Type", "application/json")
+                .header("Content-Encoding", "gzip"),
+            _ => aleph(request),
+        })
+        .await;
+        let snapshot = fetch_snapshot(api(&server), 0, Paging::Follow, None).await;
+        let Ok(FetchedStatus::Changed(fetched, size, _)) = snapshot.status else {
+            panic!("no status: {:?}", snapshot.status);
+        };
+        assert_eq!(fetched.results.len(), 3);
+        assert_eq!(size, status.len());
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        let accepted = requests[0].header("Accept-Encoding").unwrap();
+        assert!(accepted.contains("gzip") && accepted.contains("deflate"));
+
+        // The limit applies to the decompressed body.
+        let api = api(&server).with_max_body_size(status.len() - 1);
+        let snapshot = fetch_snapshot(api, 0, Paging::Follow, None).await;
+        let error = snapshot.status.unwrap_err().to_string();
+        assert!(error.starts_with("response is larger than"), "{error}");
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let headers =
+            |value: &'static str| HeaderMap::from_iter([(RETRY_AFTER, value.parse().unwrap())]);
+        assert_eq!(
+            retry_after(&headers("120"), now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2015 07:29:30 GMT"), now),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2015 07:00:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers("soon"), now), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+}
@@ -1,13 +1,49 @@
-use crate::models::{Metadata, Status};
-use chrono::{DateTime, Local};
+use crate::{
+    api::{
+        self, Api, Auth, ClientOptions, FetchedStatus, Page, Paging, Progress, RateLimited, Retry,
+        Snapshot, Unauthorized, Validators,
+    },
+    browser,
+    bug_report::{self, SchemaError},
+    compare::MetadataDiff,
+    failures::{FailedDocument, FailureReport},
+    format::NumberFormat,
+    health::{self, HealthWeights},
+    history,
+    inhibit::SleepInhibitor,
+    keymap::{self, KeyContext},
+    models::{
+        Batch, Collection, EntitiesResponse, Metadata, Queue, SessionResponse, Status,
+        StatusResult, Task, XrefResponse,
+    },
+    notification::{Notification, NotificationKind, NotificationSource, Notifications, Toasts},
+    request_log::{self, RequestLog, RequestLogEntry},
+    schedule::Scheduler,
+    schema::Parsing,
+    sessions::Sessions,
+    state::State,
+    theme::{Background, Theme},
+    timeline::{self, EventKind},
+    upload::Upload,
+    xref::XrefSummary,
+};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
 use color_eyre::eyre::eyre;
-use ratatui::widgets::TableState;
-use reqwest::header::AUTHORIZATION;
+use ratatui::{layout::Rect, widgets::TableState};
 use serde::{
     de::{MapAccess, Visitor},
     Deserialize,
 };
-use std::fs::read_to_string;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    sync::mpsc::UnboundedSender,
+    task::{AbortHandle, JoinSet},
+};
 
 #[derive(Debug)]
 pub struct App {
@@ -17,12 +53,434 @@ pub struct App {
     pub current_profile: usize,
     pub should_quit: bool,
     pub version: String,
-    pub error_message: String,
+    pub notifications: Notifications,
+    pub toasts: Toasts,
     pub collection_tablestate: TableState,
     pub current_view: CurrentView,
     pub profile_tablestate: TableState,
     pub last_fetch: DateTime<Local>,
     pub is_fetching: bool,
+    pub sort_column: Option<SortColumn>,
+    pub sort_descending: bool,
+    /// Screen areas of the table header cells, recorded while rendering so that
+    /// mouse clicks can be mapped back to the column they hit.
+    pub header_columns: Vec<(SortColumn, Rect)>,
+    /// Whether the terminal window has focus. Polling slows down while it
+    /// doesn't.
+    pub focused: bool,
+    /// Keys of the collections the user is watching, see [`StatusResult::key`].
+    pub watched: Vec<String>,
+    /// Keys of the collections to ring the bell and flash the screen for once
+    /// they complete or fail, regardless of mutes and
+    /// [`Config::error_alert`].
+    pub notify_when_done: HashSet<String>,
+    /// Seconds between fetches adjusted with `+` and `-` during this
+    /// session.
+    pub adjusted_fetch_interval: Option<i64>,
+    pub sleep_inhibitor: SleepInhibitor,
+    /// Aleph deployments noticed during this session. They are listed in the
+    /// History tab and marked on the throughput charts.
+    pub version_changes: Vec<VersionChange>,
+    /// Failed documents of the collection they were last requested for.
+    pub failure_report: Option<FailureReport>,
+    /// Selection among the documents of the failure report, while they are
+    /// shown.
+    pub failed_documents: Option<TableState>,
+    /// Selection among the links of the selected collection, while they are
+    /// shown.
+    pub link_list: Option<TableState>,
+    /// Cross-referencing results of the collection they were last requested
+    /// for.
+    pub xref_summary: Option<XrefSummary>,
+    /// Metadata of two profiles side by side, shown until closed.
+    pub metadata_diff: Option<MetadataDiff>,
+    /// Index of the profile marked to be compared with another one, see
+    /// [`App::compare_profiles`].
+    pub compare_with: Option<usize>,
+    /// Only show collections where this counter is nonzero.
+    pub counter_filter: Option<Counter>,
+    pub details_tab: DetailsTab,
+    /// State derived by aleph-tui per collection, by [`StatusResult::key`].
+    pub collections: HashMap<String, CollectionState>,
+    /// Recent requests to Aleph, shown in the debug overlay.
+    pub request_log: RequestLog,
+    pub show_request_log: bool,
+    /// Whether the explanations of the columns and stages are shown.
+    pub show_help: bool,
+    pub theme: Theme,
+    /// Table state of the profiles which were switched away from, by profile
+    /// index, restored when switching back.
+    pub profile_tablestates: HashMap<usize, TableState>,
+    /// Last status of the profiles other than the current one, by profile
+    /// index, shown right away when switching to one of them.
+    pub profile_statuses: HashMap<usize, ProfileStatus>,
+    /// When the other profiles are fetched, see [`Config::fetch_all_profiles`].
+    pub scheduler: Scheduler,
+    /// Index of the URL of each profile which could last be connected to,
+    /// tried first by the next fetch.
+    pub active_urls: Vec<usize>,
+    /// Size in bytes of the last status response.
+    pub status_size: Option<usize>,
+    /// The fetch of the current profile, aborted when switching away from it.
+    pub fetch_task: Option<AbortHandle>,
+    /// How far the status of the current profile is fetched.
+    pub fetch_progress: Progress,
+    /// The file the status was loaded from instead of fetching it, see
+    /// [`App::load_status_file`].
+    pub status_file: Option<PathBuf>,
+    /// Fetch on the next tick, regardless of the fetch interval.
+    pub fetch_requested: bool,
+    /// Page of the status shown, starting at 0, if paging is configured.
+    pub status_page: u64,
+    /// Whether the selected collection is shown full screen instead of the
+    /// table.
+    pub zoomed: bool,
+    /// Only show collections and tasks matching this, see
+    /// [`App::visible_tasks`]. Empty if there is no search.
+    pub search: String,
+    /// Whether key presses go to the search.
+    pub is_searching: bool,
+    /// The tags being typed for a collection, key presses go to it while set.
+    pub tag_editor: Option<TagEditor>,
+    /// The password being typed to log in, key presses go to it while set.
+    pub login_prompt: Option<LoginPrompt>,
+    /// The path being typed to upload, key presses go to it while set.
+    pub upload_prompt: Option<UploadPrompt>,
+    pub upload: Option<Upload>,
+    /// Whether collections with the same first tag are shown together.
+    pub group_by_tag: bool,
+    /// Whether the last fetch of the current profile failed.
+    pub fetch_failing: bool,
+    /// Ring the terminal bell with the next frame.
+    pub ring_bell: bool,
+    /// Put on the clipboard through the terminal with the next frame.
+    pub clipboard: Option<String>,
+    /// Whether long lines of the details pane are wrapped instead of cut
+    /// off.
+    pub wrap_details: bool,
+    /// Whether `j` and `k` scroll the details pane rather than move the
+    /// selection.
+    pub details_focused: bool,
+    /// Lines the details pane is scrolled down by.
+    pub details_scroll: u16,
+    /// How far the details pane can be scrolled, as of the last render.
+    pub details_max_scroll: u16,
+    /// Invert the screen until then, see [`ErrorAlert::Flash`].
+    pub flash_until: Option<DateTime<Local>>,
+    /// Don't fetch before then, since Aleph answered with 429 Too Many
+    /// Requests.
+    pub rate_limited_until: Option<DateTime<Local>>,
+    pub state: State,
+    /// Where [`App::state`] is saved. Unset if state isn't persisted, e.g.
+    /// because the state file couldn't be read.
+    pub state_file: Option<PathBuf>,
+    /// The last response which didn't match the schema, until it is reported.
+    pub schema_error: Option<SchemaError>,
+    /// Keys of the collections with delete or flush jobs which were already
+    /// announced, see [`StatusResult::is_destructive`].
+    pub destructive: HashSet<String>,
+    /// Keys of the collections whose task rows are shown, see
+    /// [`App::visible_tasks`].
+    pub expanded: HashSet<String>,
+    /// When the event loop last ticked, see [`App::check_ticks`].
+    pub last_tick: Option<DateTime<Local>>,
+    /// When the user last pressed a key or clicked.
+    pub last_input: DateTime<Local>,
+    /// Whether the screen is blanked, see [`Config::screensaver_after`].
+    pub screensaver: bool,
+    /// Tasks which newly failed at each fetch during the last hour, oldest
+    /// first.
+    pub recent_failures: VecDeque<(DateTime<Local>, u64)>,
+    /// Tasks processed between consecutive fetches during the last 15
+    /// minutes, plus the one before, oldest first.
+    pub recent_progress: VecDeque<(DateTime<Local>, u64)>,
+    /// Validators of the last status response and the page it was for, see
+    /// [`App::status_validators`].
+    status_validators: Option<(Paging, Validators)>,
+    /// HTTP clients of the profiles, by index, so that connections are
+    /// reused across fetches.
+    clients: Vec<reqwest::Client>,
+}
+
+/// Gap between two ticks after which the event loop is considered to have
+/// been blocked. Ticks are normally 50ms apart.
+const BLOCKED_AFTER: Duration = Duration::seconds(2);
+
+/// What muting a collection repeatedly cycles through, in hours.
+const MUTE_HOURS: [i64; 3] = [1, 8, 24];
+
+/// Lines of the details pane until it is resized, and how far it can be.
+const DETAILS_HEIGHT: u16 = 9;
+const DETAILS_HEIGHTS: std::ops::RangeInclusive<u16> = 4..=40;
+/// The seconds between fetches `+` and `-` go through.
+const FETCH_INTERVAL_STEPS: [i64; 11] = [1, 2, 3, 5, 10, 15, 30, 60, 120, 300, 600];
+
+/// How long the progress of a collection is kept for, and for at most how
+/// many fetches, one a second at the shortest interval.
+const HISTORY_SPAN: Duration = Duration::hours(1);
+const HISTORY_LEN: usize = 3600;
+/// How long a task may keep working without finishing anything before it is
+/// flagged, see [`CollectionState::stuck_for`].
+const STUCK_TASK_AFTER: Duration = Duration::minutes(15);
+
+/// Commit the binary was built from, empty if it wasn't built from a git
+/// checkout. Set by the build script, as is [`BUILD_TIME`].
+const BUILD_COMMIT: &str = env!("ALEPH_TUI_COMMIT");
+/// Unix time of the build.
+const BUILD_TIME: &str = env!("ALEPH_TUI_BUILD_TIME");
+/// Optional features and whether the binary was built with them.
+const FEATURES: [(&str, bool); 1] = [("web", cfg!(feature = "web"))];
+
+/// The tags of a collection being edited, see [`App::start_editing_tags`].
+#[derive(Clone, Debug)]
+pub struct TagEditor {
+    /// See [`StatusResult::key`].
+    pub key: String,
+    pub label: String,
+    /// Comma separated tags.
+    pub input: String,
+}
+
+/// The password of a profile being typed, see [`App::start_login`].
+#[derive(Clone, Debug)]
+pub struct LoginPrompt {
+    pub profile: usize,
+    pub password: String,
+}
+
+/// The path of a file or directory to upload being typed, see
+/// [`App::start_upload`].
+#[derive(Clone, Debug)]
+pub struct UploadPrompt {
+    pub collection: Collection,
+    pub path: String,
+}
+
+/// What a request started by a key press got back. Such requests run in the
+/// background like fetches do, and their replies are applied by
+/// [`App::apply_reply`] once they arrive.
+#[derive(Debug)]
+pub enum Reply {
+    /// Metadata of two profiles, see [`App::compare_profiles`].
+    Comparison {
+        names: [String; 2],
+        metadata: [color_eyre::Result<serde_json::Value>; 2],
+        requests: Vec<RequestLogEntry>,
+    },
+    /// Failed documents of a collection, see [`App::fetch_failures`].
+    Failures {
+        collection_id: String,
+        label: String,
+        response: color_eyre::Result<EntitiesResponse>,
+        requests: Vec<RequestLogEntry>,
+    },
+    /// Cross-referencing matches of a collection, see [`App::fetch_xref`].
+    Xref {
+        collection_id: String,
+        response: color_eyre::Result<XrefResponse>,
+        requests: Vec<RequestLogEntry>,
+    },
+    /// A session of a profile logging in with a password, see
+    /// [`App::stop_login`].
+    Login {
+        profile: usize,
+        session: color_eyre::Result<SessionResponse>,
+        requests: Vec<RequestLogEntry>,
+    },
+}
+
+/// Identifies a row of the table across fetches: the key of the collection
+/// and, for task rows, the names of the batch, queue and task.
+#[derive(Clone, Debug, PartialEq)]
+struct RowKey {
+    collection: String,
+    task: Option<(String, String, String)>,
+}
+
+/// The status of a profile which isn't the current one.
+#[derive(Clone, Debug)]
+pub struct ProfileStatus {
+    pub status: Status,
+    pub metadata: Metadata,
+    pub fetched: DateTime<Local>,
+    /// Kept from when the profile was last the current one, so the
+    /// sparklines, stall detection and health carry on when switching back.
+    pub collections: HashMap<String, CollectionState>,
+}
+
+/// Per-collection state accumulated across fetches.
+#[derive(Clone, Debug)]
+pub struct CollectionState {
+    /// When the collection was last part of the status or selected.
+    pub last_seen: DateTime<Local>,
+    /// Succeeded and failed tasks at each fetch during the last
+    /// [`HISTORY_SPAN`], oldest first.
+    pub history: VecDeque<(DateTime<Local>, u64)>,
+    /// When the number of succeeded and failed tasks last changed.
+    pub last_progress: DateTime<Local>,
+    /// Failed tasks at the last fetch.
+    pub failed: Option<u64>,
+    pub samples: history::Samples,
+    /// The tasks of the collection, by the names of their batch, queue and
+    /// task.
+    tasks: HashMap<(String, String, String), TaskProgress>,
+}
+
+/// The counters of a task at the last fetch, and when it last made progress.
+#[derive(Clone, Copy, Debug)]
+struct TaskProgress {
+    doing: u64,
+    finished: u64,
+    /// When more of its tasks finished, or fewer were being worked on.
+    since: DateTime<Local>,
+}
+
+impl CollectionState {
+    fn new(now: DateTime<Local>) -> Self {
+        Self {
+            last_seen: now,
+            history: VecDeque::new(),
+            last_progress: now,
+            failed: None,
+            samples: history::Samples::default(),
+            tasks: HashMap::new(),
+        }
+    }
+
+    fn record_tasks(&mut self, result: &StatusResult, now: DateTime<Local>) {
+        let mut tasks = HashMap::new();
+        for (batch, queue, task) in result.tasks() {
+            let key = (batch.name.clone(), queue.name.clone(), task.name.clone());
+            let finished = task.succeeded.saturating_add(task.failed);
+            let since = match self.tasks.get(&key) {
+                Some(before) if task.doing >= before.doing && finished <= before.finished => {
+                    before.since
+                }
+                _ => now,
+            };
+            let progress = TaskProgress {
+                doing: task.doing,
+                finished,
+                since,
+            };
+            tasks.insert(key, progress);
+        }
+        self.tasks = tasks;
+    }
+
+    /// How long `task` has been working without finishing anything or
+    /// working on less, if that's long enough to suspect it of retrying in a
+    /// loop. Aleph doesn't report retries, so this is the next best sign.
+    pub fn stuck_for(
+        &self,
+        (batch, queue, task): (&Batch, &Queue, &Task),
+        now: DateTime<Local>,
+    ) -> Option<Duration> {
+        if task.doing == 0 {
+            return None;
+        }
+        let key = (batch.name.clone(), queue.name.clone(), task.name.clone());
+        let stuck = now - self.tasks.get(&key)?.since;
+        (stuck >= STUCK_TASK_AFTER).then_some(stuck)
+    }
+
+    fn record(&mut self, done: u64, now: DateTime<Local>) {
+        if self.history.back().map(|(_, before)| *before) != Some(done) {
+            self.last_progress = now;
+        }
+        while self.history.len() >= HISTORY_LEN
+            || self
+                .history
+                .front()
+                .is_some_and(|(time, _)| now - *time > HISTORY_SPAN)
+        {
+            self.history.pop_front();
+        }
+        self.history.push_back((now, done));
+    }
+
+    /// Tasks processed between consecutive fetches, along with when the
+    /// later one was.
+    pub fn throughput(&self) -> Vec<(DateTime<Local>, u64)> {
+        self.history
+            .iter()
+            .zip(self.history.iter().skip(1))
+            .map(|((_, before), (time, after))| (*time, after.saturating_sub(*before)))
+            .collect()
+    }
+
+    /// Tasks processed per minute, oldest first, between the first fetches
+    /// at least a minute apart, along with when the later one was. Longer
+    /// gaps, e.g. while the computer slept, are averaged.
+    pub fn throughput_per_minute(&self) -> Vec<(DateTime<Local>, u64)> {
+        let mut per_minute = Vec::new();
+        let Some(mut before) = self.history.front() else {
+            return per_minute;
+        };
+        for after in &self.history {
+            let seconds = (after.0 - before.0).num_seconds();
+            if seconds >= 60 {
+                let processed = after.1.saturating_sub(before.1);
+                per_minute.push((after.0, processed.saturating_mul(60) / seconds as u64));
+                before = after;
+            }
+        }
+        per_minute
+    }
+
+    /// Tasks processed per minute over the last `fetches`, if they were
+    /// apart at all.
+    pub fn recent_throughput(&self, fetches: usize) -> Option<f64> {
+        let (last, done) = self.history.back()?;
+        let (first, done_before) = self
+            .history
+            .iter()
+            .rev()
+            .nth(fetches)
+            .or(self.history.front())?;
+        let minutes = (*last - *first).num_milliseconds() as f64 / 60_000.0;
+        (minutes > 0.0).then(|| done.saturating_sub(*done_before) as f64 / minutes)
+    }
+}
+
+/// Minutes over which [`App::throughput_averages`] are taken.
+pub const THROUGHPUT_WINDOWS: [i64; 3] = [1, 5, 15];
+
+/// Average per minute of the `samples` during the `window` before the last
+/// one, or since the first one if that is more recent. The count of the
+/// first sample is left out, since it happened before.
+fn rolling_average(samples: &VecDeque<(DateTime<Local>, u64)>, window: Duration) -> Option<f64> {
+    let (first, _) = samples.front()?;
+    let (last, _) = samples.back()?;
+    let start = (*last - window).max(*first);
+    let minutes = (*last - start).num_milliseconds() as f64 / 60_000.0;
+    if minutes <= 0.0 {
+        return None;
+    }
+    let count: u64 = samples
+        .iter()
+        .filter(|(time, _)| *time > start)
+        .map(|(_, count)| count)
+        .sum();
+    Some(count as f64 / minutes)
+}
+
+/// Drops the state of collections which haven't been seen for longer than
+/// `max_idle`, except those for which `keep` returns true.
+fn prune_idle(
+    collections: &mut HashMap<String, CollectionState>,
+    now: DateTime<Local>,
+    max_idle: Duration,
+    keep: impl Fn(&str) -> bool,
+) {
+    collections.retain(|key, state| now - state.last_seen <= max_idle || keep(key));
+}
+
+#[derive(Clone, Debug)]
+pub struct VersionChange {
+    pub time: DateTime<Local>,
+    pub from: String,
+    pub to: String,
 }
 
 #[derive(Clone, Debug)]
@@ -30,24 +488,152 @@ pub struct Config {
     default: String,
     pub profiles: Vec<Profile>,
     pub fetch_interval: i64,
+    /// Fetch interval used while the terminal window is not focused.
+    pub unfocused_fetch_interval: i64,
+    /// Keep the fetch interval adjusted with `+` and `-` in the state file,
+    /// instead of going back to `fetch_interval` with the next session.
+    pub remember_fetch_interval: bool,
+    /// Keep the system from sleeping while a watched collection is processing.
+    pub keep_awake: bool,
+    /// CSV file which collection start and completion events are appended to.
+    pub timeline_file: Option<PathBuf>,
+    /// File every request to Aleph is logged to.
+    pub request_log_file: Option<PathBuf>,
+    /// Hours after which the state kept for a collection which is neither in
+    /// the status nor selected is dropped.
+    pub prune_idle_after: i64,
+    /// How many collections can be watched at the same time.
+    pub max_watched: usize,
+    /// Merge results reporting on the same collection into one row.
+    pub merge_duplicates: bool,
+    /// Size in megabytes above which a status response is considered large.
+    pub large_status_size: f64,
+    /// Size in megabytes of the largest response read, see
+    /// [`Api::with_max_body_size`].
+    pub max_response_size: f64,
+    /// Seconds the age of the last fetch is rounded down to in the status bar.
+    pub fetch_age_resolution: i64,
+    /// Which profile to start with, instead of the default one.
+    pub auto_select: Option<AutoSelect>,
+    /// Whether responses with unknown or missing fields fail to parse.
+    pub parsing: Parsing,
+    /// Number of results to request per page of the status, for instances
+    /// which support paging it.
+    pub status_page_size: Option<u64>,
+    /// Fetch all pages of the status and show them as one, instead of one
+    /// page at a time.
+    pub merge_status_pages: bool,
+    /// How to draw attention to fetches starting to fail.
+    pub error_alert: Option<ErrorAlert>,
+    pub health_weights: HealthWeights,
+    /// How counters are written, from the `[display]` table.
+    pub number_format: NumberFormat,
+    /// Address to serve a read-only web page mirroring the table on, if
+    /// built with the `web` feature.
+    pub web_mirror: Option<String>,
+    /// How fetches failing with transient errors are retried.
+    pub retry: Retry,
+    /// Where mutes are kept instead of [`State::default_path`], e.g. a file
+    /// shared with the rest of the team.
+    pub state_file: Option<PathBuf>,
+    /// Overrides the detected brightness of the terminal background.
+    pub background: Option<Background>,
+    /// Show the number of running jobs and recent failures in large digits,
+    /// for wall displays.
+    pub big_counters: bool,
+    /// Minutes without input after which the screen is blanked.
+    pub screensaver_after: Option<i64>,
+    /// Open the profile selector when waking up from the screensaver, to
+    /// confirm which instance is shown.
+    pub screensaver_confirm_profile: bool,
+    /// Fetch all profiles along with the current one, so switching between
+    /// them is instant.
+    pub fetch_all_profiles: bool,
+    /// How many of them are fetched from the same host at a time.
+    pub max_fetches_per_host: usize,
+}
+
+/// How to pick the profile to start with, see [`App::auto_select_profile`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoSelect {
+    /// The first profile which responds.
+    Reachable,
+    /// The first profile with running jobs, or the first which responds.
+    Active,
+}
+
+/// Alert for a fetch error after successful fetches, see
+/// [`Config::error_alert`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorAlert {
+    /// Ring the terminal bell.
+    Bell,
+    /// Briefly invert the screen.
+    Flash,
+    Both,
 }
 
+/// How long the screen stays inverted for [`ErrorAlert::Flash`].
+const FLASH_DURATION: Duration = Duration::seconds(1);
+/// Number of xref matches, the most likely first, whose judgements are
+/// counted.
+const XREF_SAMPLE_SIZE: u64 = 500;
+/// How long fetches are delayed after a 429 without a `Retry-After` header.
+const RATE_LIMIT_DELAY: Duration = Duration::seconds(30);
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             default: Default::default(),
             profiles: Default::default(),
             fetch_interval: 5,
+            unfocused_fetch_interval: 30,
+            remember_fetch_interval: false,
+            keep_awake: false,
+            timeline_file: None,
+            request_log_file: None,
+            prune_idle_after: 24,
+            max_watched: 4,
+            merge_duplicates: false,
+            large_status_size: 5.0,
+            max_response_size: api::DEFAULT_MAX_BODY_SIZE as f64 / 1e6,
+            fetch_age_resolution: 1,
+            auto_select: None,
+            parsing: Parsing::default(),
+            status_page_size: None,
+            merge_status_pages: false,
+            error_alert: None,
+            health_weights: HealthWeights::default(),
+            number_format: NumberFormat::default(),
+            web_mirror: None,
+            retry: Retry::default(),
+            state_file: None,
+            background: None,
+            big_counters: false,
+            screensaver_after: None,
+            screensaver_confirm_profile: false,
+            fetch_all_profiles: false,
+            max_fetches_per_host: 2,
         }
     }
 }
 
-#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Profile {
     pub index: usize,
     pub name: String,
-    url: String,
-    token: String,
+    /// Base URLs of the instance, tried in order, see
+    /// [`Api::with_fallbacks`].
+    urls: Vec<String>,
+    auth: Auth,
+    client: ClientOptions,
+}
+
+impl Profile {
+    /// Whether the profile has more than one URL to try.
+    pub fn has_fallbacks(&self) -> bool {
+        self.urls.len() > 1
+    }
 }
 
 impl<'de> Deserialize<'de> for Config {
@@ -80,26 +666,358 @@ impl<'de> Deserialize<'de> for Config {
                             let table = value.as_table().expect("Profiles is not a table");
                             for (index, (key, value)) in table.into_iter().enumerate() {
                                 let v = value.as_table().expect("Profile is not a table");
+                                let secret = |name: &str| {
+                                    v.get(name).map(|secret| {
+                                        secret
+                                            .as_str()
+                                            .unwrap_or_else(|| panic!("{name} is not a string"))
+                                            .to_string()
+                                    })
+                                };
+                                // Aleph accepts API keys where session tokens
+                                // aren't enough, so they take precedence.
+                                let auth =
+                                    match (secret("api_key"), secret("token"), secret("email")) {
+                                        (Some(key), _, _) => Auth::ApiKey(key),
+                                        (None, Some(token), _) => Auth::Token(token),
+                                        (None, None, Some(email)) => {
+                                            Auth::Login { email, token: None }
+                                        }
+                                        (None, None, None) => {
+                                            return Err(serde::de::Error::custom(format!(
+                                            "profile {key} needs a token, an api_key or an email"
+                                        )))
+                                        }
+                                    };
+                                let path =
+                                    |name: &str| {
+                                        v.get(name).map(|path| {
+                                            PathBuf::from(path.as_str().unwrap_or_else(|| {
+                                                panic!("{name} is not a string")
+                                            }))
+                                        })
+                                    };
+                                let client_cert = match (path("client_cert"), path("client_key")) {
+                                    (Some(cert), Some(client_key)) => Some((cert, client_key)),
+                                    (None, None) => None,
+                                    _ => {
+                                        return Err(serde::de::Error::custom(format!(
+                                        "profile {key} needs both a client_cert and a client_key"
+                                    )))
+                                    }
+                                };
+                                let url = v.get("url").expect("url missing from profile");
+                                let urls: Vec<String> = match url {
+                                    toml::Value::Array(urls) => urls
+                                        .iter()
+                                        .map(|url| {
+                                            url.as_str().expect("url is not a string").to_string()
+                                        })
+                                        .collect(),
+                                    url => vec![url
+                                        .as_str()
+                                        .expect("url is not a string or a list")
+                                        .to_string()],
+                                };
+                                if urls.is_empty() {
+                                    return Err(serde::de::Error::custom(format!(
+                                        "profile {key} needs at least one url"
+                                    )));
+                                }
                                 let profile = Profile {
                                     name: key.to_string(),
                                     index,
-                                    url: v
-                                        .get("url")
-                                        .expect("url missing from profile")
-                                        .as_str()
-                                        .expect("url is not a string")
-                                        .to_string(),
-                                    token: v
-                                        .get("token")
-                                        .expect("token missing from profile")
-                                        .as_str()
-                                        .expect("token is not a string")
-                                        .to_string(),
+                                    urls,
+                                    auth,
+                                    client: ClientOptions {
+                                        timeout: match v.get("timeout_secs") {
+                                            Some(timeout) => {
+                                                let secs = timeout
+                                                    .as_float()
+                                                    .or(timeout.as_integer().map(|t| t as f64))
+                                                    .expect("timeout_secs is not a number");
+                                                // A timeout of zero fails every request.
+                                                std::time::Duration::try_from_secs_f64(secs)
+                                                    .ok()
+                                                    .filter(|timeout| !timeout.is_zero())
+                                                    .expect(
+                                                        "timeout_secs is not positive or too large",
+                                                    )
+                                            }
+                                            None => api::DEFAULT_TIMEOUT,
+                                        },
+                                        ca_cert: v.get("ca_cert").map(|path| {
+                                            PathBuf::from(
+                                                path.as_str().expect("ca_cert is not a string"),
+                                            )
+                                        }),
+                                        client_cert,
+                                        accept_invalid_certs: v
+                                            .get("accept_invalid_certs")
+                                            .map(|accept| {
+                                                accept
+                                                    .as_bool()
+                                                    .expect("accept_invalid_certs is not a boolean")
+                                            })
+                                            .unwrap_or(false),
+                                        headers: v
+                                            .get("headers")
+                                            .map(|headers| {
+                                                headers
+                                                    .as_table()
+                                                    .expect("headers is not a table")
+                                                    .iter()
+                                                    .map(|(name, value)| {
+                                                        let value =
+                                                            value.as_str().unwrap_or_else(|| {
+                                                                panic!(
+                                                                    "header {name} is not a string"
+                                                                )
+                                                            });
+                                                        (name.clone(), value.to_string())
+                                                    })
+                                                    .collect()
+                                            })
+                                            .unwrap_or_default(),
+                                    },
                                 };
                                 profiles.push(profile);
                             }
                             cfg.profiles = profiles;
                         }
+                        "fetch_interval" => {
+                            cfg.fetch_interval = value
+                                .as_integer()
+                                .expect("fetch_interval is not an integer");
+                        }
+                        "unfocused_fetch_interval" => {
+                            cfg.unfocused_fetch_interval = value
+                                .as_integer()
+                                .expect("unfocused_fetch_interval is not an integer");
+                        }
+                        "auto_select" => {
+                            let mode = value.as_str().expect("auto_select is not a string");
+                            cfg.auto_select = Some(match mode {
+                                "reachable" => AutoSelect::Reachable,
+                                "active" => AutoSelect::Active,
+                                _ => {
+                                    return Err(serde::de::Error::custom(format!(
+                                        "auto_select is {mode}, expected reachable or active"
+                                    )))
+                                }
+                            });
+                        }
+                        "parsing" => {
+                            let mode = value.as_str().expect("parsing is not a string");
+                            cfg.parsing = match mode {
+                                "lenient" => Parsing::Lenient,
+                                "strict" => Parsing::Strict,
+                                _ => {
+                                    return Err(serde::de::Error::custom(format!(
+                                        "parsing is {mode}, expected lenient or strict"
+                                    )))
+                                }
+                            };
+                        }
+                        "background" => {
+                            let background = value.as_str().expect("background is not a string");
+                            cfg.background = Some(match background {
+                                "dark" => Background::Dark,
+                                "light" => Background::Light,
+                                _ => {
+                                    return Err(serde::de::Error::custom(format!(
+                                        "background is {background}, expected dark or light"
+                                    )))
+                                }
+                            });
+                        }
+                        "error_alert" => {
+                            let alert = value.as_str().expect("error_alert is not a string");
+                            cfg.error_alert = Some(match alert {
+                                "bell" => ErrorAlert::Bell,
+                                "flash" => ErrorAlert::Flash,
+                                "both" => ErrorAlert::Both,
+                                _ => {
+                                    return Err(serde::de::Error::custom(format!(
+                                        "error_alert is {alert}, expected bell, flash or both"
+                                    )))
+                                }
+                            });
+                        }
+                        "display" => {
+                            let display = value.as_table().expect("display is not a table");
+                            for (name, value) in display {
+                                let format = &mut cfg.number_format;
+                                match name.as_str() {
+                                    "separator" => {
+                                        format.separator = value
+                                            .as_str()
+                                            .expect("separator is not a string")
+                                            .to_string();
+                                    }
+                                    "group_from" => {
+                                        format.group_from = value
+                                            .as_integer()
+                                            .expect("group_from is not an integer")
+                                            .max(0)
+                                            as u64;
+                                    }
+                                    "ungrouped_columns" => {
+                                        let columns = value
+                                            .as_array()
+                                            .expect("ungrouped_columns is not an array");
+                                        for column in columns {
+                                            let column =
+                                                column.as_str().expect("column is not a string");
+                                            format.ungrouped.push(match column {
+                                                "todo" => SortColumn::Todo,
+                                                "effective_todo" => SortColumn::EffectiveTodo,
+                                                "doing" => SortColumn::Doing,
+                                                "succeeded" => SortColumn::Succeeded,
+                                                "failed" => SortColumn::Failed,
+                                                "aborted" => SortColumn::Aborted,
+                                                "aborting" => SortColumn::Aborting,
+                                                "cancelled" => SortColumn::Cancelled,
+                                                _ => {
+                                                    return Err(serde::de::Error::custom(format!(
+                                                        "ungrouped_columns contains {column}, which is not a counter"
+                                                    )))
+                                                }
+                                            });
+                                        }
+                                    }
+                                    _ => {
+                                        return Err(serde::de::Error::custom(format!(
+                                            "unknown display option {name}"
+                                        )))
+                                    }
+                                }
+                            }
+                        }
+                        "health_weights" => {
+                            let weights = value.as_table().expect("health_weights is not a table");
+                            for (name, weight) in weights {
+                                let weight = weight
+                                    .as_float()
+                                    .or(weight.as_integer().map(|weight| weight as f64))
+                                    .expect("health weight is not a number");
+                                match name.as_str() {
+                                    "failures" => cfg.health_weights.failures = weight,
+                                    "stall" => cfg.health_weights.stall = weight,
+                                    "age" => cfg.health_weights.age = weight,
+                                    _ => {
+                                        return Err(serde::de::Error::custom(format!(
+                                            "unknown health weight {name}, expected failures, stall or age"
+                                        )))
+                                    }
+                                }
+                            }
+                        }
+                        "status_page_size" => {
+                            let size = value
+                                .as_integer()
+                                .expect("status_page_size is not an integer");
+                            cfg.status_page_size = Some(size.max(1) as u64);
+                        }
+                        "fetch_age_resolution" => {
+                            cfg.fetch_age_resolution = value
+                                .as_integer()
+                                .expect("fetch_age_resolution is not an integer");
+                        }
+                        "large_status_size" => {
+                            cfg.large_status_size = value
+                                .as_float()
+                                .or(value.as_integer().map(|size| size as f64))
+                                .expect("large_status_size is not a number");
+                        }
+                        "max_response_size" => {
+                            cfg.max_response_size = value
+                                .as_float()
+                                .or(value.as_integer().map(|size| size as f64))
+                                .expect("max_response_size is not a number");
+                        }
+                        "merge_status_pages" => {
+                            cfg.merge_status_pages = value
+                                .as_bool()
+                                .expect("merge_status_pages is not a boolean");
+                        }
+                        "fetch_all_profiles" => {
+                            cfg.fetch_all_profiles = value
+                                .as_bool()
+                                .expect("fetch_all_profiles is not a boolean");
+                        }
+                        "merge_duplicates" => {
+                            cfg.merge_duplicates =
+                                value.as_bool().expect("merge_duplicates is not a boolean");
+                        }
+                        "screensaver_after" => {
+                            let minutes = value
+                                .as_integer()
+                                .expect("screensaver_after is not an integer");
+                            cfg.screensaver_after = Some(minutes.max(1));
+                        }
+                        "screensaver_confirm_profile" => {
+                            cfg.screensaver_confirm_profile = value
+                                .as_bool()
+                                .expect("screensaver_confirm_profile is not a boolean");
+                        }
+                        "remember_fetch_interval" => {
+                            cfg.remember_fetch_interval = value
+                                .as_bool()
+                                .expect("remember_fetch_interval is not a boolean");
+                        }
+                        "big_counters" => {
+                            cfg.big_counters =
+                                value.as_bool().expect("big_counters is not a boolean");
+                        }
+                        "keep_awake" => {
+                            cfg.keep_awake = value.as_bool().expect("keep_awake is not a boolean");
+                        }
+                        "max_fetches_per_host" => {
+                            let max_fetches = value
+                                .as_integer()
+                                .expect("max_fetches_per_host is not an integer");
+                            cfg.max_fetches_per_host = max_fetches.max(1) as usize;
+                        }
+                        "max_watched" => {
+                            let max_watched =
+                                value.as_integer().expect("max_watched is not an integer");
+                            cfg.max_watched = max_watched.max(0) as usize;
+                        }
+                        "prune_idle_after" => {
+                            cfg.prune_idle_after = value
+                                .as_integer()
+                                .expect("prune_idle_after is not an integer");
+                        }
+                        "request_log_file" => {
+                            let path = value.as_str().expect("request_log_file is not a string");
+                            cfg.request_log_file = Some(PathBuf::from(path));
+                        }
+                        "retries" => {
+                            let retries = value.as_integer().expect("retries is not an integer");
+                            cfg.retry.count = retries.max(0) as u32;
+                        }
+                        "retry_backoff" => {
+                            let backoff = value
+                                .as_float()
+                                .or(value.as_integer().map(|backoff| backoff as f64))
+                                .expect("retry_backoff is not a number");
+                            cfg.retry.backoff =
+                                std::time::Duration::try_from_secs_f64(backoff.max(0.0))
+                                    .expect("retry_backoff is too large");
+                        }
+                        "web_mirror" => {
+                            let address = value.as_str().expect("web_mirror is not a string");
+                            cfg.web_mirror = Some(address.to_string());
+                        }
+                        "state_file" => {
+                            let path = value.as_str().expect("state_file is not a string");
+                            cfg.state_file = Some(PathBuf::from(path));
+                        }
+                        "timeline_file" => {
+                            let path = value.as_str().expect("timeline_file is not a string");
+                            cfg.timeline_file = Some(PathBuf::from(path));
+                        }
                         _ => {}
                     }
                 }
@@ -114,6 +1032,39 @@ impl<'de> Deserialize<'de> for Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_server::{unreachable_url, Request, Response, Server};
+
+    /// An app with the profile `one` and the `config` on top, which may add
+    /// options and further profiles.
+    fn test_app(config: &str) -> App {
+        test_app_at("\"url1\"", config)
+    }
+
+    /// Like [`test_app`], with the profile `one` at `url`, a TOML string or
+    /// array of strings.
+    fn test_app_at(url: &str, config: &str) -> App {
+        let raw = format!(
+            "default = \"one\"\n{config}\n[profiles.one]\nurl = {url}\ntoken = \"token1\"\n"
+        );
+        App::from_config(toml::from_str(&raw).unwrap()).unwrap()
+    }
+
+    /// Fetches the current profile like [`update::fetch`](crate::update)
+    /// does, and applies the snapshot.
+    async fn fetch(app: &mut App) {
+        let snapshot = api::fetch_snapshot(
+            app.api(),
+            app.current_profile,
+            app.status_paging(),
+            app.status_validators(),
+        )
+        .await;
+        app.apply_snapshot(snapshot);
+    }
+
+    fn test_status() -> Status {
+        serde_json::from_str(&read_to_string("testdata/status.json").unwrap()).unwrap()
+    }
 
     #[test]
     fn test_de_profiles() {
@@ -131,194 +1082,3101 @@ mod tests {
         "#;
 
         let cfg: Config = toml::from_str(raw).unwrap();
-        assert!(cfg.default == "foo")
+        assert!(cfg.default == "foo");
+        assert!(cfg.fetch_interval == 5);
     }
-}
-
-#[derive(Debug, PartialEq)]
-pub enum CurrentView {
-    Main,
-    ProfileSwitcher,
-}
 
-impl App {
-    pub fn new() -> color_eyre::Result<Self> {
-        let mut config_path = home::home_dir().ok_or_else(|| eyre!("Could not determine home directory"))?;
-        config_path.push(".config/aleph-tui.toml");
+    #[test]
+    fn test_de_auth() {
+        let raw = r#"
+        default = "one"
+        [profiles.one]
+        url = "url1"
+        token = "token1"
+        api_key = "key1"
+        [profiles.two]
+        url = "url2"
+        token = "token2"
+        [profiles.with_email]
+        url = "url3"
+        email = "analyst@example.org"
+        "#;
+        let cfg: Config = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.profiles[0].auth, Auth::ApiKey("key1".to_string()));
+        assert_eq!(cfg.profiles[1].auth, Auth::Token("token2".to_string()));
+        assert_eq!(
+            cfg.profiles[2].auth,
+            Auth::Login {
+                email: "analyst@example.org".to_string(),
+                token: None
+            }
+        );
 
-        let config = read_to_string(&config_path)
-            .map_err(|e| eyre!("Failed to read config file at {}: {}", config_path.display(), e))?;
+        let raw = r#"
+        [profiles.one]
+        url = "url1"
+        "#;
+        assert!(toml::from_str::<Config>(raw).is_err());
+    }
 
-        let config: Config = toml::from_str(&config)
-            .map_err(|e| eyre!("Failed to parse config file: {}", e))?;
+    #[test]
+    fn test_de_client() {
+        let raw = r#"
+        [profiles.one]
+        url = "url1"
+        token = "token1"
+        timeout_secs = 120
+        ca_cert = "/etc/ssl/private-ca.pem"
+        client_cert = "/etc/ssl/aleph-tui.pem"
+        client_key = "/etc/ssl/aleph-tui.key"
+        accept_invalid_certs = true
+        headers = { "X-Auth-Token" = "proxy-token" }
+        [profiles.two]
+        url = ["url2", "url3"]
+        token = "token2"
+        "#;
+        let cfg: Config = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.profiles[0].urls, ["url1"]);
+        assert_eq!(cfg.profiles[1].urls, ["url2", "url3"]);
+        assert_eq!(
+            cfg.profiles[0].client,
+            ClientOptions {
+                timeout: std::time::Duration::from_secs(120),
+                ca_cert: Some(PathBuf::from("/etc/ssl/private-ca.pem")),
+                client_cert: Some((
+                    PathBuf::from("/etc/ssl/aleph-tui.pem"),
+                    PathBuf::from("/etc/ssl/aleph-tui.key")
+                )),
+                accept_invalid_certs: true,
+                headers: vec![("X-Auth-Token".to_string(), "proxy-token".to_string())],
+            }
+        );
+        assert_eq!(cfg.profiles[1].client, ClientOptions::default());
+    }
 
-        let current_profile = config
-            .profiles
+    #[test]
+    fn test_cycle_sort() {
+        let mut app = test_app("");
+        app.status = test_status();
+        let failed = |app: &App| {
+            app.status
+                .results
+                .iter()
+                .map(|r| r.failed)
+                .collect::<Vec<_>>()
+        };
+        let position = SortColumn::ALL
             .iter()
-            .find(|p| p.name == config.default)
-            .ok_or_else(|| eyre!("Default profile '{}' not found in configuration", config.default))?;
-        let last_fetch = Local::now();
-
-        Ok(Self {
-            status: Status::default(),
-            config: config.clone(),
-            current_profile: current_profile.index,
-            should_quit: false,
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            error_message: String::default(),
-            collection_tablestate: TableState::default(),
-            current_view: CurrentView::Main,
-            profile_tablestate: TableState::default(),
-            last_fetch,
-            metadata: Metadata::default(),
-            is_fetching: false,
-        })
+            .position(|c| *c == SortColumn::Failed);
+        for _ in 0..=position.unwrap() {
+            app.cycle_sort();
+        }
+        assert_eq!(app.sort_column, Some(SortColumn::Failed));
+        assert!(failed(&app).is_sorted());
+        app.reverse_sort();
+        assert!(failed(&app).iter().rev().is_sorted());
+        // Past the last column, the order of Aleph is restored.
+        for _ in position.unwrap()..SortColumn::ALL.len() {
+            app.cycle_sort();
+        }
+        assert_eq!(app.sort_column, None);
     }
 
-    pub(crate) async fn fetch(&mut self) -> color_eyre::Result<()> {
-        self.is_fetching = true;
-        let client = reqwest::Client::new();
-        let auth_header = format!("Bearer {}", self.current_profile().token);
+    #[test]
+    fn test_details_height() {
+        let mut app = test_app("");
+        app.state.details_height = Some(0);
+        assert_eq!(app.details_height(), *DETAILS_HEIGHTS.start());
+        app.state.details_height = Some(u16::MAX);
+        assert_eq!(app.details_height(), *DETAILS_HEIGHTS.end());
+    }
 
-        let url = format!(
-            "{}/api/2/status",
-            self.config.profiles[self.current_profile].url
-        );
-        let status = client
-            .get(url)
-            .header(AUTHORIZATION, auth_header.to_string())
-            .header(
-                reqwest::header::USER_AGENT,
-                format!("aleph-tui/{}", self.version),
-            )
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        self.status = status;
-
-        let url = format!(
-            "{}/api/2/metadata",
-            self.config.profiles[self.current_profile].url
-        );
-        let metadata = client
-            .get(url)
-            .header(AUTHORIZATION, auth_header)
-            .header(
-                reqwest::header::USER_AGENT,
-                format!("aleph-tui/{}", self.version),
-            )
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        self.metadata = metadata;
+    #[test]
+    fn test_merge_duplicates() {
+        let mut app = test_app("merge_duplicates = true");
+        let mut status = test_status();
+        let first = status.results[0].clone();
+        status.results.push(first.clone());
+        status.total = 10;
+        app.update_status(status, 0);
 
-        self.error_message = "".to_string();
-        self.is_fetching = false;
-        Ok(())
+        assert_eq!(app.status.total, 9);
+        assert_eq!(app.status.results.len(), 3);
+        let merged = &app.status.results[0];
+        assert_eq!(merged.key(), first.key());
+        assert_eq!(merged.todo, first.todo * 2);
+        assert_eq!(merged.succeeded, first.succeeded * 2);
+        assert_eq!(merged.batches.len(), first.batches.len() * 2);
     }
 
-    pub fn current_profile(&self) -> Profile {
-        self.config.profiles[self.current_profile].clone()
+    #[test]
+    fn test_toggle_expanded() {
+        let mut app = test_app("");
+        app.status = test_status();
+        assert_eq!(app.row_count(), 3);
+        app.collection_tablestate.select(Some(0));
+        app.toggle_expanded();
+        assert_eq!(app.row_count(), 5);
+        // Collapsing from a task row selects the collection again.
+        app.collection_tablestate.select(Some(2));
+        assert!(app.selected_task().is_some());
+        app.toggle_expanded();
+        assert_eq!(app.row_count(), 3);
+        assert_eq!(app.collection_tablestate.selected(), Some(0));
+
+        // Tasks matching the search are shown either way.
+        app.set_search("index".to_string());
+        assert_eq!(app.row_count(), 2);
     }
 
-    pub fn toggle_profile_selector(&mut self) {
-        self.current_view = match self.current_view {
-            CurrentView::Main => CurrentView::ProfileSwitcher,
-            CurrentView::ProfileSwitcher => CurrentView::Main,
+    #[test]
+    fn test_links() {
+        let mut app = test_app("");
+        app.status = test_status();
+        app.collection_tablestate.select(Some(0));
+        assert_eq!(app.links()[0], ("Web interface", "http://x/datasets/94"));
+        app.toggle_links();
+        app.link_down();
+        app.copy_link();
+        assert_eq!(
+            app.clipboard.as_deref(),
+            Some("http://x/api/2/collections/94")
+        );
+        for _ in 0..5 {
+            app.link_down();
         }
+        assert_eq!(app.link_list.as_ref().unwrap().selected(), Some(3));
+        app.toggle_links();
+        assert!(!app.shows_links());
     }
 
-    pub fn show_profile_selector(&self) -> bool {
-        self.current_view == CurrentView::ProfileSwitcher
+    #[test]
+    fn test_esc() {
+        use crossterm::event::{KeyCode, KeyEvent};
+        let (replies, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = test_app("");
+        app.status = test_status();
+        app.collection_tablestate.select(Some(0));
+        let esc = |app: &mut App| {
+            crate::update::update(app, KeyEvent::from(KeyCode::Esc), &replies);
+            !app.should_quit
+        };
+
+        app.set_search("94".to_string());
+        app.toggle_zoom();
+        app.toggle_links();
+        app.toggle_help();
+        assert!(esc(&mut app) && !app.show_help);
+        assert!(esc(&mut app) && !app.shows_links());
+        assert!(esc(&mut app) && !app.is_zoomed());
+        assert!(esc(&mut app) && app.search.is_empty());
+        assert!(!esc(&mut app));
     }
 
-    pub fn set_profile(&mut self, profile: String) -> color_eyre::Result<()> {
-        let p = self.config.profiles.iter().find(|p| p.name == profile);
-        match p {
-            Some(p) => {
-                self.profile_tablestate.select(Some(p.index));
-                self.current_profile = p.index;
-                Ok(())
-            }
-            None => Err(eyre!("Profile '{:?}' not found", profile)),
-        }
+    #[test]
+    fn test_stable_selection() {
+        let mut app = test_app("");
+        let status = test_status();
+        app.update_status(status.clone(), 0);
+        app.expanded.insert("94".to_string());
+        app.collection_tablestate.select(Some(2));
+        let task = app.selected_task().map(|task| task.name.clone());
+
+        let mut reordered = status.clone();
+        reordered.results.reverse();
+        app.update_status(reordered, 0);
+        assert_eq!(app.selected_result().map(|r| r.key()), Some("94"));
+        assert_eq!(app.selected_task().map(|task| task.name.clone()), task);
+
+        // Once it is gone, the selection only stays within the table.
+        let mut shrunk = status;
+        shrunk.results.retain(|result| result.key() != "94");
+        app.update_status(shrunk, 0);
+        assert_eq!(app.collection_tablestate.selected(), Some(1));
     }
 
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+    #[test]
+    fn test_notify_when_done() {
+        let mut app = test_app("");
+        app.notify_when_done.insert("94".to_string());
+        let event = |kind| timeline::CollectionEvent {
+            kind,
+            key: "94".to_string(),
+            foreign_id: "fid94".to_string(),
+            label: "Chris".to_string(),
+            succeeded: 10,
+            failed: 0,
+        };
+        app.record_events(&[event(EventKind::Started)]);
+        assert!(!app.ring_bell);
+        app.record_events(&[event(EventKind::Completed)]);
+        assert!(app.ring_bell && app.is_flashing());
+        assert!(app.toasts.has_sticky());
+        // Only once.
+        assert!(app.notify_when_done.is_empty());
     }
 
-    pub(crate) fn profile_down(&mut self) {
-        if self.current_profile().index < self.config.profiles.len()
-            && self
-                .config
-                .profiles
-                .get(self.current_profile().index + 1)
-                .is_some()
-        {
-            self.current_profile += 1;
-            self.clear_state();
-        }
+    #[test]
+    fn test_prune_idle() {
+        let now = Local::now();
+        let mut collections = HashMap::from([
+            ("fresh".to_string(), CollectionState::new(now)),
+            (
+                "idle".to_string(),
+                CollectionState::new(now - Duration::hours(30)),
+            ),
+            (
+                "watched".to_string(),
+                CollectionState::new(now - Duration::hours(30)),
+            ),
+        ]);
+        prune_idle(&mut collections, now, Duration::hours(24), |key| {
+            key == "watched"
+        });
+        let mut keys: Vec<_> = collections.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["fresh", "watched"]);
     }
 
-    pub(crate) fn profile_up(&mut self) {
-        if self.current_profile().index > 0
-            && self
-                .config
-                .profiles
-                .get(self.current_profile().index - 1)
-                .is_some()
-        {
-            self.current_profile -= 1;
-            self.clear_state();
-        }
+    #[test]
+    fn test_de_error_alert() {
+        let cfg: Config = toml::from_str(r#"error_alert = "both""#).unwrap();
+        assert_eq!(cfg.error_alert, Some(ErrorAlert::Both));
+        assert!(toml::from_str::<Config>(r#"error_alert = "siren""#).is_err());
     }
 
-    pub(crate) fn collection_up(&mut self) {
-        let index = self.collection_tablestate.selected().unwrap_or_default();
-        if index > 0 {
-            self.collection_tablestate.select(Some(index - 1));
-        }
+    #[test]
+    fn test_de_retry() {
+        let cfg: Config = toml::from_str("retries = 4\nretry_backoff = 0.25").unwrap();
+        assert_eq!(cfg.retry.count, 4);
+        assert_eq!(cfg.retry.backoff, std::time::Duration::from_millis(250));
+        let cfg: Config = toml::from_str("").unwrap();
+        assert_eq!(cfg.retry, Retry::default());
     }
 
-    pub(crate) fn collection_down(&mut self) {
-        let index = self.collection_tablestate.selected().unwrap_or_default();
+    #[test]
+    fn test_de_display() {
+        let raw = r#"
+        [display]
+        separator = "."
+        group_from = 10000
+        ungrouped_columns = ["doing", "effective_todo"]
+        "#;
+        let cfg: Config = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.number_format.separator, ".");
+        assert_eq!(cfg.number_format.group_from, 10_000);
+        assert_eq!(
+            cfg.number_format.ungrouped,
+            [SortColumn::Doing, SortColumn::EffectiveTodo]
+        );
 
-        // Calculate total number of rows (collections + tasks)
-        let total_rows = self.status.results.iter().map(|result| {
-            1 + result.batches.iter().map(|batch| {
-                batch.queues.iter().map(|queue| queue.tasks.len()).sum::<usize>()
-            }).sum::<usize>()
-        }).sum::<usize>();
+        let raw = r#"display = { ungrouped_columns = ["label"] }"#;
+        assert!(toml::from_str::<Config>(raw).is_err());
+    }
 
-        if index < total_rows.saturating_sub(1) {
-            self.collection_tablestate.select(Some(index + 1));
+    #[test]
+    fn test_throughput() {
+        let mut state = CollectionState::new(Local::now());
+        let now = Local::now();
+        for done in [10, 15, 15, 40] {
+            state.record(done, now);
+        }
+        let counts = |series: Vec<(DateTime<Local>, u64)>| -> Vec<u64> {
+            series.into_iter().map(|(_, count)| count).collect()
+        };
+        assert_eq!(counts(state.throughput()), [5, 0, 25]);
+        assert_eq!(state.recent_throughput(2), None);
+
+        // Fetches 20 seconds apart, then a 9 minute gap.
+        let mut state = CollectionState::new(now);
+        for (seconds, done) in [(0, 0), (20, 10), (40, 20), (60, 40), (80, 50), (600, 490)] {
+            state.record(done, now + Duration::seconds(seconds));
+        }
+        assert_eq!(counts(state.throughput_per_minute()), [40, 50]);
+        assert_eq!(
+            state.throughput_per_minute()[1].0,
+            now + Duration::seconds(600)
+        );
+        assert_eq!(state.recent_throughput(2), Some(50.0));
+        assert_eq!(state.recent_throughput(10), Some(49.0));
+        // Only the last hour is kept.
+        state.record(700, now + Duration::minutes(61));
+        assert_eq!(state.history.len(), 4);
+
+        for done in 0..HISTORY_LEN as u64 {
+            state.record(done, now);
         }
+        assert_eq!(state.history.len(), HISTORY_LEN);
     }
 
-    fn clear_state(&mut self) {
-        self.status = Status::default();
-        self.metadata = Metadata::default();
-        self.error_message = String::default();
+    #[test]
+    fn test_stuck_tasks() {
+        let status = test_status();
+        let mut result = status.results[0].clone();
+        let start = Local::now();
+        let mut state = CollectionState::new(start);
+        state.record_tasks(&result, start);
+        let later = start + STUCK_TASK_AFTER;
+        // The ingest task finishes some, the analyze task doesn't.
+        result.batches[0].queues[0].tasks[0].succeeded += 1;
+        state.record_tasks(&result, later);
+        let stuck: Vec<_> = result
+            .tasks()
+            .map(|task| state.stuck_for(task, later))
+            .collect();
+        assert_eq!(stuck, [None, Some(STUCK_TASK_AFTER)]);
+
+        // Working on less counts as progress too.
+        result.batches[0].queues[0].tasks[1].doing += 1;
+        state.record_tasks(&result, later + Duration::minutes(1));
+        result.batches[0].queues[0].tasks[1].doing -= 1;
+        state.record_tasks(&result, later + Duration::minutes(2));
+        let task = result.tasks().nth(1).unwrap();
+        assert_eq!(state.stuck_for(task, later + STUCK_TASK_AFTER), None);
     }
 
-    pub(crate) fn print_version(&self) {
-        println!("aleph-tui {}", self.version);
+    #[test]
+    fn test_rolling_average() {
+        let start = Local::now();
+        let samples: VecDeque<_> = [(0, 0), (1, 30), (2, 10), (10, 40)]
+            .into_iter()
+            .map(|(minutes, count)| (start + Duration::minutes(minutes), count))
+            .collect();
+        let averages: Vec<_> = [1, 5, 15]
+            .map(|window| rolling_average(&samples, Duration::minutes(window)))
+            .to_vec();
+        assert_eq!(averages, [Some(40.0), Some(8.0), Some(8.0)]);
+        assert_eq!(
+            rolling_average(&samples.range(..1).copied().collect(), Duration::minutes(1)),
+            None
+        );
     }
 
-    pub(crate) fn print_help(&self) {
-        println!("aleph-tui");
+    #[test]
+    fn test_de_fetch_intervals() {
+        let raw = r#"
+        default = "one"
+        fetch_interval = 2
+        unfocused_fetch_interval = 60
+
+        [profiles]
+            [profiles.one]
+            url = "url1"
+            token = "token1"
+        "#;
+
+        let cfg: Config = toml::from_str(raw).unwrap();
+        assert!(cfg.fetch_interval == 2);
+        assert!(cfg.unfocused_fetch_interval == 60);
+        assert!(!cfg.fetch_all_profiles);
+        assert!(!cfg.remember_fetch_interval);
+    }
+
+    #[test]
+    fn test_adjust_fetch_interval() {
+        let mut app = test_app(
+            r#"
+            fetch_interval = 4
+            remember_fetch_interval = true
+            "#,
+        );
+        app.adjust_fetch_interval(true);
+        assert_eq!(app.fetch_interval(), 3);
+        assert_eq!(app.state.fetch_interval, Some(3));
+        for _ in 0..3 {
+            app.adjust_fetch_interval(true);
+        }
+        assert_eq!(app.fetch_interval(), 1);
+        app.adjust_fetch_interval(false);
+        assert_eq!(app.fetch_interval(), 2);
+        assert!(app.is_fetch_interval_adjusted());
+        // Unfocused, the adjusted interval counts like the configured one.
+        app.focused = false;
+        assert_eq!(app.fetch_interval(), 30);
+    }
+
+    #[test]
+    fn test_load_status_file() {
+        let mut app = test_app(
+            r#"
+            [profiles.two]
+            url = "url2"
+            token = "token2"
+            "#,
+        );
+        app.load_status_file(
+            Path::new("testdata/status.json"),
+            Some(Path::new("testdata/metadata.json")),
+        )
+        .unwrap();
+        assert!(!app.status.results.is_empty());
+        assert!(app.metadata.app.version.is_some());
+
+        // Switching would leave nothing to show.
+        app.cycle_profile(true);
+        assert_eq!(app.current_profile, 0);
+        assert!(!app.status.results.is_empty());
+
+        let missing = app.load_status_file(Path::new("testdata/missing.json"), None);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_switch_to_fetched_profile() {
+        let mut app = test_app(
+            r#"
+            fetch_all_profiles = true
+            [profiles.two]
+            url = "url2"
+            token = "token2"
+            "#,
+        );
+        // Told apart by their totals.
+        let raw = read_to_string("testdata/status.json").unwrap();
+        let status = |total| Status {
+            total,
+            ..serde_json::from_str(&raw).unwrap()
+        };
+        let snapshot = |profile, total| Snapshot {
+            profile,
+            status: Ok(FetchedStatus::Changed(
+                status(total),
+                0,
+                Validators::default(),
+            )),
+            metadata: Ok(Metadata::default()),
+            requests: Vec::new(),
+            url: format!("url{}", profile + 1),
+        };
+        app.apply_snapshot(snapshot(0, 1));
+        let (now, interval) = (Local::now(), Duration::seconds(5));
+        // Profile 1 is due half an interval after the first check.
+        assert!(app.scheduler.start(now, interval, 0, false).is_empty());
+        assert_eq!(app.scheduler.start(now + interval, interval, 0, false), [1]);
+        app.apply_snapshot(snapshot(1, 2));
+        assert_eq!(app.status.total, 1);
+        assert!(!app.scheduler.finished(1, now, interval));
+
+        let (fetched, collections) = (app.last_fetch, app.collections.len());
+        assert!(collections > 0);
+        app.switch_profile(1);
+        assert_eq!(app.status.total, 2);
+        assert!(app.collections.is_empty());
+        app.switch_profile(0);
+        assert_eq!(app.status.total, 1);
+        assert_eq!(app.last_fetch, fetched);
+        assert_eq!(app.collections.len(), collections);
+        assert!(app.profile_statuses.contains_key(&1));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let server = Server::start(|_| Response::new(429, "").header("Retry-After", "90")).await;
+        let mut app = test_app_at(&format!("\"{}\"", server.url), r#"error_alert = "both""#);
+        fetch(&mut app).await;
+        let delay = app.rate_limited_for(Local::now()).unwrap();
+        assert!(delay > Duration::seconds(85) && delay <= Duration::seconds(90));
+        // Not worth an alert, fetches resume by themselves.
+        assert!(!app.fetch_failing && !app.is_flashing());
+        assert_eq!(app.notifications.iter().count(), 0);
+
+        let server = Server::start(|_| Response::new(429, "")).await;
+        app.config.profiles[0].urls = vec![server.url.clone()];
+        // Without Retry-After.
+        fetch(&mut app).await;
+        let delay = app.rate_limited_for(Local::now()).unwrap();
+        assert!(delay > RATE_LIMIT_DELAY - Duration::seconds(5) && delay <= RATE_LIMIT_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_fall_back() {
+        let server = Server::start(|request| {
+            let file = match request.path.as_str() {
+                "/api/2/metadata" => "testdata/metadata.json",
+                _ => "testdata/status.json",
+            };
+            Response::json(&read_to_string(file).unwrap())
+        })
+        .await;
+        let unreachable = unreachable_url().await;
+        let urls = format!("[\"{unreachable}\", \"{}\"]", server.url);
+        let mut app = test_app_at(&urls, "retries = 0");
+        fetch(&mut app).await;
+        assert!(!app.fetch_failing);
+        assert!(!app.status.results.is_empty());
+        assert_eq!(app.metadata.app.title.as_deref(), Some("OCCRP Aleph"));
+        let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+        assert!(paths[0].starts_with("/api/2/status"));
+        assert_eq!(paths[1], "/api/2/metadata");
+        // The next fetch starts with the URL which could be connected to.
+        assert_eq!(app.url(0), server.url);
+    }
+
+    #[tokio::test]
+    async fn test_auto_select_fall_back() {
+        let server = Server::start(|_| Response::json("{}")).await;
+        let unreachable = unreachable_url().await;
+        let mut app = test_app_at(
+            &format!("\"{unreachable}\""),
+            &format!(
+                "auto_select = \"reachable\"\n\
+                 [profiles.two]\nurl = [\"{unreachable}\", \"{}\"]\ntoken = \"token2\"",
+                server.url
+            ),
+        );
+        app.auto_select_profile().await;
+        assert_eq!(app.current_profile, 1);
+        // Fetched from the URL which responded.
+        assert_eq!(app.url(1), server.url);
+        assert_eq!(server.requests()[0].path, "/api/2/metadata");
+    }
+
+    #[tokio::test]
+    async fn test_compare_profiles() {
+        let server =
+            Server::start(|_| Response::json(&read_to_string("testdata/metadata.json").unwrap()))
+                .await;
+        let url = format!("\"{}\"", server.url);
+        let mut app = test_app_at(
+            &url,
+            &format!("[profiles.two]\nurl = {url}\ntoken = \"token2\""),
+        );
+        let (replies, mut received) = tokio::sync::mpsc::unbounded_channel();
+        app.compare_profiles(&replies);
+        assert_eq!(app.compare_with, Some(0));
+        app.switch_profile(1);
+        app.compare_profiles(&replies);
+        // Shown once the reply arrives.
+        assert!(app.metadata_diff.is_none());
+        app.apply_reply(received.recv().await.unwrap());
+        assert!(app.metadata_diff.is_some());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_failures() {
+        // The first attempt fails like a load balancer in a hiccup.
+        let failed = std::sync::atomic::AtomicBool::new(false);
+        let server =
+            Server::start(
+                move |request| match request.path.starts_with("/api/2/entities") {
+                    true if !failed.swap(true, std::sync::atomic::Ordering::Relaxed) => {
+                        Response::new(502, "")
+                    }
+                    true => Response::json(
+                        r#"{"results": [{"id": "a1", "schema": "Pages", "properties":
+                {"processingError": ["TimeoutError: 1s"]}}], "total": 1}"#,
+                    ),
+                    false => Response::new(404, ""),
+                },
+            )
+            .await;
+        let mut app = test_app_at(&format!("\"{}\"", server.url), "retry_backoff = 0");
+        app.status = test_status();
+        app.collection_tablestate.select(Some(0));
+        let (replies, mut received) = tokio::sync::mpsc::unbounded_channel();
+        app.fetch_failures(&replies);
+        assert!(app.failure_report.is_none());
+        app.apply_reply(received.recv().await.unwrap());
+        let report = app.failure_report.as_ref().unwrap();
+        assert_eq!(
+            (report.key.as_str(), report.summary()),
+            ("94", "TimeoutError ×1".to_string())
+        );
+        assert_eq!(server.requests().len(), 2);
+        let request = &server.requests()[1];
+        assert!(
+            request.path.contains("filter%3Acollection_id=94"),
+            "{}",
+            request.path
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_xref() {
+        let server = Server::start(|_| {
+            Response::json(
+                r#"{"results": [{"score": 0.9, "judgement": "positive"},
+                {"score": 0.5, "judgement": "no_judgement"}], "total": 7}"#,
+            )
+        })
+        .await;
+        let mut app = test_app_at(&format!("\"{}\"", server.url), "");
+        app.status = test_status();
+        app.collection_tablestate.select(Some(0));
+        let (replies, mut received) = tokio::sync::mpsc::unbounded_channel();
+        app.fetch_xref(&replies);
+        assert!(app.xref_summary.is_none());
+        app.apply_reply(received.recv().await.unwrap());
+        let summary = app.xref_summary.as_ref().unwrap();
+        assert_eq!(
+            (summary.total, summary.sampled, summary.positive),
+            (7, 2, 1)
+        );
+        assert_eq!(app.details_tab, DetailsTab::Xref);
+        assert!(server.requests()[0]
+            .path
+            .starts_with("/api/2/collections/94/xref?"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_login() {
+        // Not retried, even though a 502 usually is.
+        let server = Server::start(|_| Response::new(502, "")).await;
+        let mut app = test_app_at(&format!("\"{}\"", server.url), "");
+        app.config.profiles[0].auth = Auth::Login {
+            email: "analyst@example.org".to_string(),
+            token: None,
+        };
+        app.start_login();
+        app.login_prompt.as_mut().unwrap().password = "secret".to_string();
+        let (replies, mut received) = tokio::sync::mpsc::unbounded_channel();
+        app.stop_login(true, &replies);
+        assert!(app.login_prompt.is_none());
+        app.apply_reply(received.recv().await.unwrap());
+        let notification = app.notifications.iter().next().unwrap();
+        assert!(notification.message.starts_with("Failed to log in to one"));
+        assert!(!app.fetch_requested);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/api/2/sessions/login")
+        );
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["email"], "analyst@example.org");
+        assert_eq!(body["password"], "secret");
+    }
+
+    #[tokio::test]
+    async fn test_maintenance() {
+        // Metadata claiming maintenance or not, and a failing status.
+        let serve = |maintenance: bool| {
+            let metadata = read_to_string("testdata/metadata.json").unwrap().replace(
+                "\"maintenance\": false",
+                &format!("\"maintenance\": {maintenance}"),
+            );
+            move |request: &Request| match request.path.as_str() {
+                "/api/2/metadata" => Response::json(&metadata),
+                _ => Response::new(500, "Internal Server Error"),
+            }
+        };
+        let server = Server::start(serve(true)).await;
+        let config = "error_alert = \"both\"\nunfocused_fetch_interval = 60";
+        let mut app = test_app_at(&format!("\"{}\"", server.url), config);
+        fetch(&mut app).await;
+        assert!(app.metadata.maintenance);
+        assert!(!app.fetch_failing && !app.is_flashing());
+        assert_eq!(app.notifications.iter().count(), 0);
+        assert_eq!(app.fetch_interval(), 60);
+
+        let server = Server::start(serve(false)).await;
+        app.config.profiles[0].urls = vec![server.url.clone()];
+        fetch(&mut app).await;
+        assert!(app.fetch_failing && app.is_flashing());
+        assert_eq!(app.notifications.iter().count(), 1);
+        assert_eq!(app.fetch_interval(), app.focused_fetch_interval());
+    }
+
+    #[test]
+    fn test_de_auto_select() {
+        let raw = r#"
+        default = "one"
+        auto_select = "active"
+        "#;
+        let cfg: Config = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.auto_select, Some(AutoSelect::Active));
+
+        let raw = r#"auto_select = "fastest""#;
+        assert!(toml::from_str::<Config>(raw).is_err());
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CurrentView {
+    Main,
+    ProfileSwitcher,
+}
+
+/// Tabs of the details pane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DetailsTab {
+    Overview,
+    Tasks,
+    Errors,
+    Metadata,
+    Xref,
+    History,
+}
+
+impl DetailsTab {
+    pub const ALL: [DetailsTab; 6] = [
+        DetailsTab::Overview,
+        DetailsTab::Tasks,
+        DetailsTab::Errors,
+        DetailsTab::Metadata,
+        DetailsTab::Xref,
+        DetailsTab::History,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DetailsTab::Overview => "Overview",
+            DetailsTab::Tasks => "Tasks",
+            DetailsTab::Errors => "Errors",
+            DetailsTab::Metadata => "Metadata",
+            DetailsTab::Xref => "Xref",
+            DetailsTab::History => "History",
+        }
+    }
+}
+
+/// The task counters reported per collection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Counter {
+    Todo,
+    Doing,
+    Succeeded,
+    Failed,
+    Aborted,
+    Aborting,
+    Cancelled,
+}
+
+impl Counter {
+    /// All counters, in the order they appear in the table. Quick filter
+    /// hotkeys `1` to `7` follow this order.
+    pub const ALL: [Counter; 7] = [
+        Counter::Todo,
+        Counter::Doing,
+        Counter::Succeeded,
+        Counter::Failed,
+        Counter::Aborted,
+        Counter::Aborting,
+        Counter::Cancelled,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Counter::Todo => "todo",
+            Counter::Doing => "doing",
+            Counter::Succeeded => "succeeded",
+            Counter::Failed => "failed",
+            Counter::Aborted => "aborted",
+            Counter::Aborting => "aborting",
+            Counter::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn value(&self, result: &StatusResult) -> u64 {
+        match self {
+            Counter::Todo => result.todo,
+            Counter::Doing => result.doing,
+            Counter::Succeeded => result.succeeded,
+            Counter::Failed => result.failed,
+            Counter::Aborted => result.aborted,
+            Counter::Aborting => result.aborting,
+            Counter::Cancelled => result.cancelled,
+        }
+    }
+}
+
+/// Columns of the collection table which the results can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortColumn {
+    CollectionId,
+    ForeignId,
+    Label,
+    StartTime,
+    Todo,
+    EffectiveTodo,
+    Doing,
+    Succeeded,
+    Failed,
+    Aborted,
+    Aborting,
+    Cancelled,
+    Health,
+    Progress,
+}
+
+impl SortColumn {
+    /// All columns, in the order they appear in the table.
+    pub const ALL: [SortColumn; 14] = [
+        SortColumn::CollectionId,
+        SortColumn::ForeignId,
+        SortColumn::Label,
+        SortColumn::StartTime,
+        SortColumn::Todo,
+        SortColumn::EffectiveTodo,
+        SortColumn::Doing,
+        SortColumn::Succeeded,
+        SortColumn::Failed,
+        SortColumn::Aborted,
+        SortColumn::Aborting,
+        SortColumn::Cancelled,
+        SortColumn::Health,
+        SortColumn::Progress,
+    ];
+
+    /// Compares two results by this column. The health score isn't part of
+    /// the status, so it is passed in.
+    fn compare(
+        &self,
+        a: &StatusResult,
+        b: &StatusResult,
+        health: impl Fn(&StatusResult) -> u8,
+    ) -> Ordering {
+        match self {
+            SortColumn::CollectionId => {
+                let id = |r: &StatusResult| {
+                    r.collection
+                        .as_ref()
+                        .and_then(|c| c.collection_id.parse::<u64>().ok())
+                };
+                id(a).cmp(&id(b))
+            }
+            SortColumn::ForeignId => {
+                let foreign_id =
+                    |r: &StatusResult| r.collection.as_ref().map(|c| c.foreign_id.clone());
+                foreign_id(a).cmp(&foreign_id(b))
+            }
+            SortColumn::Label => {
+                let label = |r: &StatusResult| match &r.collection {
+                    Some(c) => c.label.to_lowercase(),
+                    None => r.name.to_lowercase(),
+                };
+                label(a).cmp(&label(b))
+            }
+            SortColumn::StartTime => a.min_ts.cmp(&b.min_ts),
+            SortColumn::Todo => a.todo.cmp(&b.todo),
+            SortColumn::EffectiveTodo => a.effective_todo().cmp(&b.effective_todo()),
+            SortColumn::Doing => a.doing.cmp(&b.doing),
+            SortColumn::Succeeded => a.succeeded.cmp(&b.succeeded),
+            SortColumn::Failed => a.failed.cmp(&b.failed),
+            SortColumn::Aborted => a.aborted.cmp(&b.aborted),
+            SortColumn::Aborting => a.aborting.cmp(&b.aborting),
+            SortColumn::Cancelled => a.cancelled.cmp(&b.cancelled),
+            SortColumn::Health => health(a).cmp(&health(b)),
+            SortColumn::Progress => a.progress().cmp(&b.progress()),
+        }
+    }
+}
+
+/// `~/.config/aleph-tui.toml`.
+pub fn config_path() -> color_eyre::Result<PathBuf> {
+    let home = home::home_dir().ok_or_else(|| eyre!("Could not determine home directory"))?;
+    Ok(home.join(".config/aleph-tui.toml"))
+}
+
+impl App {
+    pub fn new() -> color_eyre::Result<Self> {
+        let config_path = config_path()?;
+        let config = read_to_string(&config_path).map_err(|e| {
+            eyre!(
+                "Failed to read config file at {}: {}",
+                config_path.display(),
+                e
+            )
+        })?;
+
+        let config: Config =
+            toml::from_str(&config).map_err(|e| eyre!("Failed to parse config file: {}", e))?;
+        let mut app = Self::from_config(config)?;
+        app.load_state();
+        app.load_sessions();
+        Ok(app)
+    }
+
+    /// Takes over the session tokens of the profiles which log in with a
+    /// password, see [`Auth::Login`].
+    fn load_sessions(&mut self) {
+        let Some(path) = Sessions::default_path() else {
+            return;
+        };
+        match Sessions::load(&path) {
+            Ok(sessions) => {
+                for profile in &mut self.config.profiles {
+                    if let Auth::Login { token, .. } = &mut profile.auth {
+                        *token = sessions.tokens.get(&profile.name).cloned();
+                    }
+                }
+            }
+            Err(e) => self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to read sessions: {e}"),
+            ),
+        }
+    }
+
+    /// Keeps the session token of `profile` for the next start.
+    fn save_session(&mut self, profile: &str, token: String) {
+        let Some(path) = Sessions::default_path() else {
+            return;
+        };
+        let saved = Sessions::load(&path).and_then(|mut sessions| {
+            sessions.tokens.insert(profile.to_string(), token);
+            sessions.save(&path)
+        });
+        if let Err(e) = saved {
+            self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to save the session: {e}"),
+            );
+        }
+    }
+
+    /// Reads the state of previous sessions. If that fails, the state is
+    /// left alone rather than overwritten later.
+    fn load_state(&mut self) {
+        let Some(path) = self.config.state_file.clone().or_else(State::default_path) else {
+            return;
+        };
+        match State::load(&path) {
+            Ok(mut state) => {
+                state.prune(Local::now());
+                self.state = state;
+                self.state_file = Some(path);
+            }
+            Err(e) => self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to read state: {e}"),
+            ),
+        }
+    }
+
+    /// Picks up changes other sessions sharing the state file made to it.
+    fn reload_state(&mut self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        match State::load(path) {
+            Ok(mut state) => {
+                state.prune(Local::now());
+                self.state = state;
+            }
+            Err(e) => self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to read state: {e}"),
+            ),
+        }
+    }
+
+    fn save_state(&mut self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        if let Err(e) = self.state.save(path) {
+            self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to write state to {}: {e}", path.display()),
+            );
+        }
+    }
+
+    pub fn from_config(config: Config) -> color_eyre::Result<Self> {
+        let current_profile = config
+            .profiles
+            .iter()
+            .find(|p| p.name == config.default)
+            .ok_or_else(|| {
+                eyre!(
+                    "Default profile '{}' not found in configuration",
+                    config.default
+                )
+            })?;
+        let last_fetch = Local::now();
+        let clients = config
+            .profiles
+            .iter()
+            .map(|profile| {
+                profile
+                    .client
+                    .build()
+                    .map_err(|e| eyre!("Profile {}: {e}", profile.name))
+            })
+            .collect::<color_eyre::Result<_>>()?;
+        let hosts = config
+            .profiles
+            .iter()
+            .map(|profile| Scheduler::host(&profile.urls[0]))
+            .collect();
+
+        Ok(Self {
+            status: Status::default(),
+            config: config.clone(),
+            current_profile: current_profile.index,
+            should_quit: false,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            notifications: Notifications::default(),
+            toasts: Toasts::default(),
+            collection_tablestate: TableState::default(),
+            current_view: CurrentView::Main,
+            profile_tablestate: TableState::default(),
+            last_fetch,
+            metadata: Metadata::default(),
+            is_fetching: false,
+            sort_column: None,
+            sort_descending: false,
+            header_columns: Vec::new(),
+            focused: true,
+            watched: Vec::new(),
+            notify_when_done: HashSet::new(),
+            adjusted_fetch_interval: None,
+            sleep_inhibitor: SleepInhibitor::default(),
+            version_changes: Vec::new(),
+            failure_report: None,
+            failed_documents: None,
+            link_list: None,
+            xref_summary: None,
+            metadata_diff: None,
+            compare_with: None,
+            counter_filter: None,
+            details_tab: DetailsTab::Overview,
+            collections: HashMap::new(),
+            request_log: RequestLog::default(),
+            show_request_log: false,
+            show_help: false,
+            theme: Theme::default(),
+            profile_tablestates: HashMap::new(),
+            profile_statuses: HashMap::new(),
+            scheduler: Scheduler::new(hosts, config.max_fetches_per_host),
+            active_urls: vec![0; config.profiles.len()],
+            status_size: None,
+            fetch_task: None,
+            fetch_progress: Progress::default(),
+            status_file: None,
+            fetch_requested: false,
+            status_page: 0,
+            zoomed: false,
+            search: String::new(),
+            is_searching: false,
+            tag_editor: None,
+            login_prompt: None,
+            upload_prompt: None,
+            upload: None,
+            group_by_tag: false,
+            fetch_failing: false,
+            ring_bell: false,
+            clipboard: None,
+            wrap_details: false,
+            details_focused: false,
+            details_scroll: 0,
+            details_max_scroll: 0,
+            flash_until: None,
+            rate_limited_until: None,
+            state: State::default(),
+            state_file: None,
+            schema_error: None,
+            destructive: HashSet::new(),
+            expanded: HashSet::new(),
+            last_tick: None,
+            last_input: Local::now(),
+            screensaver: false,
+            recent_failures: VecDeque::new(),
+            recent_progress: VecDeque::new(),
+            status_validators: None,
+            clients,
+        })
+    }
+
+    /// The API of the current profile.
+    pub fn api(&self) -> Api {
+        self.api_for(self.current_profile)
+    }
+
+    /// The URL of the profile at `index` which could last be connected to.
+    pub fn url(&self, index: usize) -> &str {
+        &self.config.profiles[index].urls[self.active_urls[index]]
+    }
+
+    pub(crate) fn api_for(&self, index: usize) -> Api {
+        let profile = &self.config.profiles[index];
+        let active = self.url(index);
+        let fallbacks = profile
+            .urls
+            .iter()
+            .filter(|url| *url != active)
+            .cloned()
+            .collect();
+        Api::new(active, profile.auth.clone(), &self.version)
+            .with_fallbacks(fallbacks)
+            .with_parsing(self.config.parsing)
+            .with_max_body_size((self.config.max_response_size.max(0.0) * 1e6) as usize)
+            .with_retry(self.config.retry)
+            .with_client(self.clients[index].clone(), profile.client.timeout)
+    }
+
+    /// Probes all profiles concurrently and switches to the first one which
+    /// is reachable or, depending on the configuration, has jobs running.
+    /// Stays with the current profile if none responds.
+    pub async fn auto_select_profile(&mut self) {
+        let Some(mode) = self.config.auto_select else {
+            return;
+        };
+        let mut probes = JoinSet::new();
+        for index in 0..self.config.profiles.len() {
+            let api = self.api_for(index);
+            let check_active = mode == AutoSelect::Active;
+            let paging = self.status_paging();
+            probes.spawn(async move { (index, api::probe(api, check_active, paging).await) });
+        }
+        let mut results = vec![None; self.config.profiles.len()];
+        while let Some(Ok((index, probe))) = probes.join_next().await {
+            self.log_requests(probe.requests);
+            results[index] = probe.active;
+            let urls = &self.config.profiles[index].urls;
+            if let Some(active) = urls.iter().position(|url| *url == probe.url) {
+                self.active_urls[index] = active;
+            }
+        }
+
+        let reachable = results.iter().position(|r| r.is_some());
+        let active = results.iter().position(|r| *r == Some(true));
+        if let Some(index) = active.or(reachable) {
+            if index != self.current_profile {
+                self.current_profile = index;
+                self.profile_tablestate.select(Some(index));
+                let message = format!("Selected profile {}", self.current_profile().name);
+                self.toast(NotificationKind::Info, message);
+            }
+        }
+    }
+
+    /// Validators of the status shown, if it was fetched with the paging
+    /// used for the next fetch.
+    pub fn status_validators(&self) -> Option<Validators> {
+        let (paging, validators) = self.status_validators.as_ref()?;
+        (*paging == self.status_paging()).then(|| validators.clone())
+    }
+
+    /// Which pages of the status to request, see [`Config::status_page_size`].
+    pub fn status_paging(&self) -> Paging {
+        match (self.config.status_page_size, self.config.merge_status_pages) {
+            (Some(limit), true) => Paging::All(limit),
+            (Some(limit), false) => Paging::Page(Page {
+                limit,
+                offset: self.status_page * limit,
+            }),
+            (None, _) => Paging::Follow,
+        }
+    }
+
+    /// Which pages of the status to request for profiles fetched in the
+    /// background: the first one, if they are shown one at a time.
+    pub fn background_paging(&self) -> Paging {
+        match self.status_paging() {
+            Paging::Page(page) => Paging::Page(Page { offset: 0, ..page }),
+            paging => paging,
+        }
+    }
+
+    /// Shows the status saved to `path`, and the metadata saved to `metadata`
+    /// if given, instead of fetching them. Nothing is fetched from then on,
+    /// so the status can be looked at as it was, e.g. to debug it.
+    pub fn load_status_file(
+        &mut self,
+        path: &Path,
+        metadata: Option<&Path>,
+    ) -> color_eyre::Result<()> {
+        let read = |path: &Path| {
+            read_to_string(path).map_err(|e| eyre!("Failed to read {}: {e}", path.display()))
+        };
+        let body = read(path)?;
+        let status = serde_json::from_str(&body)
+            .map_err(|e| eyre!("{} is not a status: {e}", path.display()))?;
+        if let Some(path) = metadata {
+            let metadata = serde_json::from_str(&read(path)?)
+                .map_err(|e| eyre!("{} is not metadata: {e}", path.display()))?;
+            self.update_metadata(metadata);
+        }
+        self.status_file = Some(path.to_path_buf());
+        self.update_status(status, body.len());
+        Ok(())
+    }
+
+    /// Number of pages of the status, if they are shown one at a time.
+    pub fn status_pages(&self) -> Option<u64> {
+        let Paging::Page(page) = self.status_paging() else {
+            return None;
+        };
+        Some(self.status.total.div_ceil(page.limit).max(1))
+    }
+
+    /// Moves to the next (or, with `forward` unset, the previous) page of the
+    /// status and fetches it right away.
+    pub(crate) fn turn_status_page(&mut self, forward: bool) {
+        let Some(pages) = self.status_pages() else {
+            return;
+        };
+        let page = match forward {
+            true => (self.status_page + 1).min(pages - 1),
+            false => self.status_page.saturating_sub(1),
+        };
+        if page != self.status_page {
+            self.status_page = page;
+            self.collection_tablestate.select(None);
+            self.fetch_requested = true;
+        }
+    }
+
+    /// Takes over a fetched snapshot. Snapshots of a profile which isn't the
+    /// current one are kept for when switching to it.
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let interval = Duration::seconds(self.fetch_interval());
+        if !self
+            .scheduler
+            .finished(snapshot.profile, Local::now(), interval)
+        {
+            self.is_fetching = false;
+            self.fetch_task = None;
+        }
+        self.log_requests(snapshot.requests);
+        let urls = &self.config.profiles[snapshot.profile].urls;
+        if let Some(active) = urls.iter().position(|url| *url == snapshot.url) {
+            self.active_urls[snapshot.profile] = active;
+        }
+        if snapshot.profile != self.current_profile {
+            return self.keep_snapshot(snapshot.profile, snapshot.status, snapshot.metadata);
+        }
+
+        let errors = [
+            snapshot.status.as_ref().err(),
+            snapshot.metadata.as_ref().err(),
+        ];
+        self.record_rate_limit(errors.iter().flatten().copied());
+        // Sessions expire, so the password is asked for again, once for
+        // every time fetches start failing so canceling the prompt sticks.
+        let logged_out = self.logs_in() && errors.iter().flatten().any(|e| e.is::<Unauthorized>());
+        if logged_out && !self.fetch_failing {
+            self.start_login();
+        }
+        // Errors are expected while the instance is in maintenance, and being
+        // rate limited isn't worth an alert either. Fetches resume by
+        // themselves in both cases.
+        let maintenance = match &snapshot.metadata {
+            Ok(metadata) => metadata.maintenance,
+            Err(_) => self.metadata.maintenance,
+        };
+        let is_expected = |e: &color_eyre::Report| maintenance || e.is::<RateLimited>();
+        self.update_fetch_failing(errors.iter().flatten().any(|e| !is_expected(e)));
+        match snapshot.status {
+            Ok(FetchedStatus::Changed(status, size, validators)) => {
+                self.notifications.clear(NotificationSource::Status);
+                self.status_validators = Some((self.status_paging(), validators));
+                self.update_status(status, size);
+            }
+            Ok(FetchedStatus::NotModified) => {
+                self.notifications.clear(NotificationSource::Status);
+                // Still counts as a fetch without progress.
+                self.update_collection_state();
+            }
+            Err(e) if is_expected(&e) => self.notifications.clear(NotificationSource::Status),
+            Err(e) if logged_out && e.is::<Unauthorized>() => self.notify(
+                NotificationKind::Warning,
+                NotificationSource::Status,
+                "Not logged in, press `L` to log in".to_string(),
+            ),
+            Err(e) => {
+                let hint = self.offer_bug_report(&e);
+                self.notify(
+                    NotificationKind::Error,
+                    NotificationSource::Status,
+                    format!("Failed to fetch status: {e}{hint}"),
+                )
+            }
+        }
+        match snapshot.metadata {
+            Ok(metadata) => {
+                self.notifications.clear(NotificationSource::Metadata);
+                self.update_metadata(metadata);
+            }
+            Err(e) if is_expected(&e) => self.notifications.clear(NotificationSource::Metadata),
+            Err(e) => {
+                let hint = self.offer_bug_report(&e);
+                self.notify(
+                    NotificationKind::Error,
+                    NotificationSource::Metadata,
+                    format!("Failed to fetch metadata: {e}{hint}"),
+                )
+            }
+        }
+    }
+
+    /// Keeps the status of a profile which isn't the current one. Errors are
+    /// only reported once it is, so the last status is kept until then.
+    fn keep_snapshot(
+        &mut self,
+        profile: usize,
+        status: color_eyre::Result<FetchedStatus>,
+        metadata: color_eyre::Result<Metadata>,
+    ) {
+        let (Ok(FetchedStatus::Changed(mut status, _, _)), Ok(metadata)) = (status, metadata)
+        else {
+            return;
+        };
+        if self.config.merge_duplicates {
+            status.merge_duplicates();
+        }
+        let fetched = Local::now();
+        let collections = self
+            .profile_statuses
+            .remove(&profile)
+            .map(|previous| previous.collections)
+            .unwrap_or_default();
+        self.profile_statuses.insert(
+            profile,
+            ProfileStatus {
+                status,
+                metadata,
+                fetched,
+                collections,
+            },
+        );
+    }
+
+    /// Delays the next fetch for as long as the longest `Retry-After` of the
+    /// rate limited requests among `errors` asks for, or lifts the delay if
+    /// there were none.
+    fn record_rate_limit<'a>(&mut self, errors: impl Iterator<Item = &'a color_eyre::Report>) {
+        self.rate_limited_until = errors
+            .filter_map(|e| e.downcast_ref::<RateLimited>())
+            .map(|e| {
+                e.retry_after
+                    .and_then(|delay| Duration::from_std(delay).ok())
+                    .unwrap_or(RATE_LIMIT_DELAY)
+            })
+            .max()
+            .map(|delay| Local::now() + delay);
+    }
+
+    /// How long fetches are still delayed for at `now` because of a 429.
+    pub fn rate_limited_for(&self, now: DateTime<Local>) -> Option<Duration> {
+        self.rate_limited_until
+            .map(|until| until - now)
+            .filter(|remaining| *remaining > Duration::zero())
+    }
+
+    /// Keeps schema errors around for a bug report, returning a hint on how
+    /// to write one for the notification.
+    fn offer_bug_report(&mut self, error: &color_eyre::Report) -> &'static str {
+        match error.downcast_ref::<SchemaError>() {
+            Some(schema_error) => {
+                self.schema_error = Some(schema_error.clone());
+                " (press `b` to write a bug report)"
+            }
+            None => "",
+        }
+    }
+
+    /// Writes a bug report about the last schema error, see [`bug_report`].
+    pub(crate) fn write_bug_report(&mut self) {
+        let Some(error) = &self.schema_error else {
+            return;
+        };
+        match bug_report::write(self, error) {
+            Ok(path) => {
+                self.schema_error = None;
+                self.toast(
+                    NotificationKind::Info,
+                    format!("Wrote bug report to {}", path.display()),
+                );
+            }
+            Err(e) => self.toast(
+                NotificationKind::Error,
+                format!("Failed to write bug report: {e}"),
+            ),
+        }
+    }
+
+    /// Writes the history of the collections for Grafana.
+    pub(crate) fn export_history(&mut self) {
+        match history::write(self) {
+            Ok(path) => self.toast(
+                NotificationKind::Info,
+                format!("Wrote history to {}", path.display()),
+            ),
+            Err(e) => self.toast(
+                NotificationKind::Error,
+                format!("Failed to write history: {e}"),
+            ),
+        }
+    }
+
+    /// Raises the configured alert when fetches start failing.
+    fn update_fetch_failing(&mut self, failing: bool) {
+        if failing && !self.fetch_failing {
+            if let Some(alert) = self.config.error_alert {
+                self.ring_bell = alert != ErrorAlert::Flash;
+                if alert != ErrorAlert::Bell {
+                    self.flash_until = Some(Local::now() + FLASH_DURATION);
+                }
+            }
+        }
+        self.fetch_failing = failing;
+    }
+
+    pub fn is_flashing(&self) -> bool {
+        self.flash_until.is_some_and(|until| until > Local::now())
+    }
+
+    /// Swaps in a new status. It is merged and sorted before it replaces the
+    /// previous one, so the table never shows a half-processed status.
+    fn update_status(&mut self, mut status: Status, size: usize) {
+        self.record_status_size(size);
+        if let Paging::Page(page) = self.status_paging() {
+            if status.results.len() as u64 > page.limit && self.status.results.is_empty() {
+                self.toast(
+                    NotificationKind::Warning,
+                    "This instance doesn't support paging the status".to_string(),
+                );
+            }
+        }
+        if self.config.merge_duplicates {
+            status.merge_duplicates();
+        }
+        self.reload_state();
+        self.sort(&mut status.results);
+        let selected = self.selected_row();
+        let previous = std::mem::replace(&mut self.status, status);
+        let events = timeline::diff(&previous, &self.status);
+        if let Some(row) = &selected {
+            self.select_row(row);
+        }
+        self.clamp_selection();
+        self.update_collection_state();
+        self.update_keep_awake();
+        self.record_events(&events);
+        self.announce_destructive();
+    }
+
+    /// Warns about delete and flush jobs until the user acknowledges them, once
+    /// for every time such a job appears.
+    fn announce_destructive(&mut self) {
+        let now = Local::now();
+        let destructive: Vec<(String, String)> = self
+            .status
+            .results
+            .iter()
+            .filter(|result| result.is_destructive())
+            .map(|result| (result.key().to_string(), result.label().to_string()))
+            .collect();
+        for (key, label) in &destructive {
+            if self.destructive.contains(key) || self.is_muted(key, now) {
+                continue;
+            }
+            self.toasts.push_sticky(
+                NotificationKind::Warning,
+                format!("Deleting or flushing data of {label}, press `a` to acknowledge"),
+            );
+        }
+        self.destructive = destructive.into_iter().map(|(key, _)| key).collect();
+    }
+
+    /// Remembers the size of the status response, warning once per profile
+    /// when it crosses the configured threshold.
+    fn record_status_size(&mut self, size: usize) {
+        let was_large = self.is_status_large();
+        self.status_size = Some(size);
+        if self.is_status_large() && !was_large {
+            self.toast(
+                NotificationKind::Warning,
+                format!(
+                    "Status responses are large ({:.1} MB), consider a longer fetch_interval",
+                    size as f64 / 1e6
+                ),
+            );
+        }
+    }
+
+    pub fn is_status_large(&self) -> bool {
+        self.status_size
+            .is_some_and(|size| size as f64 > self.config.large_status_size * 1e6)
+    }
+
+    /// Marks the collections in the status and the selected one as seen,
+    /// records their progress and forgets about collections which have been
+    /// idle for a long time, so memory stays flat over long sessions on big
+    /// instances.
+    fn update_collection_state(&mut self) {
+        let now = Local::now();
+        let mut new_failures = 0;
+        let mut processed = 0;
+        for result in &self.status.results {
+            let state = self
+                .collections
+                .entry(result.key().to_string())
+                .or_insert_with(|| CollectionState::new(now));
+            state.last_seen = now;
+            let done = result.succeeded.saturating_add(result.failed);
+            if let Some((_, before)) = state.history.back() {
+                processed += done.saturating_sub(*before);
+            }
+            state.record(done, now);
+            state.samples.record(result, now);
+            state.record_tasks(result, now);
+            // Failures which happened before aleph-tui started aren't counted.
+            if let Some(failed) = state.failed.replace(result.failed) {
+                new_failures += result.failed.saturating_sub(failed);
+            }
+        }
+        if new_failures > 0 {
+            self.recent_failures.push_back((now, new_failures));
+        }
+        while self
+            .recent_failures
+            .front()
+            .is_some_and(|(time, _)| now - *time > Duration::hours(1))
+        {
+            self.recent_failures.pop_front();
+        }
+        self.recent_progress.push_back((now, processed));
+        let window = THROUGHPUT_WINDOWS[THROUGHPUT_WINDOWS.len() - 1];
+        while self
+            .recent_progress
+            .get(1)
+            .is_some_and(|(time, _)| now - *time >= Duration::minutes(window))
+        {
+            self.recent_progress.pop_front();
+        }
+        if let Some(key) = self.selected_result().map(|r| r.key().to_string()) {
+            self.collections
+                .entry(key)
+                .or_insert_with(|| CollectionState::new(now))
+                .last_seen = now;
+        }
+
+        let watched = &self.watched;
+        prune_idle(
+            &mut self.collections,
+            now,
+            Duration::hours(self.config.prune_idle_after),
+            |key| watched.iter().any(|w| w == key),
+        );
+        if let Some(report) = &self.failure_report {
+            if !self.collections.contains_key(&report.key) {
+                self.failure_report = None;
+            }
+        }
+        if let Some(summary) = &self.xref_summary {
+            if !self.collections.contains_key(&summary.key) {
+                self.xref_summary = None;
+            }
+        }
+        let collections = &self.collections;
+        self.expanded.retain(|key| collections.contains_key(key));
+    }
+
+    /// Announces collections finishing and, if configured, writes all events
+    /// to the timeline file.
+    fn record_events(&mut self, events: &[timeline::CollectionEvent]) {
+        let now = Local::now();
+        for event in events {
+            if event.kind != EventKind::Started && self.notify_when_done.remove(&event.key) {
+                self.ring_bell = true;
+                self.flash_until = Some(now + FLASH_DURATION);
+                let message = match event.kind {
+                    EventKind::Failed => format!(
+                        "{} finished with {} failed tasks",
+                        event.label, event.failed
+                    ),
+                    _ => format!("{} completed", event.label),
+                };
+                self.toasts.push_sticky(
+                    NotificationKind::Info,
+                    format!("{message}, press `a` to acknowledge"),
+                );
+                continue;
+            }
+            if self.is_muted(&event.key, now) {
+                continue;
+            }
+            match event.kind {
+                EventKind::Started => {}
+                EventKind::Completed => {
+                    self.toast(NotificationKind::Info, format!("{} completed", event.label))
+                }
+                EventKind::Failed => self.toast(
+                    NotificationKind::Warning,
+                    format!(
+                        "{} finished with {} failed tasks",
+                        event.label, event.failed
+                    ),
+                ),
+            }
+        }
+        let Some(path) = &self.config.timeline_file else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+        let profile = self.current_profile().name;
+        if let Err(e) = timeline::append(path, Local::now(), &profile, events) {
+            self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Failed to write timeline to {}: {e}", path.display()),
+            );
+        }
+    }
+
+    fn update_metadata(&mut self, metadata: Metadata) {
+        self.record_version_change(&metadata);
+        self.metadata = metadata;
+    }
+
+    /// The status results shown in the table, in display order.
+    pub fn visible_results(&self) -> Vec<&StatusResult> {
+        self.status
+            .results
+            .iter()
+            .filter(|result| {
+                self.counter_filter
+                    .is_none_or(|counter| counter.value(result) > 0)
+            })
+            .filter(|result| self.search_matches(result) || !self.visible_tasks(result).is_empty())
+            .collect()
+    }
+
+    /// Whether the search matches the label, foreign id, job name or one of
+    /// the tags of `result`, which are searched as `#tag`; always true
+    /// without a search.
+    fn search_matches(&self, result: &StatusResult) -> bool {
+        let query = self.search.to_lowercase();
+        let foreign_id = result.collection.as_ref().map(|c| c.foreign_id.as_str());
+        let tags = self.tags(result.key()).iter().map(|tag| format!("#{tag}"));
+        [Some(result.label()), foreign_id, Some(&result.name)]
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .chain(tags)
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+
+    /// The task rows shown for `result`: all of its tasks if it is expanded
+    /// and the search matches the collection itself, otherwise only those
+    /// whose queue or task name matches the search, expanded or not.
+    pub fn visible_tasks<'a>(
+        &self,
+        result: &'a StatusResult,
+    ) -> Vec<(&'a Batch, &'a Queue, &'a Task)> {
+        if self.search_matches(result) {
+            return match self.expanded.contains(result.key()) {
+                true => result.tasks().collect(),
+                false => Vec::new(),
+            };
+        }
+        let query = self.search.to_lowercase();
+        result
+            .tasks()
+            .filter(|(_, queue, task)| {
+                queue.name.to_lowercase().contains(&query)
+                    || task.name.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// How long a task of `result` has seemingly been stuck, see
+    /// [`CollectionState::stuck_for`].
+    pub fn stuck_for(
+        &self,
+        result: &StatusResult,
+        task: (&Batch, &Queue, &Task),
+        now: DateTime<Local>,
+    ) -> Option<Duration> {
+        self.collections.get(result.key())?.stuck_for(task, now)
+    }
+
+    /// Whether any task of `result` seems stuck.
+    pub fn has_stuck_tasks(&self, result: &StatusResult, now: DateTime<Local>) -> bool {
+        result
+            .tasks()
+            .any(|task| self.stuck_for(result, task, now).is_some())
+    }
+
+    /// Number of rows in the table (collections + tasks).
+    fn row_count(&self) -> usize {
+        self.visible_results()
+            .iter()
+            .map(|result| 1 + self.visible_tasks(result).len())
+            .sum()
+    }
+
+    /// Keeps the selection within the table after rows disappeared.
+    fn clamp_selection(&mut self) {
+        let rows = self.row_count();
+        if let Some(index) = self.collection_tablestate.selected() {
+            match rows {
+                0 => self.collection_tablestate.select(None),
+                _ if index >= rows => self.collection_tablestate.select(Some(rows - 1)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows only collections where `counter` is nonzero, or shows all
+    /// collections again if that filter is already active.
+    pub(crate) fn toggle_counter_filter(&mut self, counter: Counter) {
+        self.counter_filter = match self.counter_filter {
+            Some(active) if active == counter => None,
+            _ => Some(counter),
+        };
+        self.clamp_selection();
+    }
+
+    /// Switches to the next (or, with `forward` unset, the previous) tab of
+    /// the details pane, wrapping around at either end.
+    pub(crate) fn cycle_details_tab(&mut self, forward: bool) {
+        self.details_scroll = 0;
+        let count = DetailsTab::ALL.len();
+        let index = DetailsTab::ALL
+            .iter()
+            .position(|tab| *tab == self.details_tab)
+            .unwrap_or_default();
+        let index = match forward {
+            true => (index + 1) % count,
+            false => (index + count - 1) % count,
+        };
+        self.details_tab = DetailsTab::ALL[index];
+    }
+
+    pub(crate) fn clear_counter_filter(&mut self) {
+        self.counter_filter = None;
+    }
+
+    /// Starts editing the search, keeping the current one.
+    pub(crate) fn start_search(&mut self) {
+        self.is_searching = true;
+    }
+
+    pub(crate) fn set_search(&mut self, search: String) {
+        self.search = search;
+        self.clamp_selection();
+    }
+
+    /// The local tags of the collection with `key` in the current profile.
+    pub fn tags(&self, key: &str) -> &[String] {
+        let profile = &self.config.profiles[self.current_profile].name;
+        self.state.tags(profile, key)
+    }
+
+    /// Starts editing the tags of the selected collection.
+    pub(crate) fn start_editing_tags(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        self.tag_editor = Some(TagEditor {
+            key: result.key().to_string(),
+            label: result.label().to_string(),
+            input: self.tags(result.key()).join(", "),
+        });
+    }
+
+    /// Stops editing tags, replacing the tags of the collection with the
+    /// comma separated ones typed if `save` is set.
+    pub(crate) fn stop_editing_tags(&mut self, save: bool) {
+        let Some(editor) = self.tag_editor.take() else {
+            return;
+        };
+        if !save {
+            return;
+        }
+        let profile = self.current_profile().name;
+        // Another session may have tagged it in the meantime.
+        self.reload_state();
+        let tags = editor.input.split(',').map(str::to_string);
+        self.state.set_tags(&profile, &editor.key, tags);
+        let message = match self.tags(&editor.key) {
+            [] => format!("Removed the tags of {}", editor.label),
+            tags => format!("Tagged {} with {}", editor.label, tags.join(", ")),
+        };
+        self.toast(NotificationKind::Info, message);
+        self.save_state();
+        self.sort_results();
+        self.clamp_selection();
+    }
+
+    /// Whether the current profile logs in with a password.
+    pub fn logs_in(&self) -> bool {
+        matches!(
+            self.config.profiles[self.current_profile].auth,
+            Auth::Login { .. }
+        )
+    }
+
+    /// Asks for the password of the current profile, if it logs in with one.
+    pub(crate) fn start_login(&mut self) {
+        if self.logs_in() {
+            self.login_prompt = Some(LoginPrompt {
+                profile: self.current_profile,
+                password: String::new(),
+            });
+        }
+    }
+
+    /// Closes the password prompt, starting to log in with the password typed
+    /// if `log_in` is set. The session is sent to `replies`.
+    pub(crate) fn stop_login(&mut self, log_in: bool, replies: &UnboundedSender<Reply>) {
+        let Some(prompt) = self.login_prompt.take() else {
+            return;
+        };
+        let (true, Auth::Login { email, .. }) =
+            (log_in, &self.config.profiles[prompt.profile].auth)
+        else {
+            return;
+        };
+        let api = self.api_for(prompt.profile);
+        let request = api.log_in(email, &prompt.password);
+        let replies = replies.clone();
+        tokio::spawn(async move {
+            let mut requests = Vec::new();
+            // Not retried, like other POSTs.
+            let session = async {
+                let response = api
+                    .send(request, &mut requests)
+                    .await
+                    .map_err(|e| api.error(e))?;
+                api::check_status(response)?
+                    .json()
+                    .await
+                    .map_err(|e| api.error(e))
+            }
+            .await;
+            let _ = replies.send(Reply::Login {
+                profile: prompt.profile,
+                session,
+                requests,
+            });
+        });
+    }
+
+    /// Switches `profile` to the session it logged in with, or tells why it
+    /// couldn't log in. The session token is kept for the next start.
+    fn finish_login(&mut self, profile: usize, session: color_eyre::Result<SessionResponse>) {
+        let Auth::Login { email, .. } = &self.config.profiles[profile].auth else {
+            return;
+        };
+        let (name, email) = (self.config.profiles[profile].name.clone(), email.clone());
+        match session {
+            Ok(session) => {
+                self.config.profiles[profile].auth = Auth::Login {
+                    email,
+                    token: Some(session.token.clone()),
+                };
+                self.save_session(&name, session.token);
+                self.notifications.clear(NotificationSource::Status);
+                self.toast(NotificationKind::Info, format!("Logged in to {name}"));
+                self.fetch_requested = true;
+            }
+            Err(e) => self.notify(
+                NotificationKind::Error,
+                NotificationSource::Action,
+                format!("Failed to log in to {name}: {e}"),
+            ),
+        }
+    }
+
+    /// Asks for the path of a file or directory to upload to the selected
+    /// collection, if it may be written to.
+    pub(crate) fn start_upload(&mut self) {
+        let Some(collection) = self.selected_result().and_then(|r| r.collection.clone()) else {
+            return;
+        };
+        let message = match &self.upload {
+            Some(upload) => format!("Still uploading to {}", upload.label),
+            None if !collection.writeable => format!("Can't upload to {}", collection.label),
+            None => {
+                self.upload_prompt = Some(UploadPrompt {
+                    collection,
+                    path: String::new(),
+                });
+                return;
+            }
+        };
+        self.notify(NotificationKind::Error, NotificationSource::Action, message);
+    }
+
+    /// Closes the upload prompt, uploading the path typed in the background
+    /// if `upload` is set. `~/` stands for the home directory.
+    pub(crate) fn stop_upload(&mut self, upload: bool) {
+        let Some(prompt) = self.upload_prompt.take() else {
+            return;
+        };
+        if !upload || prompt.path.is_empty() {
+            return;
+        }
+        let path = match (prompt.path.strip_prefix("~/"), home::home_dir()) {
+            (Some(path), Some(home)) => home.join(path),
+            _ => PathBuf::from(&prompt.path),
+        };
+        match Upload::start(self.api(), &prompt.collection, &path) {
+            Ok(upload) => self.upload = Some(upload),
+            Err(e) => self.notify(
+                NotificationKind::Error,
+                NotificationSource::Action,
+                format!("Failed to upload: {e}"),
+            ),
+        }
+    }
+
+    /// Reports the upload once it is done, and fetches the status so that
+    /// the new files show up.
+    pub(crate) fn check_upload(&mut self) {
+        let Some(outcome) = self.upload.as_mut().and_then(Upload::finished) else {
+            return;
+        };
+        let Some(upload) = self.upload.take() else {
+            return;
+        };
+        self.log_requests(outcome.requests);
+        match outcome.result {
+            Ok(files) => {
+                let message = format!("Uploaded {files} files to {}", upload.label);
+                self.toast(NotificationKind::Info, message);
+                self.fetch_requested = true;
+            }
+            Err(e) => {
+                let (uploaded, _) = upload.progress.files();
+                let message = format!(
+                    "Failed to upload to {} after {uploaded} files: {e}",
+                    upload.label
+                );
+                self.notify(NotificationKind::Error, NotificationSource::Action, message);
+            }
+        }
+    }
+
+    /// Shows collections grouped by their first tag, or in the sort order
+    /// only again.
+    pub(crate) fn toggle_group_by_tag(&mut self) {
+        self.group_by_tag = !self.group_by_tag;
+        self.sort_results();
+    }
+
+    /// Stops editing the search, clearing it unless `keep` is set.
+    pub(crate) fn stop_search(&mut self, keep: bool) {
+        self.is_searching = false;
+        if !keep {
+            self.set_search(String::new());
+        }
+    }
+
+    /// The status result owning the selected row, which is either the
+    /// collection row itself or one of its task rows.
+    pub fn selected_result(&self) -> Option<&StatusResult> {
+        let index = self.collection_tablestate.selected()?;
+        let mut first_row = 0;
+        for result in self.visible_results() {
+            let rows = 1 + self.visible_tasks(result).len();
+            if index < first_row + rows {
+                return Some(result);
+            }
+            first_row += rows;
+        }
+        None
+    }
+
+    /// Shows or hides the task rows of the selected collection, selecting
+    /// its row.
+    pub(crate) fn toggle_expanded(&mut self) {
+        let Some(key) = self
+            .selected_result()
+            .map(|result| result.key().to_string())
+        else {
+            return;
+        };
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key.clone());
+        }
+        self.select_row(&RowKey {
+            collection: key,
+            task: None,
+        });
+    }
+
+    /// What the selected row shows, to find it again after the rows changed.
+    fn selected_row(&self) -> Option<RowKey> {
+        let index = self.collection_tablestate.selected()?;
+        let mut first_row = 0;
+        for result in self.visible_results() {
+            let tasks = self.visible_tasks(result);
+            if index <= first_row + tasks.len() {
+                let task = (index - first_row)
+                    .checked_sub(1)
+                    .and_then(|index| tasks.get(index))
+                    .map(|(batch, queue, task)| {
+                        (batch.name.clone(), queue.name.clone(), task.name.clone())
+                    });
+                return Some(RowKey {
+                    collection: result.key().to_string(),
+                    task,
+                });
+            }
+            first_row += 1 + tasks.len();
+        }
+        None
+    }
+
+    /// Selects `row`, or the row of its collection if the task isn't shown
+    /// any more. Leaves the selection alone if the collection is gone.
+    fn select_row(&mut self, row: &RowKey) {
+        let mut first_row = 0;
+        let mut found = None;
+        for result in self.visible_results() {
+            let tasks = self.visible_tasks(result);
+            if result.key() == row.collection {
+                let offset = row.task.as_ref().and_then(|(batch, queue, task)| {
+                    tasks.iter().position(|(b, q, t)| {
+                        b.name == *batch && q.name == *queue && t.name == *task
+                    })
+                });
+                found = Some(first_row + offset.map_or(0, |offset| offset + 1));
+                break;
+            }
+            first_row += 1 + tasks.len();
+        }
+        if found.is_some() {
+            self.collection_tablestate.select(found);
+        }
+    }
+
+    /// The task of the selected row, if it is a task row.
+    pub fn selected_task(&self) -> Option<&Task> {
+        let index = self.collection_tablestate.selected()?;
+        let mut first_row = 0;
+        for result in self.visible_results() {
+            let tasks = self.visible_tasks(result);
+            if index <= first_row + tasks.len() {
+                let (_, _, task) = tasks.get((index - first_row).checked_sub(1)?)?;
+                return Some(task);
+            }
+            first_row += 1 + tasks.len();
+        }
+        None
+    }
+
+    /// The watched collections in the order they were added, with their
+    /// current status if they are still part of it.
+    pub fn watched_results(&self) -> Vec<(&str, Option<&StatusResult>)> {
+        self.watched
+            .iter()
+            .map(|key| {
+                let result = self.status.results.iter().find(|r| r.key() == key);
+                (key.as_str(), result)
+            })
+            .collect()
+    }
+
+    pub fn is_watched(&self, result: &StatusResult) -> bool {
+        self.watched.iter().any(|key| key == result.key())
+    }
+
+    /// Asks to be notified when the selected collection completes or fails,
+    /// or no longer.
+    pub(crate) fn toggle_notify(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let key = result.key().to_string();
+        let label = result.label().to_string();
+        let message = match self.notify_when_done.remove(&key) {
+            true => format!("Won't notify when {label} is done"),
+            false => {
+                self.notify_when_done.insert(key);
+                format!("Will notify when {label} is done")
+            }
+        };
+        self.toast(NotificationKind::Info, message);
+    }
+
+    /// Starts or stops watching the selected collection.
+    pub(crate) fn toggle_watch(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let key = result.key().to_string();
+        let label = result.label().to_string();
+        if self.watched.contains(&key) {
+            self.watched.retain(|k| *k != key);
+            self.toast(NotificationKind::Info, format!("Stopped watching {label}"));
+        } else if self.watched.len() >= self.config.max_watched {
+            self.toast(
+                NotificationKind::Warning,
+                format!(
+                    "Can't watch more than {} collections",
+                    self.config.max_watched
+                ),
+            );
+        } else {
+            self.watched.push(key);
+            self.toast(NotificationKind::Info, format!("Watching {label}"));
+        }
+        self.update_keep_awake();
+    }
+
+    /// How long `result` has been processing without progress at `now`.
+    pub fn stalled_for(&self, result: &StatusResult, now: DateTime<Local>) -> Duration {
+        match (result.is_processing(), self.collections.get(result.key())) {
+            (true, Some(state)) => now - state.last_progress,
+            _ => Duration::zero(),
+        }
+    }
+
+    /// Health score of `result` at `now`, see [`health::score`].
+    pub fn health(&self, result: &StatusResult, now: DateTime<Local>) -> u8 {
+        let finished = result.succeeded.saturating_add(result.failed);
+        let failure_rate = match finished {
+            0 => 0.0,
+            _ => result.failed as f64 / finished as f64,
+        };
+        let stalled = self.stalled_for(result, now);
+        // Aleph reports timestamps in UTC without an offset.
+        let started = result.min_ts.as_deref().and_then(|ts| {
+            NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f")
+                .ok()
+                .map(|started| started.and_utc())
+        });
+        let age = match started {
+            Some(started) => now.with_timezone(&Utc) - started,
+            None => Duration::zero(),
+        };
+        health::score(self.config.health_weights, failure_rate, stalled, age)
+    }
+
+    /// Whether alerts for the collection with `key` are muted at `now`.
+    pub fn is_muted(&self, key: &str, now: DateTime<Local>) -> bool {
+        let profile = &self.config.profiles[self.current_profile].name;
+        self.state.muted_until(profile, key, now).is_some()
+    }
+
+    /// Mutes the alerts of the selected collection for the next duration in
+    /// [`MUTE_HOURS`] which is longer than what is left of its current mute,
+    /// or unmutes it after the longest one.
+    pub(crate) fn cycle_mute(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let key = result.key().to_string();
+        let label = result.label().to_string();
+        let profile = self.current_profile().name;
+        let now = Local::now();
+        // Another session may have muted it in the meantime.
+        self.reload_state();
+        let remaining = match self.state.muted_until(&profile, &key, now) {
+            Some(until) => until - now,
+            None => Duration::zero(),
+        };
+        let hours = MUTE_HOURS
+            .into_iter()
+            .find(|hours| Duration::hours(*hours) > remaining + Duration::minutes(1));
+        let message = match hours {
+            Some(hours) => {
+                self.state
+                    .mute(&profile, &key, Some(now + Duration::hours(hours)));
+                format!("Muted {label} for {hours}h")
+            }
+            None => {
+                self.state.mute(&profile, &key, None);
+                format!("Unmuted {label}")
+            }
+        };
+        self.toast(NotificationKind::Info, message);
+        self.save_state();
+    }
+
+    /// Inhibits system sleep for as long as a watched collection is still
+    /// processing, if `keep_awake` is enabled.
+    fn update_keep_awake(&mut self) {
+        if !self.config.keep_awake {
+            return;
+        }
+        let processing = self
+            .status
+            .results
+            .iter()
+            .any(|r| r.is_processing() && self.is_watched(r));
+        if let Err(e) = self
+            .sleep_inhibitor
+            .set(processing, "Watching Aleph collections")
+        {
+            self.config.keep_awake = false;
+            self.notify(
+                NotificationKind::Warning,
+                NotificationSource::System,
+                format!("Disabled keep awake: {e}"),
+            );
+        }
+    }
+
+    /// Notices when the instance is upgraded (or rolled back) mid-session.
+    fn record_version_change(&mut self, metadata: &Metadata) {
+        let (Some(from), Some(to)) = (&self.metadata.app.version, &metadata.app.version) else {
+            return;
+        };
+        if from == to {
+            return;
+        }
+        let change = VersionChange {
+            time: Local::now(),
+            from: from.clone(),
+            to: to.clone(),
+        };
+        self.toast(
+            NotificationKind::Warning,
+            format!(
+                "Aleph version changed from {} to {}",
+                change.from, change.to
+            ),
+        );
+        self.version_changes.push(change);
+    }
+
+    /// Adds requests to the request log and, if configured, the log file.
+    fn log_requests(&mut self, entries: Vec<RequestLogEntry>) {
+        for entry in entries {
+            if let Some(path) = &self.config.request_log_file {
+                if let Err(e) = request_log::append(path, &entry) {
+                    let message = format!("Failed to write request log to {}: {e}", path.display());
+                    self.notify(
+                        NotificationKind::Warning,
+                        NotificationSource::System,
+                        message,
+                    );
+                }
+            }
+            self.request_log.push(entry);
+        }
+    }
+
+    pub fn toggle_request_log(&mut self) {
+        self.show_request_log = !self.show_request_log;
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
+
+    /// Shows the documents of the failure report of the selected collection,
+    /// or closes them.
+    pub(crate) fn toggle_failed_documents(&mut self) {
+        if self.failed_documents.take().is_some() {
+            return;
+        }
+        let key = self.selected_result().map(|result| result.key());
+        match &self.failure_report {
+            Some(report) if Some(report.key.as_str()) == key => {
+                self.failed_documents = Some(TableState::default().with_selected(0));
+            }
+            _ => self.toast(
+                NotificationKind::Info,
+                "Press `e` to fetch failed documents first".to_string(),
+            ),
+        }
+    }
+
+    /// The links of the selected collection, by what they lead to.
+    pub fn links(&self) -> Vec<(&'static str, &str)> {
+        let Some(collection) = self.selected_result().and_then(|r| r.collection.as_ref()) else {
+            return Vec::new();
+        };
+        let links = &collection.links;
+        [
+            ("Web interface", links.ui.as_str()),
+            ("API", links.self_.as_str()),
+            ("Reconciliation", links.reconcile.as_str()),
+            ("Cross-referencing export", links.xref_export.as_str()),
+        ]
+        .into_iter()
+        .filter(|(_, url)| !url.is_empty())
+        .collect()
+    }
+
+    /// Shows the links of the selected collection, or closes them.
+    pub(crate) fn toggle_links(&mut self) {
+        self.link_list = match (&self.link_list, self.links().is_empty()) {
+            (None, false) => Some(TableState::default().with_selected(0)),
+            _ => None,
+        };
+    }
+
+    /// Whether the links of the selected collection are shown.
+    pub fn shows_links(&self) -> bool {
+        self.link_list.is_some() && !self.links().is_empty()
+    }
+
+    pub(crate) fn link_up(&mut self) {
+        if let Some(state) = &mut self.link_list {
+            state.select_previous();
+        }
+    }
+
+    pub(crate) fn link_down(&mut self) {
+        let count = self.links().len();
+        if let Some(state) = &mut self.link_list {
+            if state.selected().is_some_and(|index| index + 1 < count) {
+                state.select_next();
+            }
+        }
+    }
+
+    fn selected_link(&self) -> Option<(&'static str, String)> {
+        let index = self.link_list.as_ref()?.selected()?;
+        let (name, url) = self.links().get(index).copied()?;
+        Some((name, url.to_string()))
+    }
+
+    /// Opens the selected link in the browser.
+    pub(crate) fn open_link(&mut self) {
+        let Some((name, url)) = self.selected_link() else {
+            return;
+        };
+        match browser::open(&url) {
+            Ok(()) => self.toast(NotificationKind::Info, format!("Opened the {name} link")),
+            Err(e) => self.toast(
+                NotificationKind::Error,
+                format!("Failed to open {url}: {e}"),
+            ),
+        }
+    }
+
+    /// Copies the selected link to the clipboard.
+    pub(crate) fn copy_link(&mut self) {
+        let Some((name, url)) = self.selected_link() else {
+            return;
+        };
+        self.clipboard = Some(url);
+        self.toast(NotificationKind::Info, format!("Copied the {name} link"));
+    }
+
+    /// The failure report while its documents are shown.
+    pub fn shown_failure_report(&self) -> Option<&FailureReport> {
+        self.failed_documents.as_ref()?;
+        self.failure_report.as_ref()
+    }
+
+    pub(crate) fn failed_document_up(&mut self) {
+        if let Some(state) = &mut self.failed_documents {
+            state.select_previous();
+        }
+    }
+
+    pub(crate) fn failed_document_down(&mut self) {
+        let count = self
+            .failure_report
+            .as_ref()
+            .map_or(0, |report| report.documents.len());
+        if let Some(state) = &mut self.failed_documents {
+            if state.selected().is_some_and(|index| index + 1 < count) {
+                state.select_next();
+            }
+        }
+    }
+
+    /// Shows the selected collection full screen, or goes back to the table.
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed && self.selected_result().is_some();
+    }
+
+    /// Lines of the details pane, including its borders. The state file can
+    /// be edited by hand, so the height is kept in bounds.
+    pub fn details_height(&self) -> u16 {
+        let height = self.state.details_height.unwrap_or(DETAILS_HEIGHT);
+        height.clamp(*DETAILS_HEIGHTS.start(), *DETAILS_HEIGHTS.end())
+    }
+
+    /// Grows the details pane by a line at the expense of the table, or
+    /// shrinks it with `grow` unset. The height is kept in the state.
+    pub(crate) fn resize_details(&mut self, grow: bool) {
+        let height = match grow {
+            true => self.details_height().saturating_add(1),
+            false => self.details_height().saturating_sub(1),
+        };
+        let height = height.clamp(*DETAILS_HEIGHTS.start(), *DETAILS_HEIGHTS.end());
+        if height != self.details_height() {
+            self.reload_state();
+            self.state.details_height = Some(height);
+            self.save_state();
+        }
+    }
+
+    /// Lets `j` and `k` scroll the details pane, or move the selection again.
+    pub(crate) fn toggle_details_focus(&mut self) {
+        self.details_focused = !self.details_focused && self.selected_result().is_some();
+        self.details_scroll = 0;
+    }
+
+    pub(crate) fn scroll_details(&mut self, down: bool) {
+        self.details_scroll = match down {
+            true => (self.details_scroll + 1).min(self.details_max_scroll),
+            false => self.details_scroll.saturating_sub(1),
+        };
+    }
+
+    pub(crate) fn toggle_wrap_details(&mut self) {
+        self.wrap_details = !self.wrap_details;
+    }
+
+    /// Copies the URL of the selected collection to the clipboard.
+    pub(crate) fn copy_url(&mut self) {
+        let Some(collection) = self.selected_result().and_then(|r| r.collection.as_ref()) else {
+            return;
+        };
+        let message = format!("Copied the URL of {}", collection.label);
+        self.clipboard = Some(collection.links.ui.clone());
+        self.toast(NotificationKind::Info, message);
+    }
+
+    /// Whether the selected collection is shown full screen. Zooming ends
+    /// when nothing is selected anymore, e.g. because the collection is done.
+    pub fn is_zoomed(&self) -> bool {
+        self.zoomed && self.selected_result().is_some()
+    }
+
+    /// Marks the current profile to be compared with, or, if another one is
+    /// marked already, starts fetching the metadata of both to show them side
+    /// by side. The metadata is sent to `replies`.
+    pub(crate) fn compare_profiles(&mut self, replies: &UnboundedSender<Reply>) {
+        let other = match self.compare_with.take() {
+            Some(other) if other != self.current_profile => other,
+            _ => {
+                self.compare_with = Some(self.current_profile);
+                let message = format!(
+                    "Press `c` on another profile to compare it with {}",
+                    self.current_profile().name
+                );
+                return self.toast(NotificationKind::Info, message);
+            }
+        };
+        let fetch = |index| {
+            let api = self.api_for(index);
+            async move {
+                let mut requests = Vec::new();
+                let metadata: color_eyre::Result<serde_json::Value> = async {
+                    let response = api
+                        .send_retrying(api.get("metadata"), &mut requests)
+                        .await?;
+                    response.json().await.map_err(|e| api.error(e))
+                }
+                .await;
+                (metadata, requests)
+            }
+        };
+        let indices = [other, self.current_profile];
+        let fetches = indices.map(fetch);
+        let names = indices.map(|index| self.config.profiles[index].name.clone());
+        let replies = replies.clone();
+        tokio::spawn(async move {
+            let [first, second] = fetches;
+            let ((first, mut requests), (second, second_requests)) = tokio::join!(first, second);
+            requests.extend(second_requests);
+            // The receiver only goes away when the app quits.
+            let _ = replies.send(Reply::Comparison {
+                names,
+                metadata: [first, second],
+                requests,
+            });
+        });
+    }
+
+    /// Takes over the reply to a request started by a key press.
+    pub fn apply_reply(&mut self, reply: Reply) {
+        match reply {
+            Reply::Comparison {
+                names,
+                metadata,
+                requests,
+            } => {
+                self.log_requests(requests);
+                self.show_comparison(names, metadata)
+            }
+            Reply::Failures {
+                collection_id,
+                label,
+                response,
+                requests,
+            } => {
+                self.log_requests(requests);
+                self.show_failures(collection_id, label, response)
+            }
+            Reply::Xref {
+                collection_id,
+                response,
+                requests,
+            } => {
+                self.log_requests(requests);
+                self.show_xref(collection_id, response)
+            }
+            Reply::Login {
+                profile,
+                session,
+                requests,
+            } => {
+                self.log_requests(requests);
+                self.finish_login(profile, session)
+            }
+        }
+    }
+
+    /// Shows the metadata of two profiles side by side, or why it couldn't be
+    /// fetched.
+    fn show_comparison(
+        &mut self,
+        names: [String; 2],
+        metadata: [color_eyre::Result<serde_json::Value>; 2],
+    ) {
+        let metadata = names.iter().zip(metadata).map(|(name, result)| {
+            result.map_err(|e| format!("Failed to fetch metadata of {name}: {e}"))
+        });
+        match metadata.collect::<Result<Vec<_>, _>>() {
+            Ok(metadata) => {
+                self.metadata_diff = Some(MetadataDiff::new(names, [&metadata[0], &metadata[1]]));
+            }
+            Err(message) => {
+                self.notify(NotificationKind::Error, NotificationSource::Action, message)
+            }
+        }
+    }
+
+    /// Starts fetching the failed documents of the selected collection, to
+    /// cluster them by error once they are sent to `replies`.
+    pub(crate) fn fetch_failures(&mut self, replies: &UnboundedSender<Reply>) {
+        let Some(collection) = self.selected_result().and_then(|r| r.collection.clone()) else {
+            return;
+        };
+        let api = self.api();
+        let mut requests = Vec::new();
+        let request = api.get("entities").query(&[
+            ("filter:collection_id", collection.collection_id.as_str()),
+            ("filter:properties.processingStatus", "failure"),
+            ("limit", "200"),
+        ]);
+        let replies = replies.clone();
+        tokio::spawn(async move {
+            let response = async {
+                let response = api.send_retrying(request, &mut requests).await?;
+                response.json().await.map_err(|e| api.error(e))
+            }
+            .await;
+            let _ = replies.send(Reply::Failures {
+                collection_id: collection.collection_id,
+                label: collection.label,
+                response,
+                requests,
+            });
+        });
+    }
+
+    /// Clusters the failed documents of a collection by error, or tells why
+    /// they couldn't be fetched.
+    fn show_failures(
+        &mut self,
+        collection_id: String,
+        label: String,
+        response: color_eyre::Result<EntitiesResponse>,
+    ) {
+        match response {
+            Ok(response) => {
+                let documents: Vec<FailedDocument> =
+                    response.results.iter().map(FailedDocument::from).collect();
+                let total = response.total.unwrap_or(response.results.len() as u64);
+                self.toast(
+                    NotificationKind::Info,
+                    format!("Fetched {total} failed documents of {label}"),
+                );
+                self.failure_report = Some(FailureReport::new(collection_id, documents, total));
+                if self.failed_documents.is_some() {
+                    self.failed_documents = Some(TableState::default().with_selected(0));
+                }
+            }
+            Err(e) => self.notify(
+                NotificationKind::Error,
+                NotificationSource::Action,
+                format!("Failed to fetch failed documents: {e}"),
+            ),
+        }
+    }
+
+    /// Starts fetching the most likely cross-referencing matches of the
+    /// selected collection, to count how they were judged and show them in
+    /// the xref tab once they are sent to `replies`.
+    pub(crate) fn fetch_xref(&mut self, replies: &UnboundedSender<Reply>) {
+        let Some(collection) = self.selected_result().and_then(|r| r.collection.clone()) else {
+            return;
+        };
+        let api = self.api();
+        let mut requests = Vec::new();
+        let path = format!("collections/{}/xref", collection.collection_id);
+        let request = api.get(&path).query(&[("limit", XREF_SAMPLE_SIZE)]);
+        let replies = replies.clone();
+        tokio::spawn(async move {
+            let response = async {
+                let response = api.send_retrying(request, &mut requests).await?;
+                response.json().await.map_err(|e| api.error(e))
+            }
+            .await;
+            let _ = replies.send(Reply::Xref {
+                collection_id: collection.collection_id,
+                response,
+                requests,
+            });
+        });
+    }
+
+    /// Shows how the cross-referencing matches of a collection were judged,
+    /// or tells why they couldn't be fetched.
+    fn show_xref(&mut self, collection_id: String, response: color_eyre::Result<XrefResponse>) {
+        match response {
+            Ok(response) => {
+                let total = response.total.unwrap_or(response.results.len() as u64);
+                self.xref_summary = Some(XrefSummary::new(collection_id, &response.results, total));
+                self.details_tab = DetailsTab::Xref;
+            }
+            Err(e) => self.notify(
+                NotificationKind::Error,
+                NotificationSource::Action,
+                format!("Failed to fetch xref results: {e}"),
+            ),
+        }
+    }
+
+    pub(crate) fn notify(
+        &mut self,
+        kind: NotificationKind,
+        source: NotificationSource,
+        message: String,
+    ) {
+        self.notifications
+            .push(Notification::new(kind, source, message));
+    }
+
+    pub(crate) fn toast(&mut self, kind: NotificationKind, message: String) {
+        self.toasts.push(kind, message);
+    }
+
+    /// Seconds between two fetches, depending on whether the terminal is
+    /// focused. Instances in maintenance are polled as rarely as while the
+    /// terminal isn't focused.
+    pub fn fetch_interval(&self) -> i64 {
+        match self.focused && !self.metadata.maintenance {
+            true => self.focused_fetch_interval(),
+            false => self
+                .config
+                .unfocused_fetch_interval
+                .max(self.focused_fetch_interval()),
+        }
+    }
+
+    /// The configured seconds between fetches, unless they were adjusted.
+    fn focused_fetch_interval(&self) -> i64 {
+        let remembered = self
+            .state
+            .fetch_interval
+            .filter(|_| self.config.remember_fetch_interval);
+        self.adjusted_fetch_interval
+            .or(remembered)
+            .unwrap_or(self.config.fetch_interval)
+    }
+
+    /// Whether the fetch interval was adjusted with `+` and `-`.
+    pub fn is_fetch_interval_adjusted(&self) -> bool {
+        self.focused_fetch_interval() != self.config.fetch_interval
+    }
+
+    /// Fetches more often, or with `faster` unset less often, going through
+    /// [`FETCH_INTERVAL_STEPS`].
+    pub(crate) fn adjust_fetch_interval(&mut self, faster: bool) {
+        let current = self.focused_fetch_interval();
+        let next = match faster {
+            true => FETCH_INTERVAL_STEPS.iter().rev().find(|s| **s < current),
+            false => FETCH_INTERVAL_STEPS.iter().find(|s| **s > current),
+        };
+        let Some(&interval) = next else {
+            return self.toast(
+                NotificationKind::Info,
+                format!("Already fetching every {current}s"),
+            );
+        };
+        self.adjusted_fetch_interval = Some(interval);
+        if self.config.remember_fetch_interval {
+            self.reload_state();
+            self.state.fetch_interval = Some(interval);
+            self.save_state();
+        }
+        self.toast(
+            NotificationKind::Info,
+            format!("Fetching every {interval}s"),
+        );
+    }
+
+    pub fn current_profile(&self) -> Profile {
+        self.config.profiles[self.current_profile].clone()
+    }
+
+    pub fn toggle_profile_selector(&mut self) {
+        self.current_view = match self.current_view {
+            CurrentView::Main => CurrentView::ProfileSwitcher,
+            CurrentView::ProfileSwitcher => CurrentView::Main,
+        }
+    }
+
+    /// The part of the interface key presses currently go to.
+    pub fn key_context(&self) -> KeyContext {
+        match (self.show_request_log, self.show_profile_selector()) {
+            _ if self.login_prompt.is_some() => KeyContext::Login,
+            _ if self.upload_prompt.is_some() => KeyContext::Upload,
+            _ if self.is_searching => KeyContext::Search,
+            _ if self.tag_editor.is_some() => KeyContext::Tags,
+            _ if self.show_help => KeyContext::Help,
+            _ if self.metadata_diff.is_some() => KeyContext::Comparison,
+            _ if self.shown_failure_report().is_some() => KeyContext::FailedDocuments,
+            _ if self.shows_links() => KeyContext::Links,
+            _ if self.details_focused && self.selected_result().is_some() => KeyContext::Details,
+            (true, _) => KeyContext::RequestLog,
+            (false, true) => KeyContext::ProfileSelector,
+            (false, false) if self.is_zoomed() => KeyContext::Focus,
+            (false, false) => KeyContext::Main,
+        }
+    }
+
+    pub fn show_profile_selector(&self) -> bool {
+        self.current_view == CurrentView::ProfileSwitcher
+    }
+
+    pub fn set_profile(&mut self, profile: String) -> color_eyre::Result<()> {
+        let p = self.config.profiles.iter().find(|p| p.name == profile);
+        match p {
+            Some(p) => {
+                self.profile_tablestate.select(Some(p.index));
+                self.current_profile = p.index;
+                Ok(())
+            }
+            None => Err(eyre!("Profile '{:?}' not found", profile)),
+        }
+    }
+
+    /// Warns if there was a long gap since the last tick. The event loop was
+    /// blocked then, e.g. by a frozen fetch or because the machine was
+    /// asleep, so the data shown can be older than the status bar suggests.
+    pub fn check_ticks(&mut self, now: DateTime<Local>) {
+        let Some(gap) = self.last_tick.replace(now).map(|last| now - last) else {
+            return;
+        };
+        if gap < BLOCKED_AFTER {
+            return;
+        }
+        self.notify(
+            NotificationKind::Warning,
+            NotificationSource::System,
+            format!(
+                "aleph-tui was blocked for {}s, the data shown may be stale",
+                gap.num_seconds()
+            ),
+        );
+        self.fetch_requested = true;
+    }
+
+    /// Blanks the screen once there was no input for
+    /// [`Config::screensaver_after`] minutes.
+    pub fn check_idle(&mut self, now: DateTime<Local>) {
+        if let Some(minutes) = self.config.screensaver_after {
+            if now - self.last_input >= Duration::minutes(minutes) {
+                self.screensaver = true;
+            }
+        }
+    }
+
+    /// Records input at `now`, waking up from the screensaver. Returns
+    /// whether it did, in which case the input isn't acted upon.
+    pub(crate) fn record_input(&mut self, now: DateTime<Local>) -> bool {
+        self.last_input = now;
+        if !std::mem::take(&mut self.screensaver) {
+            return false;
+        }
+        if self.config.screensaver_confirm_profile {
+            self.current_view = CurrentView::ProfileSwitcher;
+        }
+        true
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    pub(crate) fn profile_down(&mut self) {
+        if self.current_profile().index < self.config.profiles.len()
+            && self
+                .config
+                .profiles
+                .get(self.current_profile().index + 1)
+                .is_some()
+        {
+            self.switch_profile(self.current_profile + 1);
+        }
+    }
+
+    pub(crate) fn profile_up(&mut self) {
+        if self.current_profile().index > 0
+            && self
+                .config
+                .profiles
+                .get(self.current_profile().index - 1)
+                .is_some()
+        {
+            self.switch_profile(self.current_profile - 1);
+        }
+    }
+
+    /// Switches to the next (or, with `forward` unset, the previous) profile,
+    /// wrapping around at either end.
+    pub(crate) fn cycle_profile(&mut self, forward: bool) {
+        let count = self.config.profiles.len();
+        if count < 2 {
+            return;
+        }
+        let index = match forward {
+            true => (self.current_profile + 1) % count,
+            false => (self.current_profile + count - 1) % count,
+        };
+        self.switch_profile(index);
+    }
+
+    /// Makes `index` the current profile, keeping the table state, the
+    /// status and the collection state of the previous one around. The last
+    /// status of `index` is shown until it is fetched again.
+    fn switch_profile(&mut self, index: usize) {
+        // The status of a file belongs to no profile in particular.
+        if self.status_file.is_some() {
+            return;
+        }
+        let tablestate = self.profile_tablestates.remove(&index).unwrap_or_default();
+        let previous = std::mem::replace(&mut self.collection_tablestate, tablestate);
+        self.profile_tablestates
+            .insert(self.current_profile, previous);
+        let mut status = ProfileStatus {
+            status: Status::default(),
+            metadata: Metadata::default(),
+            fetched: self.last_fetch,
+            collections: std::mem::take(&mut self.collections),
+        };
+        // Later pages would be shown as the first one when switching back.
+        if self.status_page == 0 && !self.status.results.is_empty() {
+            status.status = std::mem::take(&mut self.status);
+            status.metadata = std::mem::take(&mut self.metadata);
+        }
+        self.profile_statuses.insert(self.current_profile, status);
+        // Its response would only hold up fetching `index`.
+        if let Some(task) = self.fetch_task.take() {
+            task.abort();
+            self.is_fetching = false;
+        }
+        self.current_profile = index;
+        self.profile_tablestate.select(Some(index));
+        self.clear_state();
+        if let Some(previous) = self.profile_statuses.remove(&index) {
+            self.status = previous.status;
+            self.metadata = previous.metadata;
+            self.last_fetch = previous.fetched;
+            self.collections = previous.collections;
+            self.sort_results();
+            self.clamp_selection();
+        }
+        self.fetch_requested = true;
+    }
+
+    pub(crate) fn collection_up(&mut self) {
+        let index = self.collection_tablestate.selected().unwrap_or_default();
+        if index > 0 {
+            self.collection_tablestate.select(Some(index - 1));
+        }
+    }
+
+    pub(crate) fn collection_down(&mut self) {
+        let index = self.collection_tablestate.selected().unwrap_or_default();
+        if index < self.row_count().saturating_sub(1) {
+            self.collection_tablestate.select(Some(index + 1));
+        }
+    }
+
+    /// Sorts by `column`, or reverses the sort direction if the results are
+    /// already sorted by it.
+    pub(crate) fn toggle_sort(&mut self, column: SortColumn) {
+        if self.sort_column == Some(column) {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_column = Some(column);
+            self.sort_descending = false;
+        }
+        self.sort_results();
+    }
+
+    /// Sorts by the next column of the table, in the same direction, or by
+    /// none after the last one.
+    pub(crate) fn cycle_sort(&mut self) {
+        self.sort_column = match self.sort_column {
+            None => Some(SortColumn::ALL[0]),
+            Some(column) => SortColumn::ALL
+                .iter()
+                .skip_while(|other| **other != column)
+                .nth(1)
+                .copied(),
+        };
+        self.sort_results();
+    }
+
+    /// Reverses the order of the column sorted by, if any.
+    pub(crate) fn reverse_sort(&mut self) {
+        if self.sort_column.is_some() {
+            self.sort_descending = !self.sort_descending;
+            self.sort_results();
+        }
+    }
+
+    /// Applies the current sort order to the status results. Task rows are
+    /// part of their collection's result, so they move along with it.
+    pub(crate) fn sort_results(&mut self) {
+        let selected = self.selected_row();
+        let mut results = std::mem::take(&mut self.status.results);
+        self.sort(&mut results);
+        self.status.results = results;
+        if let Some(row) = &selected {
+            self.select_row(row);
+        }
+    }
+
+    fn sort(&self, results: &mut [StatusResult]) {
+        if let Some(column) = self.sort_column {
+            let now = Local::now();
+            results.sort_by(|a, b| {
+                let ordering = column.compare(a, b, |result| self.health(result, now));
+                match self.sort_descending {
+                    true => ordering.reverse(),
+                    false => ordering,
+                }
+            });
+        }
+        if self.group_by_tag {
+            // Stable, so the sort order is kept within groups. Untagged
+            // collections come last.
+            results.sort_by_key(|result| {
+                let group = self.tags(result.key()).first();
+                (group.is_none(), group)
+            });
+        }
+    }
+
+    fn clear_state(&mut self) {
+        self.status = Status::default();
+        self.metadata = Metadata::default();
+        self.notifications.clear_all();
+        self.version_changes.clear();
+        self.failure_report = None;
+        self.xref_summary = None;
+        self.status_size = None;
+        self.status_page = 0;
+        self.fetch_failing = false;
+        self.schema_error = None;
+        self.destructive.clear();
+        self.recent_failures.clear();
+        self.recent_progress.clear();
+        self.expanded.clear();
+        self.status_validators = None;
+        self.rate_limited_until = None;
+    }
+
+    /// Tasks which failed during the last hour, as far as aleph-tui saw.
+    pub fn failures_last_hour(&self) -> u64 {
+        self.recent_failures.iter().map(|(_, failed)| failed).sum()
+    }
+
+    /// Tasks processed per minute over each of the [`THROUGHPUT_WINDOWS`],
+    /// like a load average, once there were two fetches.
+    pub fn throughput_averages(&self) -> Option<Vec<f64>> {
+        THROUGHPUT_WINDOWS
+            .iter()
+            .map(|window| rolling_average(&self.recent_progress, Duration::minutes(*window)))
+            .collect()
+    }
+
+    pub fn print_version(&self) {
+        match BUILD_COMMIT {
+            "" => println!("aleph-tui {}", self.version),
+            commit => println!("aleph-tui {} ({commit})", self.version),
+        }
+    }
+
+    /// What to tell when asking for support: the build and the files in use.
+    pub fn about(&self) -> Vec<(&'static str, String)> {
+        let built = BUILD_TIME
+            .parse()
+            .ok()
+            .and_then(|time| DateTime::from_timestamp(time, 0))
+            .map_or("unknown".to_string(), |time| {
+                time.format("%Y-%m-%d %H:%M UTC").to_string()
+            });
+        let features: Vec<&str> = FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| *feature)
+            .collect();
+        let path = |path: Option<PathBuf>| {
+            path.map_or("none".to_string(), |path| path.display().to_string())
+        };
+        vec![
+            ("Version", self.version.clone()),
+            (
+                "Commit",
+                match BUILD_COMMIT {
+                    "" => "unknown".to_string(),
+                    commit => commit.to_string(),
+                },
+            ),
+            ("Built", built),
+            (
+                "Features",
+                match features.is_empty() {
+                    true => "none".to_string(),
+                    false => features.join(", "),
+                },
+            ),
+            ("Config", path(config_path().ok())),
+            ("State", path(self.state_file.clone())),
+        ]
+    }
+
+    pub fn print_about(&self) {
+        for (name, value) in self.about() {
+            println!("{:<9} {value}", format!("{name}:"));
+        }
+    }
+
+    pub fn print_help(&self) {
+        println!("aleph-tui");
         println!();
         println!("USAGE");
-        println!("aleph-tui [PROFILE]");
+        println!("aleph-tui [OPTIONS] [PROFILE]");
+        println!("aleph-tui config export [FILE]   Write the config without secrets");
+        println!("aleph-tui config import FILE     Replace the config, keeping its secrets");
         println!();
         println!("OPTIONS");
-        println!("--version   Print version");
-        println!("--help      Show help");
+        println!("--version             Print version");
+        println!("--about               Print the version, commit, build time, features and files in use");
+        println!("--help                Show help");
+        println!("--color               When to use colors: auto (default), always or never");
+        println!("--from-file FILE      Show the status saved to FILE instead of fetching it");
+        println!("--metadata-file FILE  Show the metadata saved to FILE along with it");
+        println!();
+        println!("KEYS");
+        for binding in keymap::BINDINGS {
+            println!("{:<11} {}", binding.keys, binding.description);
+        }
     }
 }
 
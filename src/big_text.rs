@@ -0,0 +1,47 @@
+/// Height in rows of text rendered by [`lines`].
+pub const HEIGHT: usize = 5;
+
+/// Glyphs of the digits, 3 columns wide, `#` marking filled cells.
+const DIGITS: [[&str; HEIGHT]; 10] = [
+    ["###", "# #", "# #", "# #", "###"],
+    ["  #", "  #", "  #", "  #", "  #"],
+    ["###", "  #", "###", "#  ", "###"],
+    ["###", "  #", "###", "  #", "###"],
+    ["# #", "# #", "###", "  #", "  #"],
+    ["###", "#  ", "###", "  #", "###"],
+    ["###", "#  ", "###", "# #", "###"],
+    ["###", "  #", "  #", "  #", "  #"],
+    ["###", "# #", "###", "# #", "###"],
+    ["###", "# #", "###", "  #", "###"],
+];
+
+/// Renders the digits of `text` large enough to be read from across a room,
+/// drawing filled cells with `fill`, e.g. `█`. Other characters are left
+/// out.
+pub fn lines(text: &str, fill: char) -> [String; HEIGHT] {
+    let glyphs: Vec<_> = text
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|digit| DIGITS[digit as usize])
+        .collect();
+    std::array::from_fn(|row| {
+        glyphs
+            .iter()
+            .map(|glyph| glyph[row].replace('#', &fill.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let lines = lines("1,0", '#');
+        assert_eq!(lines[0], "  # ###");
+        assert_eq!(lines[2], "  # # #");
+        assert_eq!(lines[4], "  # ###");
+    }
+}
@@ -0,0 +1,50 @@
+use std::process::{Child, Command, Stdio};
+
+use color_eyre::Result;
+
+/// Opens `url` with the platform's default browser, without waiting for it.
+/// Only web links are opened, since they come from the instance.
+pub fn open(url: &str) -> Result<()> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(color_eyre::eyre::eyre!("Not a web link: {url}"));
+    }
+    let mut child = spawn(url)?;
+    // The opener exits right away, it only has to be reaped.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn spawn(url: &str) -> Result<Child> {
+    Ok(Command::new("open")
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn spawn(url: &str) -> Result<Child> {
+    Ok(Command::new("xdg-open")
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?)
+}
+
+#[cfg(windows)]
+fn spawn(url: &str) -> Result<Child> {
+    // Unlike `cmd /C start`, this doesn't interpret `&` and the like.
+    Ok(Command::new("explorer")
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn spawn(_url: &str) -> Result<Child> {
+    Err(color_eyre::eyre::eyre!(
+        "Opening links is not supported on this platform"
+    ))
+}
@@ -0,0 +1,188 @@
+use std::{fmt, path::PathBuf};
+
+use chrono::Local;
+use color_eyre::Result;
+
+use crate::{app::App, persist, request_log};
+
+/// Characters of the payload kept on either side of where parsing failed.
+const EXCERPT_CONTEXT: usize = 300;
+/// Marks where parsing failed in the excerpt.
+const ERROR_MARKER: &str = "<<HERE>>";
+const ISSUES_URL: &str = "https://github.com/stchris/aleph-tui/issues";
+
+/// A response of Aleph which doesn't match the schema aleph-tui expects, with
+/// enough of the payload to report it.
+#[derive(Clone, Debug)]
+pub struct SchemaError {
+    pub message: String,
+    /// The payload around where parsing failed. String values and `secrets`
+    /// are redacted, only the structure and numbers are kept.
+    pub excerpt: String,
+}
+
+impl SchemaError {
+    pub fn new(error: &serde_json::Error, body: &[u8], secrets: &[&str]) -> Self {
+        let body = String::from_utf8_lossy(body);
+        let offset = offset(&body, error.line(), error.column());
+        let (redacted, offset) = redact_values(&body, offset);
+        let start = floor_char_boundary(&redacted, offset.saturating_sub(EXCERPT_CONTEXT));
+        let end = floor_char_boundary(&redacted, offset + EXCERPT_CONTEXT);
+        let excerpt = format!(
+            "{}{ERROR_MARKER}{}",
+            &redacted[start..offset],
+            &redacted[offset..end]
+        );
+        Self {
+            message: error.to_string(),
+            excerpt: request_log::redact(&excerpt, secrets),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Byte offset of the 1-based `line` and `column` serde_json reports.
+fn offset(text: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    floor_char_boundary(text, line_start + column.saturating_sub(1))
+}
+
+/// Replaces the string values of a JSON document with `"…"`, keeping object
+/// keys, numbers and the structure. Also maps `offset` in `json` to the
+/// corresponding offset in the redacted document.
+fn redact_values(json: &str, offset: usize) -> (String, usize) {
+    let mut redacted = String::with_capacity(json.len());
+    let mut mapped = None;
+    let mut chars = json.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if i >= offset && mapped.is_none() {
+            mapped = Some(redacted.len());
+        }
+        if c != '"' {
+            redacted.push(c);
+            continue;
+        }
+        let mut end = json.len();
+        let mut escaped = false;
+        for (j, c) in chars.by_ref() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    end = j + 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        if offset > i && offset < end && mapped.is_none() {
+            mapped = Some(redacted.len());
+        }
+        let is_key = json[end..].trim_start().starts_with(':');
+        match is_key {
+            true => redacted.push_str(&json[i..end]),
+            false => redacted.push_str("\"…\""),
+        }
+    }
+    let mapped = mapped.unwrap_or(redacted.len());
+    (redacted, mapped)
+}
+
+/// A summary of the config without URLs or tokens.
+fn config_summary(app: &App) -> String {
+    let config = &app.config;
+    [
+        format!("profiles: {}", config.profiles.len()),
+        format!("fetch_interval: {}", config.fetch_interval),
+        format!(
+            "unfocused_fetch_interval: {}",
+            config.unfocused_fetch_interval
+        ),
+        format!("merge_duplicates: {}", config.merge_duplicates),
+        format!("status_page_size: {:?}", config.status_page_size),
+        format!("merge_status_pages: {}", config.merge_status_pages),
+        format!("auto_select: {:?}", config.auto_select),
+        format!("parsing: {:?}", config.parsing),
+        format!("fetch_all_profiles: {}", config.fetch_all_profiles),
+        format!("max_fetches_per_host: {}", config.max_fetches_per_host),
+    ]
+    .map(|line| format!("- {line}"))
+    .join("\n")
+}
+
+/// The report for `error`, ready to be attached to an issue.
+pub fn render(app: &App, error: &SchemaError) -> String {
+    let or_unknown = |version: &Option<String>| version.clone().unwrap_or("unknown".to_string());
+    format!(
+        "# aleph-tui bug report\n\n\
+         Please check this report before attaching it to an issue at {ISSUES_URL}.\n\n\
+         ## Error\n\n\
+         The response of Aleph doesn't match what aleph-tui expects: {}\n\n\
+         ## Versions\n\n\
+         - aleph-tui: {}\n\
+         - Aleph: {}\n\
+         - followthemoney: {}\n\
+         - OS: {} {}\n\n\
+         ## Config\n\n\
+         {}\n\n\
+         ## Payload excerpt\n\n\
+         String values are redacted, {ERROR_MARKER} marks where parsing failed.\n\n\
+         ```json\n{}\n```\n",
+        error.message,
+        app.version,
+        or_unknown(&app.metadata.app.version),
+        or_unknown(&app.metadata.app.ftm_version),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        config_summary(app),
+        error.excerpt,
+    )
+}
+
+/// Writes the report for `error` to the temporary directory, returning its
+/// path.
+pub fn write(app: &App, error: &SchemaError) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "aleph-tui-report-{}.md",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    persist::write_atomic(&path, render(app, error).as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Status;
+
+    #[test]
+    fn test_schema_error() {
+        let body = br#"{"results": [{"name": "secret-token leak", "todo": "many"}], "total": 1}"#;
+        let error = serde_json::from_slice::<Status>(body).unwrap_err();
+        let error = SchemaError::new(&error, body, &["secret-token"]);
+        assert!(!error.excerpt.contains("leak"));
+        assert!(!error.excerpt.contains("secret-token"));
+        assert!(error
+            .excerpt
+            .contains(r#"{"name": "…", "todo": <<HERE>>"…"}"#));
+    }
+}
@@ -0,0 +1,125 @@
+use color_eyre::{eyre::eyre, Result};
+use toml::{Table, Value};
+
+/// Keys of a profile which hold credentials and are left out of bundles.
+/// Headers are usually there to get past a proxy, with a token as well.
+const SECRET_KEYS: [&str; 3] = ["token", "api_key", "headers"];
+/// Token of imported profiles for which there is no secret yet, as in the
+/// example config of the README.
+const TOKEN_PLACEHOLDER: &str = "<YOUR API TOKEN HERE>";
+
+fn parse(config: &str) -> Result<Table> {
+    config
+        .parse()
+        .map_err(|e| eyre!("Failed to parse config: {e}"))
+}
+
+fn profiles_mut(config: &mut Table) -> Option<&mut Table> {
+    config.get_mut("profiles")?.as_table_mut()
+}
+
+/// The config without the secrets of its profiles, to be shared with a team.
+pub fn export(config: &str) -> Result<String> {
+    let mut config = parse(config)?;
+    for profile in profiles_mut(&mut config)
+        .into_iter()
+        .flat_map(|profiles| profiles.iter_mut().map(|(_, profile)| profile))
+    {
+        if let Some(profile) = profile.as_table_mut() {
+            profile.retain(|key, _| !SECRET_KEYS.contains(&key));
+        }
+    }
+    Ok(format!(
+        "# aleph-tui config bundle, import it with `aleph-tui config import`.\n\
+         # Tokens and API keys are left out.\n\n{}",
+        toml::to_string(&config)?
+    ))
+}
+
+/// Result of [`import`].
+#[derive(Debug)]
+pub struct Import {
+    pub config: String,
+    /// Profiles of the bundle which the current config has no secrets for.
+    /// They get a placeholder token which needs to be replaced.
+    pub missing_secrets: Vec<String>,
+}
+
+/// Replaces `current` with the settings of `bundle`, keeping the secrets of
+/// the profiles both have in common and the profiles only `current` has.
+pub fn import(bundle: &str, current: Option<&str>) -> Result<Import> {
+    let mut config = parse(bundle)?;
+    let mut current = current.map(parse).transpose()?.unwrap_or_default();
+    let mut current_profiles = profiles_mut(&mut current)
+        .map(std::mem::take)
+        .unwrap_or_default();
+    let mut missing_secrets = Vec::new();
+    if !config.contains_key("profiles") {
+        config.insert("profiles".to_string(), Value::Table(Table::new()));
+    }
+    let profiles = profiles_mut(&mut config).ok_or_else(|| eyre!("profiles is not a table"))?;
+    for (name, profile) in profiles.iter_mut() {
+        let Some(profile) = profile.as_table_mut() else {
+            continue;
+        };
+        let known = current_profiles.remove(name);
+        let secrets = known
+            .as_ref()
+            .and_then(Value::as_table)
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| SECRET_KEYS.contains(&key.as_str()));
+        for (key, secret) in secrets {
+            profile.insert(key.clone(), secret.clone());
+        }
+        // Profiles which log in with a password have nothing to set.
+        let logs_in = profile.contains_key("email");
+        if !logs_in && !SECRET_KEYS.iter().any(|key| profile.contains_key(*key)) {
+            profile.insert("token".to_string(), TOKEN_PLACEHOLDER.into());
+            missing_secrets.push(name.clone());
+        }
+    }
+    profiles.extend(current_profiles);
+    Ok(Import {
+        config: toml::to_string(&config)?,
+        missing_secrets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_import() {
+        let current = r#"
+        default = "mine"
+        fetch_interval = 5
+        [profiles.shared]
+        url = "https://shared"
+        token = "secret"
+        [profiles.mine]
+        url = "https://mine"
+        api_key = "key"
+        "#;
+        let bundle = export(current).unwrap();
+        assert!(!bundle.contains("secret"));
+        assert!(!bundle.contains("key\""));
+
+        let bundle = bundle.replace("fetch_interval = 5", "fetch_interval = 10");
+        let bundle = format!("{bundle}\n[profiles.new]\nurl = \"https://new\"\n");
+        let import = import(&bundle, Some(current)).unwrap();
+        assert_eq!(import.missing_secrets, ["new"]);
+        let config = parse(&import.config).unwrap();
+        assert_eq!(config["fetch_interval"].as_integer(), Some(10));
+        assert_eq!(
+            config["profiles"]["shared"]["token"].as_str(),
+            Some("secret")
+        );
+        assert_eq!(config["profiles"]["mine"]["api_key"].as_str(), Some("key"));
+        assert_eq!(
+            config["profiles"]["new"]["token"].as_str(),
+            Some(TOKEN_PLACEHOLDER)
+        );
+    }
+}
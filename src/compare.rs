@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Keys shown first, since they are what usually explains why a dataset
+/// behaves differently on two instances.
+const VERSION_KEYS: [&str; 2] = ["app.version", "app.ftm_version"];
+
+/// The metadata of two profiles side by side.
+#[derive(Clone, Debug)]
+pub struct MetadataDiff {
+    pub profiles: [String; 2],
+    /// Versions first, then every other setting in alphabetical order.
+    pub rows: Vec<DiffRow>,
+}
+
+/// A setting of the metadata, e.g. `app.version`, and its value on each
+/// profile, `None` if a profile doesn't report it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub key: String,
+    pub values: [Option<String>; 2],
+}
+
+impl DiffRow {
+    pub fn differs(&self) -> bool {
+        self.values[0] != self.values[1]
+    }
+}
+
+impl MetadataDiff {
+    pub fn new(profiles: [String; 2], metadata: [&Value; 2]) -> Self {
+        let [first, second] = metadata.map(|metadata| {
+            let mut settings = BTreeMap::new();
+            flatten(metadata, String::new(), &mut settings);
+            settings
+        });
+        let mut keys: Vec<&String> = first.keys().chain(second.keys()).collect();
+        keys.sort_by_key(|key| (!VERSION_KEYS.contains(&key.as_str()), *key));
+        keys.dedup();
+        let rows = keys
+            .into_iter()
+            .map(|key| DiffRow {
+                key: key.clone(),
+                values: [first.get(key).cloned(), second.get(key).cloned()],
+            })
+            .collect();
+        Self { profiles, rows }
+    }
+}
+
+/// Collects the values of `value` by their dotted path, e.g.
+/// `app.locales.en`. Arrays are kept as a whole, written as JSON.
+fn flatten(value: &Value, path: String, settings: &mut BTreeMap<String, String>) {
+    let text = match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{path}.{key}"),
+                };
+                flatten(value, path, settings);
+            }
+            return;
+        }
+        Value::String(text) => text.clone(),
+        Value::Null => "-".to_string(),
+        value => value.to_string(),
+    };
+    settings.insert(path, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_diff() {
+        let staging = json!({
+            "maintenance": false,
+            "app": {"version": "4.0.0", "ftm_version": "3.5.8", "publish": true},
+        });
+        let prod = json!({
+            "maintenance": false,
+            "app": {"version": "3.15.5", "ftm_version": "3.5.8", "locales": ["en"]},
+        });
+        let diff = MetadataDiff::new(
+            ["staging".to_string(), "prod".to_string()],
+            [&staging, &prod],
+        );
+        let keys: Vec<_> = diff.rows.iter().map(|row| row.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "app.ftm_version",
+                "app.version",
+                "app.locales",
+                "app.publish",
+                "maintenance"
+            ]
+        );
+        let differs: Vec<_> = diff.rows.iter().map(DiffRow::differs).collect();
+        assert_eq!(differs, [false, true, true, true, false]);
+        assert_eq!(diff.rows[2].values, [None, Some("[\"en\"]".to_string())]);
+    }
+}
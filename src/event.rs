@@ -1,11 +1,9 @@
-use std::{
-    sync::mpsc,
-    thread,
-    time::{Duration, Instant},
-};
+use std::time::Duration;
 
-use color_eyre::Result;
-use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{Event as CrosstermEvent, EventStream, KeyEvent, KeyEventKind, MouseEvent};
+use futures_util::StreamExt;
+use tokio::{sync::mpsc, task::JoinHandle};
 
 /// Terminal events.
 #[derive(Clone, Copy, Debug)]
@@ -18,70 +16,64 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Terminal window gained focus.
+    FocusGained,
+    /// Terminal window lost focus.
+    FocusLost,
 }
 
 /// Terminal event handler.
 #[derive(Debug)]
 pub struct EventHandler {
-    /// Event sender channel.
-    #[allow(dead_code)]
-    sender: mpsc::Sender<Event>,
     /// Event receiver channel.
-    receiver: mpsc::Receiver<Event>,
-    /// Event handler thread.
-    #[allow(dead_code)]
-    handler: thread::JoinHandle<()>,
+    receiver: mpsc::UnboundedReceiver<Event>,
+    /// Event handler task.
+    handler: JoinHandle<()>,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
     pub fn new(tick_rate: u64) -> Self {
         let tick_rate = Duration::from_millis(tick_rate);
-        let (sender, receiver) = mpsc::channel();
-        let handler = {
-            let sender = sender.clone();
-            thread::spawn(move || {
-                let mut last_tick = Instant::now();
-                loop {
-                    let timeout = tick_rate
-                        .checked_sub(last_tick.elapsed())
-                        .unwrap_or(tick_rate);
-
-                    if event::poll(timeout).expect("unable to poll for event") {
-                        match event::read().expect("unable to read event") {
-                            CrosstermEvent::Key(e) => {
-                                if e.kind == event::KeyEventKind::Press {
-                                    sender.send(Event::Key(e))
-                                } else {
-                                    Ok(()) // ignore KeyEventKind::Release on windows
-                                }
-                            }
-                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
-                            CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            _ => unimplemented!(),
-                        }
-                        .expect("failed to send terminal event")
-                    }
-
-                    if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
-                        last_tick = Instant::now();
-                    }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let handler = tokio::spawn(async move {
+            let mut reader = EventStream::new();
+            let mut tick = tokio::time::interval(tick_rate);
+            loop {
+                let event = tokio::select! {
+                    _ = tick.tick() => Event::Tick,
+                    Some(Ok(event)) = reader.next() => match event {
+                        // ignore KeyEventKind::Release on windows
+                        CrosstermEvent::Key(e) if e.kind == KeyEventKind::Press => Event::Key(e),
+                        CrosstermEvent::Mouse(e) => Event::Mouse(e),
+                        CrosstermEvent::Resize(w, h) => Event::Resize(w, h),
+                        CrosstermEvent::FocusGained => Event::FocusGained,
+                        CrosstermEvent::FocusLost => Event::FocusLost,
+                        _ => continue,
+                    },
+                };
+                if sender.send(event).is_err() {
+                    break;
                 }
-            })
-        };
-        Self {
-            sender,
-            receiver,
-            handler,
-        }
+            }
+        });
+        Self { receiver, handler }
     }
 
-    /// Receive the next event from the handler thread.
+    /// Receive the next event from the handler task.
     ///
-    /// This function will always block the current thread if
-    /// there is no data available and it's possible for more data to be sent.
-    pub fn next(&self) -> Result<Event> {
-        Ok(self.receiver.recv()?)
+    /// This waits until there is an event, without blocking the runtime, so
+    /// it can be raced against other futures in the main loop.
+    pub async fn next(&mut self) -> Result<Event> {
+        self.receiver
+            .recv()
+            .await
+            .ok_or_else(|| eyre!("The event handler stopped"))
+    }
+}
+
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        self.handler.abort();
     }
 }
@@ -0,0 +1,169 @@
+use itertools::Itertools;
+
+use crate::models::Entity;
+
+/// Failed documents of a collection, grouped by the kind of error.
+#[derive(Clone, Debug, Default)]
+pub struct FailureReport {
+    /// Key of the collection, see [`crate::models::StatusResult::key`].
+    pub key: String,
+    /// Clusters of similar errors, most frequent first.
+    pub clusters: Vec<(String, usize)>,
+    /// The documents the clusters were built from.
+    pub documents: Vec<FailedDocument>,
+    /// Number of documents the clusters were built from.
+    pub sampled: usize,
+    /// Number of failed documents in the collection.
+    pub total: u64,
+}
+
+impl FailureReport {
+    pub fn new(key: String, documents: Vec<FailedDocument>, total: u64) -> Self {
+        let clusters = documents
+            .iter()
+            .map(|document| normalize(&document.error))
+            .counts()
+            .into_iter()
+            .sorted_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)))
+            .collect();
+        Self {
+            key,
+            clusters,
+            sampled: documents.len(),
+            documents,
+            total,
+        }
+    }
+
+    /// One line summary like "TimeoutError ×842, OCRFailure ×17".
+    pub fn summary(&self) -> String {
+        self.clusters
+            .iter()
+            .map(|(error, count)| format!("{error} ×{count}"))
+            .join(", ")
+    }
+
+    /// Number of failed documents by MIME type, most frequent first, to tell
+    /// whether failures are down to one kind of file.
+    pub fn mime_types(&self) -> Vec<(&str, usize)> {
+        self.documents
+            .iter()
+            .map(|document| document.mime_type.as_deref().unwrap_or("unknown"))
+            .counts()
+            .into_iter()
+            .sorted_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)))
+            .collect()
+    }
+}
+
+/// What Aleph stored about a document it failed to process.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FailedDocument {
+    pub name: String,
+    pub mime_type: Option<String>,
+    /// In bytes.
+    pub size: Option<u64>,
+    /// Where the document came from, e.g. the path it was crawled from.
+    pub source: Option<String>,
+    pub error: String,
+}
+
+impl From<&Entity> for FailedDocument {
+    fn from(entity: &Entity) -> Self {
+        let first = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| entity.property(name).into_iter().next())
+        };
+        // Numbers are usually sent as strings, but not by every version.
+        let size = entity
+            .properties
+            .get("fileSize")
+            .and_then(|values| values.first())
+            .and_then(|value| value.as_u64().or_else(|| value.as_str()?.parse().ok()));
+        Self {
+            name: first(&["fileName", "title", "name"]).unwrap_or_else(|| entity.id.clone()),
+            mime_type: first(&["mimeType"]),
+            size,
+            source: first(&["sourceUrl"]),
+            error: first(&["processingError"]).unwrap_or_default(),
+        }
+    }
+}
+
+/// Reduces an error message to something comparable between documents: the
+/// exception class if there is one, otherwise the message with all numbers
+/// masked out.
+fn normalize(error: &str) -> String {
+    let line = error.lines().next().unwrap_or_default().trim();
+    if let Some((class, _)) = line.split_once(':') {
+        let is_class = !class.is_empty()
+            && class
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if is_class {
+            return class.to_string();
+        }
+    }
+    let masked: String = line
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect();
+    let masked = masked.split_whitespace().join(" ");
+    match masked.chars().count() > 60 {
+        true => masked.chars().take(59).chain(['…']).collect(),
+        false => masked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("TimeoutError: request to 10.0.0.1 timed out"),
+            "TimeoutError"
+        );
+        assert_eq!(
+            normalize("Could not  extract page 12 of 300\nTraceback..."),
+            "Could not extract page ## of ###"
+        );
+    }
+
+    #[test]
+    fn test_clusters() {
+        let documents =
+            ["OCRFailure: page 1", "TimeoutError: 1s", "TimeoutError: 5s"].map(|error| {
+                FailedDocument {
+                    error: error.to_string(),
+                    ..Default::default()
+                }
+            });
+        let report = FailureReport::new("94".to_string(), documents.to_vec(), 3);
+        assert_eq!(report.summary(), "TimeoutError ×2, OCRFailure ×1");
+    }
+
+    #[test]
+    fn test_failed_document() {
+        let entity: Entity = serde_json::from_value(serde_json::json!({
+            "id": "a1",
+            "schema": "Pages",
+            "properties": {
+                "fileName": ["scan.tiff"],
+                "mimeType": ["image/tiff"],
+                "fileSize": ["12345"],
+                "processingError": ["OCRFailure: page 1"]
+            }
+        }))
+        .unwrap();
+        let document = FailedDocument::from(&entity);
+        assert_eq!(document.name, "scan.tiff");
+        assert_eq!(document.size, Some(12345));
+        assert_eq!(document.source, None);
+
+        let report = FailureReport::new("94".to_string(), vec![document.clone(), document], 2);
+        assert_eq!(report.mime_types(), [("image/tiff", 2)]);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::app::SortColumn;
+
+/// How counters are written, configured in the `[display]` table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    /// Put between groups of three digits. Empty to turn grouping off.
+    pub separator: String,
+    /// Numbers below this are written without grouping.
+    pub group_from: u64,
+    /// Columns which are never grouped, e.g. because they are narrow.
+    pub ungrouped: Vec<SortColumn>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            separator: ",".to_string(),
+            group_from: 0,
+            ungrouped: Vec::new(),
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn format(&self, column: SortColumn, value: u64) -> String {
+        let digits = value.to_string();
+        if self.separator.is_empty() || value < self.group_from || self.ungrouped.contains(&column)
+        {
+            return digits;
+        }
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push_str(&self.separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let mut format = NumberFormat::default();
+        assert_eq!(format.format(SortColumn::Todo, 999), "999");
+        assert_eq!(format.format(SortColumn::Todo, 1_234_567), "1,234,567");
+
+        format.separator = "\u{202f}".to_string();
+        format.group_from = 10_000;
+        format.ungrouped = vec![SortColumn::Doing];
+        assert_eq!(format.format(SortColumn::Todo, 9_999), "9999");
+        assert_eq!(format.format(SortColumn::Todo, 12_345), "12\u{202f}345");
+        assert_eq!(format.format(SortColumn::Doing, 12_345), "12345");
+    }
+}
@@ -0,0 +1,130 @@
+/// Explanation of something shown in aleph-tui, for the help overlay.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub term: &'static str,
+    pub text: &'static str,
+}
+
+/// The columns of the collection table, in order.
+pub const COLUMNS: &[Entry] = &[
+    Entry {
+        term: "Start Time",
+        text: "When the first task of the job was queued",
+    },
+    Entry {
+        term: "Todo",
+        text: "Tasks waiting in the queue for a worker",
+    },
+    Entry {
+        term: "Eff. Todo",
+        text: "Todo without aborted and cancelled tasks, which will never run",
+    },
+    Entry {
+        term: "Doing",
+        text: "Tasks a worker is processing right now",
+    },
+    Entry {
+        term: "Success",
+        text: "Tasks which finished without errors",
+    },
+    Entry {
+        term: "Failed",
+        text: "Tasks which raised an error, usually after being retried; `e` shows their errors",
+    },
+    Entry {
+        term: "Aborted",
+        text: "Tasks which were stopped while running, e.g. because their job was cancelled",
+    },
+    Entry {
+        term: "Aborting",
+        text: "Tasks which are being stopped",
+    },
+    Entry {
+        term: "Cancel",
+        text: "Tasks which were cancelled before they ran",
+    },
+    Entry {
+        term: "Health",
+        text: "Score from 0 (worst) to 100 computed by aleph-tui from failures, stalls and age",
+    },
+    Entry {
+        term: "Progress",
+        text: "Share of the tasks which finished, without aborted and cancelled ones",
+    },
+];
+
+/// The stages of Aleph's processing pipeline, as they appear in task names.
+pub const STAGES: &[Entry] = &[
+    Entry {
+        term: "ingest",
+        text: "Extracts text and entities from uploaded files, including OCR of images and scans",
+    },
+    Entry {
+        term: "analyze",
+        text: "Tags entities with the names, countries, emails and the like found in their text",
+    },
+    Entry {
+        term: "index",
+        text: "Writes entities to the search index, so that they can be found",
+    },
+    Entry {
+        term: "xref",
+        text: "Cross-references the entities of a collection against other collections",
+    },
+    Entry {
+        term: "reingest",
+        text: "Ingests all files of a collection again",
+    },
+    Entry {
+        term: "reindex",
+        text: "Writes all entities of a collection to the search index again",
+    },
+    Entry {
+        term: "loadmapping",
+        text: "Generates entities from a table using an entity mapping",
+    },
+    Entry {
+        term: "flushmapping",
+        text: "Deletes the entities generated by an entity mapping",
+    },
+    Entry {
+        term: "exportsearch",
+        text: "Exports search results to a file",
+    },
+    Entry {
+        term: "exportxref",
+        text: "Exports cross-referencing results to a file",
+    },
+    Entry {
+        term: "updateentity",
+        text: "Applies changes made to an entity, e.g. in the entity editor",
+    },
+    Entry {
+        term: "pruneentity",
+        text: "Deletes an entity and everything derived from it",
+    },
+];
+
+/// The explanation of the stage a task is named after. Task names are
+/// matched regardless of case and separators, e.g. `export-search` matches
+/// `exportsearch`.
+pub fn stage(task: &str) -> Option<&'static Entry> {
+    let normalized: String = task
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    STAGES.iter().find(|entry| entry.term == normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage() {
+        assert_eq!(stage("Export-Search").map(|e| e.term), Some("exportsearch"));
+        assert_eq!(stage("ingest").map(|e| e.term), Some("ingest"));
+        assert!(stage("unknown").is_none());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::Duration;
+
+/// Processing which hasn't made progress for this long counts fully against
+/// the health score.
+pub const MAX_STALL: Duration = Duration::minutes(30);
+/// Jobs running for this long count fully against the health score.
+const MAX_AGE: Duration = Duration::hours(24);
+
+/// How much each component counts towards the health score, see [`score`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthWeights {
+    pub failures: f64,
+    pub stall: f64,
+    pub age: f64,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            failures: 1.0,
+            stall: 1.0,
+            age: 0.5,
+        }
+    }
+}
+
+/// Fraction of `max` that `duration` amounts to, at most 1.
+fn fraction(duration: Duration, max: Duration) -> f64 {
+    (duration.num_seconds().max(0) as f64 / max.num_seconds() as f64).min(1.0)
+}
+
+/// Health of a collection from 0 (worst) to 100, from the share of its
+/// finished tasks which failed, how long it hasn't made progress and how
+/// long it has been running, weighted by `weights`.
+pub fn score(weights: HealthWeights, failure_rate: f64, stalled: Duration, age: Duration) -> u8 {
+    let components = [
+        (weights.failures, failure_rate.clamp(0.0, 1.0)),
+        (weights.stall, fraction(stalled, MAX_STALL)),
+        (weights.age, fraction(age, MAX_AGE)),
+    ];
+    let total: f64 = components.iter().map(|(weight, _)| weight.max(0.0)).sum();
+    if total == 0.0 {
+        return 100;
+    }
+    let penalty: f64 = components
+        .iter()
+        .map(|(weight, value)| weight.max(0.0) * value)
+        .sum();
+    (100.0 * (1.0 - penalty / total)).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let weights = HealthWeights::default();
+        let zero = Duration::zero();
+        assert_eq!(score(weights, 0.0, zero, zero), 100);
+        assert_eq!(score(weights, 1.0, MAX_STALL * 2, MAX_AGE), 0);
+        assert_eq!(score(weights, 0.5, zero, zero), 80);
+        assert_eq!(score(weights, 0.0, MAX_STALL / 2, zero), 80);
+
+        let only_failures = HealthWeights {
+            failures: 1.0,
+            stall: 0.0,
+            age: 0.0,
+        };
+        assert_eq!(score(only_failures, 0.25, MAX_STALL, MAX_AGE), 75);
+    }
+}
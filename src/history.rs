@@ -0,0 +1,135 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use chrono::{DateTime, Duration, Local};
+use color_eyre::Result;
+use serde_json::{json, Value};
+
+use crate::{app::App, models::StatusResult, persist};
+
+/// How often the counters of a collection are sampled at most.
+const SAMPLE_INTERVAL: Duration = Duration::minutes(1);
+/// Number of samples kept per collection, a day's worth.
+const SAMPLES_LEN: usize = 24 * 60;
+/// Names of the [`Sample::values`].
+const COUNTERS: [&str; 4] = ["todo", "doing", "succeeded", "failed"];
+
+/// The counters of a collection at one point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub time: DateTime<Local>,
+    pub todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+impl Sample {
+    fn values(&self) -> [u64; 4] {
+        [self.todo, self.doing, self.succeeded, self.failed]
+    }
+}
+
+/// The counters of a collection over the last day, sampled once a minute, to
+/// graph them afterwards, see [`write`].
+#[derive(Clone, Debug, Default)]
+pub struct Samples {
+    /// Label of the collection when it was last sampled.
+    pub label: String,
+    pub samples: VecDeque<Sample>,
+}
+
+impl Samples {
+    pub fn record(&mut self, result: &StatusResult, now: DateTime<Local>) {
+        self.label = result.label().to_string();
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| now - last.time < SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        if self.samples.len() == SAMPLES_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            time: now,
+            todo: result.todo,
+            doing: result.doing,
+            succeeded: result.succeeded,
+            failed: result.failed,
+        });
+    }
+}
+
+/// The samples as time series in the format of Grafana's JSON datasources,
+/// one per collection and counter, with `[value, milliseconds]` datapoints.
+pub fn grafana<'a>(collections: impl IntoIterator<Item = &'a Samples>) -> Value {
+    let series: Vec<Value> = collections
+        .into_iter()
+        .filter(|samples| !samples.samples.is_empty())
+        .flat_map(|samples| {
+            COUNTERS.iter().enumerate().map(|(index, name)| {
+                let datapoints: Vec<Value> = samples
+                    .samples
+                    .iter()
+                    .map(|sample| json!([sample.values()[index], sample.time.timestamp_millis()]))
+                    .collect();
+                json!({
+                    "target": format!("{} {name}", samples.label),
+                    "datapoints": datapoints,
+                })
+            })
+        })
+        .collect();
+    Value::Array(series)
+}
+
+/// Writes the samples of all collections of the current profile to the
+/// temporary directory, returning its path.
+pub fn write(app: &App) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "aleph-tui-history-{}-{}.json",
+        app.current_profile().name,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let mut collections: Vec<&Samples> = app.collections.values().map(|c| &c.samples).collect();
+    collections.sort_by(|a, b| a.label.cmp(&b.label));
+    let series = serde_json::to_vec(&grafana(collections))?;
+    persist::write_atomic(&path, &series)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::models::Status;
+
+    #[test]
+    fn test_grafana() {
+        let status: Status =
+            serde_json::from_str(&read_to_string("testdata/status.json").unwrap()).unwrap();
+        let result = &status.results[0];
+        let now = Local::now();
+        let mut samples = Samples::default();
+        samples.record(result, now);
+        // Too soon after the first one.
+        samples.record(result, now + Duration::seconds(5));
+        samples.record(result, now + Duration::minutes(1));
+        assert_eq!(samples.samples.len(), 2);
+
+        let series = grafana([&samples]);
+        assert_eq!(series.as_array().unwrap().len(), 4);
+        assert_eq!(
+            series[2],
+            json!({
+                "target": format!("{} succeeded", result.label()),
+                "datapoints": [
+                    [result.succeeded, now.timestamp_millis()],
+                    [result.succeeded, (now + Duration::minutes(1)).timestamp_millis()]
+                ]
+            })
+        );
+    }
+}
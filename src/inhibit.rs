@@ -0,0 +1,81 @@
+use std::process::Child;
+
+use color_eyre::Result;
+
+/// Keeps the system from going to sleep while it is active, by running the
+/// platform's inhibitor tool as a child process.
+#[derive(Debug, Default)]
+pub struct SleepInhibitor {
+    child: Option<Child>,
+}
+
+impl SleepInhibitor {
+    pub fn is_active(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Starts or stops inhibiting sleep. Does nothing if the inhibitor already
+    /// is in the requested state.
+    pub fn set(&mut self, active: bool, reason: &str) -> Result<()> {
+        match (active, self.child.take()) {
+            (true, None) => self.child = Some(Self::spawn(reason)?),
+            (false, Some(mut child)) => {
+                child.kill()?;
+                child.wait()?;
+            }
+            (_, child) => self.child = child,
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn spawn(_reason: &str) -> Result<Child> {
+        use std::process::{Command, Stdio};
+
+        // `-w` ties the assertion to our process, so it is released even if
+        // aleph-tui doesn't get to clean up after itself.
+        Ok(Command::new("caffeinate")
+            .args(["-i", "-w", &std::process::id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn spawn(reason: &str) -> Result<Child> {
+        use std::process::{Command, Stdio};
+
+        // The inhibitor lock is held for as long as the wrapped command runs,
+        // which waits for our process to exit.
+        Ok(Command::new("systemd-inhibit")
+            .args([
+                "--what=idle:sleep",
+                "--who=aleph-tui",
+                &format!("--why={reason}"),
+                "--mode=block",
+                "tail",
+                &format!("--pid={}", std::process::id()),
+                "-f",
+                "/dev/null",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn spawn(_reason: &str) -> Result<Child> {
+        Err(color_eyre::eyre::eyre!(
+            "Keeping the system awake is not supported on this platform"
+        ))
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use itertools::Itertools;
+
+/// Part of the interface which currently receives key presses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyContext {
+    Main,
+    /// The selected collection is shown full screen.
+    Focus,
+    ProfileSelector,
+    RequestLog,
+    /// A search is being typed.
+    Search,
+    /// Tags of a collection are being typed.
+    Tags,
+    /// The password of a profile is being typed.
+    Login,
+    /// The path of a file or directory to upload is being typed.
+    Upload,
+    /// The explanations of columns and stages are shown.
+    Help,
+    /// The metadata of two profiles is shown side by side.
+    Comparison,
+    /// The documents of a failure report are shown.
+    FailedDocuments,
+    /// The links of the selected collection are shown.
+    Links,
+    /// The details pane is scrolled with `j` and `k`.
+    Details,
+}
+
+/// A documented key binding. The keys themselves are handled in
+/// [`crate::update::update`].
+#[derive(Clone, Copy, Debug)]
+pub struct Binding {
+    /// The key or keys, as written in the help.
+    pub keys: &'static str,
+    /// Short description for the shortcut line. Bindings without one are only
+    /// listed in the help.
+    pub hint: Option<&'static str>,
+    pub description: &'static str,
+    pub contexts: &'static [KeyContext],
+}
+
+const ALL: &[KeyContext] = &[
+    KeyContext::Main,
+    KeyContext::Focus,
+    KeyContext::ProfileSelector,
+    KeyContext::RequestLog,
+    KeyContext::Help,
+    KeyContext::Comparison,
+    KeyContext::FailedDocuments,
+    KeyContext::Links,
+    KeyContext::Details,
+];
+/// Where `Esc` closes what is shown instead of quitting.
+const OVERLAYS: &[KeyContext] = &[
+    KeyContext::Focus,
+    KeyContext::Help,
+    KeyContext::Comparison,
+    KeyContext::FailedDocuments,
+    KeyContext::Links,
+];
+const MAIN: &[KeyContext] = &[KeyContext::Main];
+const COLLECTION: &[KeyContext] = &[KeyContext::Main, KeyContext::Focus];
+
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: "q, Esc, ^C",
+        hint: Some("quit"),
+        description: "Quit, or clear the search applied with Esc",
+        contexts: &[
+            KeyContext::Main,
+            KeyContext::ProfileSelector,
+            KeyContext::RequestLog,
+            KeyContext::Details,
+        ],
+    },
+    Binding {
+        keys: "q, ^C",
+        hint: Some("quit"),
+        description: "Quit",
+        contexts: OVERLAYS,
+    },
+    Binding {
+        keys: "p",
+        hint: Some("select profile"),
+        description: "Select profile",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "p",
+        hint: Some("close"),
+        description: "Close the profile selector",
+        contexts: &[KeyContext::ProfileSelector],
+    },
+    Binding {
+        keys: "j, k",
+        hint: None,
+        description: "Move selection",
+        contexts: &[
+            KeyContext::Main,
+            KeyContext::Focus,
+            KeyContext::ProfileSelector,
+            KeyContext::FailedDocuments,
+            KeyContext::Links,
+        ],
+    },
+    Binding {
+        keys: "Enter",
+        hint: Some("switch"),
+        description: "Switch to the selected profile",
+        contexts: &[KeyContext::ProfileSelector],
+    },
+    Binding {
+        keys: "c",
+        hint: Some("compare"),
+        description: "Mark the selected profile for comparison, then compare the metadata of \
+                      another one with it",
+        contexts: &[KeyContext::ProfileSelector],
+    },
+    Binding {
+        keys: "c, Esc",
+        hint: Some("close"),
+        description: "Close the comparison",
+        contexts: &[KeyContext::Comparison],
+    },
+    Binding {
+        keys: "Tab",
+        hint: None,
+        description: "Scroll the details pane with j and k",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "j, k",
+        hint: Some("scroll"),
+        description: "Scroll the details pane",
+        contexts: &[KeyContext::Details],
+    },
+    Binding {
+        keys: "Tab",
+        hint: Some("back"),
+        description: "Move the selection with j and k again",
+        contexts: &[KeyContext::Details],
+    },
+    Binding {
+        keys: "Enter, Space",
+        hint: None,
+        description: "Show or hide the task rows of the selected collection",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "w",
+        hint: Some("watch"),
+        description: "Watch the selected collection",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "m",
+        hint: None,
+        description: "Mute alerts of the selected collection for 1h, 8h, 24h, then unmute",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "n",
+        hint: None,
+        description: "Ring the bell and flash the screen once the selected collection is done",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "e",
+        hint: Some("failures"),
+        description: "Fetch failed documents of the selected collection",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "E",
+        hint: None,
+        description: "Show the name, type, size and source of the failed documents fetched",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "E, Esc",
+        hint: Some("close"),
+        description: "Close the failed documents",
+        contexts: &[KeyContext::FailedDocuments],
+    },
+    Binding {
+        keys: "x",
+        hint: None,
+        description:
+            "Fetch how the cross-referencing matches of the selected collection were judged",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "Z",
+        hint: Some("focus"),
+        description: "Show the selected collection full screen",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "Z, Esc",
+        hint: Some("back"),
+        description: "Go back to the table",
+        contexts: &[KeyContext::Focus],
+    },
+    Binding {
+        keys: "W",
+        hint: None,
+        description: "Wrap long labels and URLs in the details pane",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "y",
+        hint: None,
+        description: "Copy the URL of the selected collection",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "l",
+        hint: None,
+        description: "List the links of the selected collection, to open or copy them",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "Enter, o",
+        hint: Some("open"),
+        description: "Open the selected link in the browser",
+        contexts: &[KeyContext::Links],
+    },
+    Binding {
+        keys: "y",
+        hint: Some("copy"),
+        description: "Copy the selected link",
+        contexts: &[KeyContext::Links],
+    },
+    Binding {
+        keys: "l, Esc",
+        hint: Some("close"),
+        description: "Close the links",
+        contexts: &[KeyContext::Links],
+    },
+    Binding {
+        keys: "[, ]",
+        hint: Some("tabs"),
+        description: "Switch between the tabs of the details pane",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "^↑, ^↓",
+        hint: None,
+        description: "Make the details pane larger or smaller",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "^←, ^→",
+        hint: None,
+        description: "Switch to the previous or next profile",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "PgUp, PgDn",
+        hint: None,
+        description: "Switch between pages of the status, if status_page_size is set",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "d",
+        hint: None,
+        description: "Show the log of requests to Aleph",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "d",
+        hint: Some("close"),
+        description: "Close the log of requests",
+        contexts: &[KeyContext::RequestLog],
+    },
+    Binding {
+        keys: "F1",
+        hint: Some("help"),
+        description: "Explain the columns and the stages of the selected collection",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "F1, Esc",
+        hint: Some("close"),
+        description: "Close the explanations",
+        contexts: &[KeyContext::Help],
+    },
+    Binding {
+        keys: "a",
+        hint: None,
+        description: "Acknowledge warnings about delete and flush jobs",
+        contexts: ALL,
+    },
+    Binding {
+        keys: "b",
+        hint: None,
+        description: "Write a bug report about a response Aleph sent which aleph-tui can't read",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "+, -",
+        hint: None,
+        description: "Fetch more or less often",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "H",
+        hint: None,
+        description: "Write the counters of the collections over the last day for Grafana",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "/",
+        hint: Some("search"),
+        description: "Search collections, queues and tasks",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "Enter",
+        hint: Some("apply"),
+        description: "Apply the search",
+        contexts: &[KeyContext::Search],
+    },
+    Binding {
+        keys: "Esc",
+        hint: Some("clear"),
+        description: "Clear the search",
+        contexts: &[KeyContext::Search],
+    },
+    Binding {
+        keys: "t",
+        hint: None,
+        description: "Tag the selected collection",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "Enter",
+        hint: Some("save"),
+        description: "Save the tags, separated by commas",
+        contexts: &[KeyContext::Tags],
+    },
+    Binding {
+        keys: "Esc",
+        hint: Some("cancel"),
+        description: "Keep the tags as they were",
+        contexts: &[KeyContext::Tags],
+    },
+    Binding {
+        keys: "L",
+        hint: None,
+        description: "Log in again, for profiles with an email instead of a token",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "Enter",
+        hint: Some("log in"),
+        description: "Log in with the password typed",
+        contexts: &[KeyContext::Login],
+    },
+    Binding {
+        keys: "Esc",
+        hint: Some("cancel"),
+        description: "Don't log in",
+        contexts: &[KeyContext::Login],
+    },
+    Binding {
+        keys: "U",
+        hint: None,
+        description: "Upload a file or directory to the selected collection",
+        contexts: COLLECTION,
+    },
+    Binding {
+        keys: "Enter",
+        hint: Some("upload"),
+        description: "Upload the file or directory typed",
+        contexts: &[KeyContext::Upload],
+    },
+    Binding {
+        keys: "Esc",
+        hint: Some("cancel"),
+        description: "Don't upload",
+        contexts: &[KeyContext::Upload],
+    },
+    Binding {
+        keys: "s",
+        hint: None,
+        description: "Sort by the next column, or in the order of Aleph after the last one",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "S",
+        hint: None,
+        description: "Reverse the sort order",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "g",
+        hint: None,
+        description: "Group collections by their first tag",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "1-7",
+        hint: None,
+        description: "Only show collections with nonzero todo, doing, succeeded, \
+                      failed, aborted, aborting or cancelled",
+        contexts: MAIN,
+    },
+    Binding {
+        keys: "0",
+        hint: None,
+        description: "Show all collections",
+        contexts: MAIN,
+    },
+];
+
+/// The shortcut line for `context`, like "`q`, `^C` - quit, `w` - watch".
+pub fn hints(context: KeyContext) -> String {
+    let hints = BINDINGS
+        .iter()
+        .filter(|binding| binding.contexts.contains(&context))
+        .filter_map(|binding| {
+            let hint = binding.hint?;
+            let keys = binding.keys.split(", ").map(|key| format!("`{key}`"));
+            Some(format!("{} - {hint}", keys.format(", ")))
+        })
+        .join(", ");
+    format!("Shortcuts: {hints}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints() {
+        assert_eq!(
+            hints(KeyContext::RequestLog),
+            "Shortcuts: `q`, `Esc`, `^C` - quit, `d` - close"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! The building blocks of aleph-tui. The binary wires them up to a terminal;
+//! they are a library so that examples can drive the interface as well.
+#![deny(clippy::unwrap_used)]
+
+pub mod api;
+pub mod app;
+pub mod big_text;
+pub mod browser;
+pub mod bug_report;
+pub mod bundle;
+pub mod compare;
+pub mod event;
+pub mod failures;
+pub mod format;
+pub mod glossary;
+pub mod health;
+pub mod history;
+pub mod inhibit;
+pub mod keymap;
+pub mod models;
+pub mod notification;
+pub mod persist;
+pub mod request_log;
+pub mod schedule;
+pub mod schema;
+pub mod sessions;
+pub mod state;
+#[cfg(test)]
+mod test_server;
+pub mod theme;
+pub mod timeline;
+pub mod tui;
+pub mod ui;
+pub mod update;
+pub mod upload;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod xref;
@@ -1,65 +1,194 @@
 #![deny(clippy::unwrap_used)]
 
-pub mod app;
-pub mod event;
-pub mod models;
-pub mod tui;
-pub mod ui;
-pub mod update;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use app::App;
-
-use color_eyre::Result;
-use event::{Event, EventHandler};
+use aleph_tui::{
+    app::{self, App},
+    bundle,
+    event::{Event, EventHandler},
+    persist,
+    theme::{Background, ColorMode, Theme},
+    tui::Tui,
+    update,
+};
+use color_eyre::{eyre::eyre, Result};
 use ratatui::prelude::{CrosstermBackend, Terminal};
-use tui::Tui;
+use tokio::sync::mpsc;
+
+/// How often the screen is redrawn while the terminal is not focused.
+const UNFOCUSED_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    human_panic::setup_panic!();
+    human_panic::setup_panic!(human_panic::metadata!().support(
+        "- Open an issue at https://github.com/stchris/aleph-tui/issues and attach the report"
+    ));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Bundles are imported before there is a config to start from.
+    if let Some(("config", args)) = args.split_first().map(|(cmd, args)| (cmd.as_str(), args)) {
+        return config_command(args);
+    }
     let mut app = App::new()?;
-    let first_arg = std::env::args().nth(1);
-    let quit = match first_arg {
-        Some(arg) => match arg.as_str() {
+    let mut color_mode = ColorMode::default();
+    let mut profile_given = false;
+    let mut status_file = None;
+    let mut metadata_file = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
             "--version" => {
                 app.print_version();
-                true
+                std::process::exit(0);
+            }
+            "--about" => {
+                app.print_about();
+                std::process::exit(0);
             }
             "--help" => {
                 app.print_help();
-                true
+                std::process::exit(0);
             }
-            _ => {
-                app.set_profile(arg)?;
-                false
+            "--from-file" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| eyre!("--from-file needs a file"))?;
+                status_file = Some(PathBuf::from(path));
             }
-        },
-        None => false,
-    };
-    if quit {
-        std::process::exit(0);
+            "--metadata-file" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| eyre!("--metadata-file needs a file"))?;
+                metadata_file = Some(PathBuf::from(path));
+            }
+            "--color" => {
+                let mode = args.next().ok_or_else(|| eyre!("--color needs a value"))?;
+                color_mode = mode.parse()?;
+            }
+            _ => match arg.strip_prefix("--color=") {
+                Some(mode) => color_mode = mode.parse()?,
+                None => {
+                    app.set_profile(arg)?;
+                    profile_given = true;
+                }
+            },
+        }
+    }
+    app.theme = Theme::new(color_mode);
+    if app.theme.colors {
+        app.theme.background = app.config.background.unwrap_or_else(Background::detect);
+    }
+    match &status_file {
+        Some(path) => app.load_status_file(path, metadata_file.as_deref())?,
+        None if metadata_file.is_some() => {
+            return Err(eyre!("--metadata-file only works with --from-file"))
+        }
+        None if !profile_given => app.auto_select_profile().await,
+        None => {}
+    }
+
+    #[cfg(feature = "web")]
+    let mirror = match &app.config.web_mirror {
+        Some(address) => Some(aleph_tui::web::Mirror::start(address).await?),
+        None => None,
     };
+    #[cfg(feature = "web")]
+    if let Some(mirror) = &mirror {
+        mirror.publish(&app);
+    }
+    #[cfg(not(feature = "web"))]
+    if app.config.web_mirror.is_some() {
+        app.toasts.push(
+            aleph_tui::notification::NotificationKind::Warning,
+            "web_mirror is set, but aleph-tui was built without the web feature".to_string(),
+        );
+    }
 
-    app.fetch()
-        .await
-        .unwrap_or_else(|e| app.error_message = e.to_string());
+    // The first snapshot is fetched on the first tick, so the interface shows
+    // up right away even if the instance is slow to respond.
+    app.fetch_requested = true;
 
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(50);
     let mut tui = Tui::new(terminal, events);
     tui.enter()?;
+    let (snapshot_sender, mut snapshots) = mpsc::unbounded_channel();
+    let (reply_sender, mut replies) = mpsc::unbounded_channel();
 
+    let mut last_draw: Option<Instant> = None;
     while !app.should_quit {
-        tui.draw(&mut app)?;
-        match tui.events.next()? {
-            Event::Tick => update::fetch(&mut app).await,
-            Event::Key(key_event) => update::update(&mut app, key_event).await,
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
-        };
+        let redraw = app.focused
+            || last_draw.is_none_or(|last_draw| last_draw.elapsed() >= UNFOCUSED_REDRAW_INTERVAL);
+        if redraw {
+            tui.draw(&mut app)?;
+            last_draw = Some(Instant::now());
+        }
+        // Fetches and the requests of key presses run in the background, so
+        // key presses are handled while a slow instance is still responding.
+        tokio::select! {
+            event = tui.events.next() => match event? {
+                Event::Tick => update::tick(&mut app, &snapshot_sender),
+                Event::Key(key_event) => update::update(&mut app, key_event, &reply_sender),
+                Event::Mouse(mouse_event) => update::mouse(&mut app, mouse_event),
+                Event::Resize(_, _) => {}
+                Event::FocusGained => app.focused = true,
+                Event::FocusLost => app.focused = false,
+            },
+            Some(snapshot) = snapshots.recv() => {
+                app.apply_snapshot(snapshot);
+                #[cfg(feature = "web")]
+                if let Some(mirror) = &mirror {
+                    mirror.publish(&app);
+                }
+            }
+            Some(reply) = replies.recv() => app.apply_reply(reply),
+        }
     }
 
     tui.exit()?;
     Ok(())
 }
+
+/// Runs `aleph-tui config export [FILE]` or `aleph-tui config import FILE`,
+/// see [`bundle`].
+fn config_command(args: &[String]) -> Result<()> {
+    let path = app::config_path()?;
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|e| eyre!("Failed to read {}: {e}", path.display()))
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[..] {
+        ["export"] => print!("{}", bundle::export(&read(&path)?)?),
+        ["export", file] => {
+            persist::write_atomic(Path::new(file), bundle::export(&read(&path)?)?.as_bytes())?;
+            println!("Exported {} to {file}", path.display());
+        }
+        ["import", file] => {
+            let current = match path.exists() {
+                true => Some(read(&path)?),
+                false => None,
+            };
+            let import = bundle::import(&read(Path::new(file))?, current.as_deref())?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            if let Some(backup) = persist::replace(&path, import.config.as_bytes())? {
+                println!("Backed up the previous config to {}", backup.display());
+            }
+            println!("Imported {file} to {}", path.display());
+            for profile in import.missing_secrets {
+                println!("Set the token or api_key of profile {profile} there");
+            }
+        }
+        _ => {
+            return Err(eyre!(
+                "Usage: aleph-tui config export [FILE] or aleph-tui config import FILE"
+            ))
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
 use serde::Deserialize;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Links {
-    #[serde(alias = "self")]
+    #[serde(rename = "self")]
     pub self_: String,
     pub xref_export: String,
     pub reconcile: String,
@@ -16,6 +19,8 @@ pub struct Collection {
     pub category: String,
     pub frequency: String,
     pub countries: Option<Vec<String>>,
+    /// Not reported by Aleph 3.x.
+    #[serde(default)]
     pub name: String,
     pub collection_id: String,
     pub foreign_id: String,
@@ -28,74 +33,77 @@ pub struct Collection {
     pub shallow: bool,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct Task {
-    pub todo: u32,
-    pub doing: u32,
-    pub succeeded: u32,
-    pub failed: u32,
-    pub aborted: u32,
-    pub aborting: u32,
-    pub cancelled: u32,
+    pub todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub aborted: u64,
+    pub aborting: u64,
+    pub cancelled: u64,
     pub min_ts: Option<String>,
     pub max_ts: Option<String>,
     pub name: String,
     pub remaining_time: Option<String>,
     pub took: Option<String>,
-    pub total: u32,
-    pub active: u32,
-    pub finished: u32,
+    pub total: u64,
+    pub active: u64,
+    pub finished: u64,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct Queue {
-    pub todo: u32,
-    pub doing: u32,
-    pub succeeded: u32,
-    pub failed: u32,
-    pub aborted: u32,
-    pub aborting: u32,
-    pub cancelled: u32,
+    pub todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub aborted: u64,
+    pub aborting: u64,
+    pub cancelled: u64,
     pub min_ts: Option<String>,
     pub max_ts: Option<String>,
     pub name: String,
     pub tasks: Vec<Task>,
     pub remaining_time: Option<String>,
     pub took: Option<String>,
-    pub total: u32,
-    pub active: u32,
-    pub finished: u32,
+    pub total: u64,
+    pub active: u64,
+    pub finished: u64,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct Batch {
-    pub todo: u32,
-    pub doing: u32,
-    pub succeeded: u32,
-    pub failed: u32,
-    pub aborted: u32,
-    pub aborting: u32,
-    pub cancelled: u32,
+    pub todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub aborted: u64,
+    pub aborting: u64,
+    pub cancelled: u64,
     pub min_ts: Option<String>,
     pub max_ts: Option<String>,
     pub name: String,
     pub queues: Vec<Queue>,
     pub remaining_time: Option<String>,
     pub took: Option<String>,
-    pub total: u32,
-    pub active: u32,
-    pub finished: u32,
+    pub total: u64,
+    pub active: u64,
+    pub finished: u64,
 }
 
+/// A result of the status, normalized from the schema of whichever Aleph
+/// version reported it, see [`StatusResultPayload`].
 #[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "StatusResultPayload")]
 pub struct StatusResult {
-    pub todo: u32,
-    pub doing: u32,
-    pub succeeded: u32,
-    pub failed: u32,
-    pub aborted: u32,
-    pub aborting: u32,
-    pub cancelled: u32,
+    pub todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub aborted: u64,
+    pub aborting: u64,
+    pub cancelled: u64,
     pub min_ts: Option<String>,
     pub max_ts: Option<String>,
     pub name: String,
@@ -103,15 +111,328 @@ pub struct StatusResult {
     pub collection: Option<Collection>,
     pub remaining_time: Option<String>,
     pub took: Option<String>,
-    pub total: u32,
-    pub active: u32,
-    pub finished: u32,
+    pub total: u64,
+    pub active: u64,
+    pub finished: u64,
+    /// The stages as Aleph 3.x reports them, which also make up the
+    /// batches of its results.
+    pub stages: Option<StageOrStages>,
+}
+
+/// A processing stage of a job, as reported by Aleph 3.x. A stage
+/// corresponds to a task of later versions.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Stage {
+    pub job_id: String,
+    pub stage: String,
+    pub pending: u64,
+    pub running: u64,
+    pub finished: u64,
+}
+
+/// Some Aleph 3.x versions report a single stage instead of a list.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StageOrStages {
+    Stage(Stage),
+    Stages(Vec<Stage>),
+}
+
+impl StageOrStages {
+    pub fn as_slice(&self) -> &[Stage] {
+        match self {
+            StageOrStages::Stage(stage) => std::slice::from_ref(stage),
+            StageOrStages::Stages(stages) => stages,
+        }
+    }
+}
+
+/// A result of the status in the schema of Aleph 3.x, with `pending`,
+/// `running` and `finished` counters and optional `stages`, or of 4.x, with
+/// `todo`, `doing` and the other counters and `batches`. Everything which
+/// isn't in both is optional here, so that type errors still point at the
+/// offending value for bug reports. What each version requires is checked
+/// when it is converted into a [`StatusResult`].
+#[derive(Deserialize)]
+struct StatusResultPayload {
+    finished: u64,
+    collection: Option<Collection>,
+    // 4.x
+    todo: Option<u64>,
+    doing: Option<u64>,
+    succeeded: Option<u64>,
+    failed: Option<u64>,
+    aborted: Option<u64>,
+    aborting: Option<u64>,
+    cancelled: Option<u64>,
+    min_ts: Option<String>,
+    max_ts: Option<String>,
+    name: Option<String>,
+    batches: Option<Vec<Batch>>,
+    remaining_time: Option<String>,
+    took: Option<String>,
+    total: Option<u64>,
+    active: Option<u64>,
+    // 3.x
+    pending: Option<u64>,
+    running: Option<u64>,
+    stages: Option<StageOrStages>,
+    start_time: Option<String>,
+    last_update: Option<String>,
+}
+
+impl TryFrom<StatusResultPayload> for StatusResult {
+    type Error = String;
+
+    fn try_from(payload: StatusResultPayload) -> Result<Self, Self::Error> {
+        let (Some(pending), None) = (payload.pending, payload.todo) else {
+            return payload.into_v4();
+        };
+        Ok(payload.into_v3(pending))
+    }
+}
+
+impl StatusResultPayload {
+    fn into_v4(self) -> Result<StatusResult, String> {
+        let required = |value: Option<u64>, field: &str| {
+            value.ok_or_else(|| format!("missing field `{field}`"))
+        };
+        Ok(StatusResult {
+            todo: required(self.todo, "todo")?,
+            doing: required(self.doing, "doing")?,
+            succeeded: required(self.succeeded, "succeeded")?,
+            failed: required(self.failed, "failed")?,
+            aborted: required(self.aborted, "aborted")?,
+            aborting: required(self.aborting, "aborting")?,
+            cancelled: required(self.cancelled, "cancelled")?,
+            min_ts: self.min_ts,
+            max_ts: self.max_ts,
+            name: self.name.ok_or("missing field `name`")?,
+            batches: self.batches.ok_or("missing field `batches`")?,
+            collection: self.collection,
+            remaining_time: self.remaining_time,
+            took: self.took,
+            total: required(self.total, "total")?,
+            active: required(self.active, "active")?,
+            finished: self.finished,
+            stages: None,
+        })
+    }
+
+    /// Aleph 3.x doesn't tell failed tasks apart, so finished ones count as
+    /// succeeded. The stages become the tasks of one batch per job.
+    fn into_v3(self, pending: u64) -> StatusResult {
+        let running = self.running.unwrap_or_default();
+        let stages = self
+            .stages
+            .as_ref()
+            .map_or(&[][..], StageOrStages::as_slice);
+        let batches = stages
+            .iter()
+            .chunk_by(|stage| stage.job_id.as_str())
+            .into_iter()
+            .map(|(job_id, stages)| {
+                let tasks: Vec<Task> = stages
+                    .map(|stage| Task {
+                        todo: stage.pending,
+                        doing: stage.running,
+                        succeeded: stage.finished,
+                        name: stage.stage.clone(),
+                        total: stage.pending + stage.running + stage.finished,
+                        active: stage.pending + stage.running,
+                        finished: stage.finished,
+                        ..Default::default()
+                    })
+                    .collect();
+                let sum = |counter: fn(&Task) -> u64| tasks.iter().map(counter).sum::<u64>();
+                let name = match job_id {
+                    "" => "-".to_string(),
+                    job_id => job_id.to_string(),
+                };
+                let queue = Queue {
+                    todo: sum(|task| task.todo),
+                    doing: sum(|task| task.doing),
+                    succeeded: sum(|task| task.succeeded),
+                    name: name.clone(),
+                    total: sum(|task| task.total),
+                    active: sum(|task| task.active),
+                    finished: sum(|task| task.finished),
+                    tasks,
+                    ..Default::default()
+                };
+                Batch {
+                    todo: queue.todo,
+                    doing: queue.doing,
+                    succeeded: queue.succeeded,
+                    name,
+                    total: queue.total,
+                    active: queue.active,
+                    finished: queue.finished,
+                    queues: vec![queue],
+                    ..Default::default()
+                }
+            })
+            .collect();
+        StatusResult {
+            todo: pending,
+            doing: running,
+            succeeded: self.finished,
+            failed: 0,
+            aborted: 0,
+            aborting: 0,
+            cancelled: 0,
+            min_ts: self.start_time,
+            max_ts: self.last_update,
+            // Only shown for results without a collection, like exports.
+            name: stages.iter().map(|stage| stage.stage.as_str()).join(", "),
+            batches,
+            collection: self.collection,
+            remaining_time: None,
+            took: None,
+            total: pending + running + self.finished,
+            active: pending + running,
+            finished: self.finished,
+            stages: self.stages.clone(),
+        }
+    }
+}
+
+/// Subtracts the tasks which will never run from a counter.
+pub fn effective(count: u64, aborted: u64, cancelled: u64) -> u64 {
+    count.saturating_sub(aborted.saturating_add(cancelled))
+}
+
+impl StatusResult {
+    /// Identifies the result across fetches: the collection id, or the job
+    /// name for results which don't belong to a collection.
+    pub fn key(&self) -> &str {
+        match &self.collection {
+            Some(c) => &c.collection_id,
+            None => &self.name,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match &self.collection {
+            Some(c) => &c.label,
+            None => &self.name,
+        }
+    }
+
+    /// All tasks of the result with the batch and queue they belong to.
+    pub fn tasks(&self) -> impl Iterator<Item = (&Batch, &Queue, &Task)> {
+        self.batches
+            .iter()
+            .flat_map(|batch| batch.queues.iter().map(move |queue| (batch, queue)))
+            .flat_map(|(batch, queue)| queue.tasks.iter().map(move |task| (batch, queue, task)))
+    }
+
+    /// Whether the result is deleting or flushing data, which is easy to
+    /// trigger on the wrong collection and can't be undone.
+    pub fn is_destructive(&self) -> bool {
+        let destructive = |name: &str| {
+            let name = name.to_lowercase();
+            name.contains("delete") || name.contains("flush")
+        };
+        destructive(&self.name)
+            || self
+                .tasks()
+                .any(|(_, queue, task)| destructive(&queue.name) || destructive(&task.name))
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.batches
+            .iter()
+            .flat_map(|batch| &batch.queues)
+            .map(|queue| queue.tasks.len())
+            .sum()
+    }
+
+    pub fn is_processing(&self) -> bool {
+        self.todo > 0 || self.doing > 0
+    }
+
+    /// Todo without the tasks which were aborted or cancelled and will never
+    /// run.
+    pub fn effective_todo(&self) -> u64 {
+        effective(self.todo, self.aborted, self.cancelled)
+    }
+
+    /// Total without the tasks which were aborted or cancelled.
+    pub fn effective_total(&self) -> u64 {
+        effective(self.total, self.aborted, self.cancelled)
+    }
+
+    /// Percentage of the effective total which succeeded or failed.
+    pub fn progress(&self) -> u64 {
+        match self.effective_total() {
+            0 => 100,
+            total => {
+                let done = self.succeeded.saturating_add(self.failed) as u128;
+                (done * 100 / total as u128).min(100) as u64
+            }
+        }
+    }
+
+    /// Adds the counters and batches of `other`, which reports on the same
+    /// collection.
+    fn merge(&mut self, other: StatusResult) {
+        self.todo = self.todo.saturating_add(other.todo);
+        self.doing = self.doing.saturating_add(other.doing);
+        self.succeeded = self.succeeded.saturating_add(other.succeeded);
+        self.failed = self.failed.saturating_add(other.failed);
+        self.aborted = self.aborted.saturating_add(other.aborted);
+        self.aborting = self.aborting.saturating_add(other.aborting);
+        self.cancelled = self.cancelled.saturating_add(other.cancelled);
+        self.total = self.total.saturating_add(other.total);
+        self.active = self.active.saturating_add(other.active);
+        self.finished = self.finished.saturating_add(other.finished);
+        self.min_ts = self.min_ts.take().into_iter().chain(other.min_ts).min();
+        self.max_ts = self.max_ts.take().into_iter().chain(other.max_ts).max();
+        self.remaining_time = self.remaining_time.take().or(other.remaining_time);
+        self.took = self.took.take().or(other.took);
+        self.batches.extend(other.batches);
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
 pub struct Status {
     pub results: Vec<StatusResult>,
-    pub total: u32,
+    pub total: u64,
+    /// Link to the next page, for instances which paginate the status.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+impl Status {
+    /// Merges results which report on the same collection, as some Aleph
+    /// versions return one result per batch. The first occurrence keeps its
+    /// place, and the total counts each collection once.
+    pub fn merge_duplicates(&mut self) {
+        let mut results: Vec<StatusResult> = Vec::with_capacity(self.results.len());
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut merged = 0;
+        for result in self.results.drain(..) {
+            let duplicate = match &result.collection {
+                Some(_) => indices.get(result.key()).copied(),
+                None => None,
+            };
+            match duplicate {
+                Some(index) => {
+                    results[index].merge(result);
+                    merged += 1;
+                }
+                None => {
+                    if result.collection.is_some() {
+                        indices.insert(result.key().to_string(), results.len());
+                    }
+                    results.push(result);
+                }
+            }
+        }
+        self.total = self.total.saturating_sub(merged);
+        self.results = results;
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -119,6 +440,9 @@ pub struct MetadataApp {
     pub title: Option<String>,
     pub version: Option<String>,
     pub ftm_version: Option<String>,
+    /// Message configured by the admins of the instance, e.g. to announce
+    /// downtime.
+    pub banner: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -128,6 +452,61 @@ pub struct Metadata {
     pub app: MetadataApp,
 }
 
+/// A FollowTheMoney entity as returned by the entities API.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Entity {
+    pub id: String,
+    pub schema: String,
+    #[serde(default)]
+    pub properties: HashMap<String, Vec<serde_json::Value>>,
+}
+
+impl Entity {
+    /// The string values of a property. Values referencing other entities are
+    /// skipped.
+    pub fn property(&self, name: &str) -> Vec<String> {
+        self.properties
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str().map(|v| v.to_string()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EntitiesResponse {
+    pub results: Vec<Entity>,
+    pub total: Option<u64>,
+}
+
+/// A match candidate found by cross-referencing a collection.
+#[derive(Clone, Debug, Deserialize)]
+pub struct XrefMatch {
+    pub score: Option<f64>,
+    /// `positive`, `negative`, `unsure` or `no_judgement`.
+    #[serde(default)]
+    pub judgement: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct XrefResponse {
+    pub results: Vec<XrefMatch>,
+    pub total: Option<u64>,
+}
+
+/// A document or folder created by uploading it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IngestResponse {
+    pub id: String,
+}
+
+/// A session created by logging in with a password.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SessionResponse {
+    pub token: String,
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::read_to_string;
@@ -159,6 +538,26 @@ mod tests {
         assert!(status.results[0].collection.is_none());
     }
 
+    #[test]
+    fn test_v3_normalization() {
+        let test: String = read_to_string("testdata/export.json").unwrap();
+        let status: Status = serde_json::from_str(&test).unwrap();
+        let result = &status.results[0];
+        assert_eq!(result.label(), "exportsearch, exportxref");
+        assert_eq!(
+            (result.doing, result.succeeded, result.total),
+            (1, 118, 119)
+        );
+        let tasks: Vec<_> = result.tasks().map(|(_, _, task)| &task.name).collect();
+        assert_eq!(tasks, ["exportsearch", "exportxref"]);
+    }
+
+    #[test]
+    fn test_effective() {
+        assert_eq!(effective(10, 2, 3), 5);
+        assert_eq!(effective(1, 2, 3), 0);
+    }
+
     #[test]
     fn test_metadata_deserialization() {
         let test = read_to_string("testdata/metadata.json").unwrap();
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Duration, Local};
+
+/// Severity of a notification, which determines its color and lifetime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotificationKind {
+    Error,
+    Warning,
+    Info,
+}
+
+impl NotificationKind {
+    /// How long a notification of this kind stays on screen. Errors are kept
+    /// until whatever caused them is resolved.
+    fn lifetime(&self) -> Option<Duration> {
+        match self {
+            NotificationKind::Error => None,
+            NotificationKind::Warning => Some(Duration::seconds(30)),
+            NotificationKind::Info => Some(Duration::seconds(5)),
+        }
+    }
+}
+
+/// Part of the application a notification originates from. There is at most
+/// one notification per source at any time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotificationSource {
+    Status,
+    Metadata,
+    Action,
+    /// Local subsystems of aleph-tui itself.
+    System,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub source: NotificationSource,
+    pub message: String,
+    pub expires_at: Option<DateTime<Local>>,
+}
+
+impl Notification {
+    pub fn new(kind: NotificationKind, source: NotificationSource, message: String) -> Self {
+        Self {
+            kind,
+            source,
+            message,
+            expires_at: kind.lifetime().map(|lifetime| Local::now() + lifetime),
+        }
+    }
+}
+
+/// The notifications currently shown in the notification line.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    entries: Vec<Notification>,
+}
+
+impl Notifications {
+    /// Adds a notification, replacing any previous one from the same source.
+    pub fn push(&mut self, notification: Notification) {
+        self.clear(notification.source);
+        self.entries.push(notification);
+    }
+
+    pub fn clear(&mut self, source: NotificationSource) {
+        self.entries.retain(|n| n.source != source);
+    }
+
+    pub fn clear_all(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drops all notifications which expired before `now`.
+    pub fn expire(&mut self, now: DateTime<Local>) {
+        self.entries
+            .retain(|n| n.expires_at.is_none_or(|expires_at| expires_at > now));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Notification> {
+        self.entries.iter()
+    }
+}
+
+/// A short-lived message shown stacked in a corner of the screen.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub kind: NotificationKind,
+    pub message: String,
+    /// Sticky toasts stay until they are dismissed.
+    pub expires_at: Option<DateTime<Local>>,
+}
+
+/// Queue of toasts, oldest first.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    entries: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Maximum number of toasts shown at once. Older toasts are dropped when
+    /// more arrive.
+    const MAX: usize = 5;
+
+    pub fn push(&mut self, kind: NotificationKind, message: String) {
+        let lifetime = match kind {
+            NotificationKind::Info => Duration::seconds(5),
+            NotificationKind::Warning | NotificationKind::Error => Duration::seconds(10),
+        };
+        self.insert(Toast {
+            kind,
+            message,
+            expires_at: Some(Local::now() + lifetime),
+        });
+    }
+
+    /// Adds a toast which stays until [`Toasts::dismiss_sticky`] is called.
+    pub fn push_sticky(&mut self, kind: NotificationKind, message: String) {
+        self.insert(Toast {
+            kind,
+            message,
+            expires_at: None,
+        });
+    }
+
+    /// Adds a toast, dropping the oldest one which isn't sticky if there are
+    /// too many.
+    fn insert(&mut self, toast: Toast) {
+        self.entries.push(toast);
+        if self.entries.len() > Self::MAX {
+            let oldest = self
+                .entries
+                .iter()
+                .position(|t| t.expires_at.is_some())
+                .unwrap_or_default();
+            self.entries.remove(oldest);
+        }
+    }
+
+    /// Drops all toasts which expired before `now`.
+    pub fn expire(&mut self, now: DateTime<Local>) {
+        self.entries
+            .retain(|t| t.expires_at.is_none_or(|expires_at| expires_at > now));
+    }
+
+    pub fn has_sticky(&self) -> bool {
+        self.entries.iter().any(|t| t.expires_at.is_none())
+    }
+
+    pub fn dismiss_sticky(&mut self) {
+        self.entries.retain(|t| t.expires_at.is_some());
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Toast> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_replaces_same_source() {
+        let mut notifications = Notifications::default();
+        notifications.push(Notification::new(
+            NotificationKind::Error,
+            NotificationSource::Status,
+            "first".to_string(),
+        ));
+        notifications.push(Notification::new(
+            NotificationKind::Error,
+            NotificationSource::Metadata,
+            "metadata".to_string(),
+        ));
+        notifications.push(Notification::new(
+            NotificationKind::Error,
+            NotificationSource::Status,
+            "second".to_string(),
+        ));
+        let messages: Vec<_> = notifications.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(messages, ["metadata", "second"]);
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut notifications = Notifications::default();
+        notifications.push(Notification::new(
+            NotificationKind::Error,
+            NotificationSource::Status,
+            "error".to_string(),
+        ));
+        notifications.push(Notification::new(
+            NotificationKind::Info,
+            NotificationSource::Action,
+            "done".to_string(),
+        ));
+        notifications.expire(Local::now() + Duration::minutes(1));
+        let messages: Vec<_> = notifications.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(messages, ["error"]);
+    }
+
+    #[test]
+    fn test_toasts_are_capped() {
+        let mut toasts = Toasts::default();
+        for i in 0..7 {
+            toasts.push(NotificationKind::Info, i.to_string());
+        }
+        let messages: Vec<_> = toasts.iter().map(|t| t.message.as_str()).collect();
+        assert_eq!(messages, ["2", "3", "4", "5", "6"]);
+        toasts.expire(Local::now() + Duration::minutes(1));
+        assert_eq!(toasts.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_sticky_toasts() {
+        let mut toasts = Toasts::default();
+        toasts.push_sticky(NotificationKind::Warning, "sticky".to_string());
+        for i in 0..7 {
+            toasts.push(NotificationKind::Info, i.to_string());
+        }
+        toasts.expire(Local::now() + Duration::minutes(1));
+        let messages: Vec<_> = toasts.iter().map(|t| t.message.as_str()).collect();
+        assert_eq!(messages, ["sticky"]);
+        toasts.dismiss_sticky();
+        assert!(!toasts.has_sticky());
+    }
+}
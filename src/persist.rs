@@ -0,0 +1,95 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use color_eyre::{eyre::eyre, Result};
+
+/// Writes `contents` to `path` without ever leaving a partially written file
+/// behind: the data goes to a temporary file next to `path` first, which then
+/// replaces it in a single rename. Files which are frequently rewritten, like
+/// the state file, are written this way.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    write_via_tmp(
+        path,
+        contents,
+        OpenOptions::new().write(true).create(true).truncate(true),
+    )
+}
+
+/// Writes `contents` to `path` like [`write_atomic`], but only readable by
+/// the user from the start, for files with secrets like session tokens.
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    write_via_tmp(path, contents, &options)
+}
+
+fn write_via_tmp(path: &Path, contents: &[u8], options: &OpenOptions) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| eyre!("{} is not a file", path.display()))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    Ok(result?)
+}
+
+/// Copies `path` to a timestamped backup next to it, e.g.
+/// `aleph-tui.toml.20240501-103000.bak`. Returns `None` if there is nothing
+/// to back up.
+pub fn backup(path: &Path) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut backup_name = path
+        .file_name()
+        .ok_or_else(|| eyre!("{} is not a file", path.display()))?
+        .to_os_string();
+    backup_name.push(format!(".{}.bak", Local::now().format("%Y%m%d-%H%M%S")));
+    let backup_path = path.with_file_name(backup_name);
+    fs::copy(path, &backup_path)?;
+    Ok(Some(backup_path))
+}
+
+/// Replaces the file at `path`, keeping a backup of its previous version. This
+/// is how config files edited from within aleph-tui are written.
+pub fn replace(path: &Path, contents: &[u8]) -> Result<Option<PathBuf>> {
+    let backup_path = backup(path)?;
+    write_atomic(path, contents)?;
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace() {
+        let dir = std::env::temp_dir().join(format!("aleph-tui-persist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        assert_eq!(replace(&path, b"first").unwrap(), None);
+        let backup_path = replace(&path, b"second").unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(&backup_path).unwrap(), "first");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
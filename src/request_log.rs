@@ -0,0 +1,145 @@
+use std::{collections::VecDeque, fs::OpenOptions, io::Write, path::Path, time::Duration};
+
+use chrono::{DateTime, Local};
+use color_eyre::Result;
+
+/// Query parameters which may carry credentials.
+const SECRET_PARAMS: [&str; 3] = ["api_key", "token", "access_token"];
+
+/// Marker replacing secrets in logged requests.
+const REDACTED: &str = "[REDACTED]";
+
+/// Bytes of a response body kept in the log.
+const BODY_EXCERPT: usize = 1024;
+
+/// One HTTP request made to Aleph.
+#[derive(Clone, Debug)]
+pub struct RequestLogEntry {
+    pub time: DateTime<Local>,
+    pub method: String,
+    /// The requested URL, with secrets redacted.
+    pub url: String,
+    /// The response status code, or the error if there was no response.
+    pub status: Result<u16, String>,
+    pub duration: Duration,
+    /// The start of the response body, with secrets redacted, if it was
+    /// read.
+    pub body: Option<String>,
+    /// Fields of the response which didn't match the schema, see
+    /// [`crate::schema::Deviations`].
+    pub deviations: Vec<String>,
+}
+
+impl RequestLogEntry {
+    /// One line description like "GET https://… 200 123ms".
+    pub fn line(&self) -> String {
+        let status = match &self.status {
+            Ok(code) => code.to_string(),
+            Err(e) => format!("error: {e}"),
+        };
+        format!(
+            "{} {} {status} {}ms",
+            self.method,
+            self.url,
+            self.duration.as_millis()
+        )
+    }
+}
+
+/// The most recent requests, oldest first.
+#[derive(Debug, Default)]
+pub struct RequestLog {
+    entries: VecDeque<RequestLogEntry>,
+}
+
+impl RequestLog {
+    /// Number of requests kept in memory.
+    const MAX: usize = 100;
+
+    pub fn push(&mut self, entry: RequestLogEntry) {
+        self.entries.push_back(entry);
+        if self.entries.len() > Self::MAX {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, RequestLogEntry> {
+        self.entries.iter()
+    }
+
+    /// The most recent request whose response body was read.
+    pub fn last_with_body(&self) -> Option<&RequestLogEntry> {
+        self.entries.iter().rev().find(|entry| entry.body.is_some())
+    }
+}
+
+/// The first [`BODY_EXCERPT`] bytes of `body`, with `secrets` redacted.
+pub fn excerpt(body: &[u8], secrets: &[&str]) -> String {
+    let text = String::from_utf8_lossy(&body[..body.len().min(BODY_EXCERPT)]);
+    // A character cut in half at the end isn't worth a replacement character.
+    let text = match body.len() > BODY_EXCERPT {
+        true => format!("{}…", text.trim_end_matches('\u{FFFD}')),
+        false => text.into_owned(),
+    };
+    redact(&text, secrets)
+}
+
+/// Replaces all occurrences of `secrets` and the values of query parameters
+/// which commonly carry credentials.
+pub fn redact(text: &str, secrets: &[&str]) -> String {
+    let text = secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        });
+    let Some((base, query)) = text.split_once('?') else {
+        return text;
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name) => format!("{name}={REDACTED}"),
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{base}?{}", query.join("&"))
+}
+
+/// Appends the entry to the log file at `path`.
+pub fn append(path: &Path, entry: &RequestLogEntry) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{} {}", entry.time.to_rfc3339(), entry.line())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact(
+                "https://aleph/api/2/status?secret-token",
+                &["secret-token", ""]
+            ),
+            "https://aleph/api/2/status?[REDACTED]"
+        );
+        assert_eq!(
+            redact("https://aleph/api/2/entities?limit=1&api_key=abc", &[]),
+            "https://aleph/api/2/entities?limit=1&api_key=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(
+            excerpt(b"{\"token\": \"abc\"}", &["abc"]),
+            "{\"token\": \"[REDACTED]\"}"
+        );
+        let body = "ä".repeat(1000);
+        let excerpt = excerpt(body.as_bytes(), &[]);
+        assert_eq!(excerpt, format!("{}…", "ä".repeat(512)));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Local};
+
+/// When to fetch the profiles which aren't the current one, see
+/// [`crate::app::Config::fetch_all_profiles`]. Their first fetches are
+/// spread over the fetch interval and at most `per_host` fetches run against
+/// the same host at a time, so many profiles don't turn into bursts of
+/// requests.
+#[derive(Clone, Debug)]
+pub struct Scheduler {
+    /// Host of each profile, by index.
+    hosts: Vec<String>,
+    per_host: usize,
+    /// When each profile is due next, by index. Unset until the first fetch
+    /// is scheduled.
+    due: Vec<Option<DateTime<Local>>>,
+    running: HashSet<usize>,
+}
+
+impl Scheduler {
+    pub fn new(hosts: Vec<String>, per_host: usize) -> Self {
+        let due = vec![None; hosts.len()];
+        Self {
+            hosts,
+            per_host: per_host.max(1),
+            due,
+            running: HashSet::new(),
+        }
+    }
+
+    /// Host of `url`, all of it if it has none.
+    pub fn host(url: &str) -> String {
+        reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// Profiles to fetch at `now`, which are marked as running until
+    /// [`Scheduler::finished`]. `current` isn't fetched by the scheduler, but
+    /// counts against the limit of its host while `current_running`.
+    pub fn start(
+        &mut self,
+        now: DateTime<Local>,
+        interval: Duration,
+        current: usize,
+        current_running: bool,
+    ) -> Vec<usize> {
+        let count = self.hosts.len() as i32;
+        for (index, due) in self.due.iter_mut().enumerate() {
+            due.get_or_insert_with(|| now + interval * index as i32 / count);
+        }
+        let mut running: HashMap<&str, usize> = HashMap::new();
+        let busy = self
+            .running
+            .iter()
+            .copied()
+            .chain(current_running.then_some(current));
+        for index in busy {
+            *running.entry(&self.hosts[index]).or_default() += 1;
+        }
+        let mut started = Vec::new();
+        for (index, due) in self.due.iter().enumerate() {
+            let host = self.hosts[index].as_str();
+            let is_due = due.is_some_and(|due| due <= now);
+            if index == current || !is_due || self.running.contains(&index) {
+                continue;
+            }
+            let running = running.entry(host).or_default();
+            if *running < self.per_host {
+                *running += 1;
+                started.push(index);
+            }
+        }
+        self.running.extend(&started);
+        started
+    }
+
+    /// Records that the fetch of `profile` finished at `now`, scheduling the
+    /// next one. Returns whether it was started by the scheduler.
+    pub fn finished(&mut self, profile: usize, now: DateTime<Local>, interval: Duration) -> bool {
+        if !self.running.remove(&profile) {
+            return false;
+        }
+        self.postpone(profile, now + interval);
+        true
+    }
+
+    /// Doesn't fetch `profile` before `until`, e.g. because it was just
+    /// fetched as the current profile.
+    pub fn postpone(&mut self, profile: usize, until: DateTime<Local>) {
+        if let Some(due) = self.due.get_mut(profile) {
+            *due = Some(until);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduler() {
+        let hosts = ["a", "a", "a", "b"].map(str::to_string).to_vec();
+        let mut scheduler = Scheduler::new(hosts, 1);
+        let now = Local::now();
+        let interval = Duration::seconds(8);
+        // Spread over the interval, profile 0 is the current one.
+        assert!(scheduler.start(now, interval, 0, true).is_empty());
+        let later = now + Duration::seconds(6);
+        // Host a is busy with the current profile.
+        assert_eq!(scheduler.start(later, interval, 0, true), [3]);
+        assert_eq!(scheduler.start(later, interval, 0, false), [1]);
+        assert!(scheduler.start(later, interval, 0, false).is_empty());
+
+        assert!(scheduler.finished(1, later, interval));
+        assert!(!scheduler.finished(0, later, interval));
+        assert_eq!(scheduler.start(later, interval, 0, false), [2]);
+    }
+}
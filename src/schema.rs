@@ -0,0 +1,395 @@
+use std::{cell::RefCell, collections::BTreeSet, fmt};
+
+use itertools::Itertools;
+use serde::de::{
+    self, value::StringDeserializer, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess,
+    IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+
+/// How responses which don't quite match the schema aleph-tui expects are
+/// parsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Parsing {
+    /// Unknown fields are ignored, they are only noted in the request log.
+    #[default]
+    Lenient,
+    /// Unknown fields are errors, to notice changes of the API. Optional
+    /// fields which are missing are still only noted, since which ones are
+    /// sent depends on the version of Aleph.
+    Strict,
+}
+
+/// Fields of a response which the schema doesn't know or which the response
+/// lacks, by path like `results[].collection.label`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Deviations {
+    pub unknown: BTreeSet<String>,
+    pub missing: BTreeSet<String>,
+}
+
+impl Deviations {
+    /// Up to two lines like "unknown fields: results[].tags, total_count".
+    pub fn lines(&self) -> Vec<String> {
+        [("unknown", &self.unknown), ("missing", &self.missing)]
+            .into_iter()
+            .filter(|(_, paths)| !paths.is_empty())
+            .map(|(kind, paths)| format!("{kind} fields: {}", paths.iter().join(", ")))
+            .collect()
+    }
+}
+
+/// Parses a JSON document like [`serde_json::from_slice`], noting the fields
+/// of objects deserialized into structs which don't match the struct.
+pub fn from_slice<T: DeserializeOwned>(body: &[u8]) -> serde_json::Result<(T, Deviations)> {
+    let deviations = RefCell::new(Deviations::default());
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = T::deserialize(Checked {
+        inner: &mut deserializer,
+        path: String::new(),
+        deviations: &deviations,
+    })?;
+    deserializer.end()?;
+    Ok((value, deviations.into_inner()))
+}
+
+/// Wraps a deserializer to check every struct deserialized from it against
+/// the fields of the object it is deserialized from.
+struct Checked<'a, D> {
+    inner: D,
+    /// Where in the document the value is.
+    path: String,
+    deviations: &'a RefCell<Deviations>,
+}
+
+impl<'a, D> Checked<'a, D> {
+    fn visitor<V>(
+        &self,
+        visitor: V,
+        fields: Option<&'static [&'static str]>,
+    ) -> CheckedVisitor<'a, V> {
+        CheckedVisitor {
+            inner: visitor,
+            path: self.path.clone(),
+            fields,
+            deviations: self.deviations,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+                let visitor = self.visitor(visitor, None);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Checked<'_, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        let visitor = self.visitor(visitor, Some(fields));
+        self.inner.deserialize_struct(name, fields, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Deserializes a nested value with a [`Checked`] deserializer.
+struct CheckedSeed<'a, S> {
+    inner: S,
+    path: String,
+    deviations: &'a RefCell<Deviations>,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for CheckedSeed<'_, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.inner.deserialize(Checked {
+            inner: deserializer,
+            path: self.path,
+            deviations: self.deviations,
+        })
+    }
+}
+
+struct CheckedVisitor<'a, V> {
+    inner: V,
+    path: String,
+    /// The fields of the struct being deserialized, if it is one.
+    fields: Option<&'static [&'static str]>,
+    deviations: &'a RefCell<Deviations>,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, value: $ty) -> Result<V::Value, E> {
+                self.inner.$method(value)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for CheckedVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(Checked {
+            inner: deserializer,
+            path: self.path,
+            deviations: self.deviations,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(Checked {
+            inner: deserializer,
+            path: self.path,
+            deviations: self.deviations,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(CheckedSeq {
+            inner: seq,
+            path: format!("{}[]", self.path),
+            deviations: self.deviations,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(CheckedMap {
+            inner: map,
+            path: self.path,
+            fields: self.fields,
+            seen: Vec::new(),
+            deviations: self.deviations,
+        })
+    }
+
+    // The variants aren't checked, the status has no enums with fields.
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(data)
+    }
+}
+
+struct CheckedSeq<'a, A> {
+    inner: A,
+    /// Path of the elements.
+    path: String,
+    deviations: &'a RefCell<Deviations>,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for CheckedSeq<'_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.inner.next_element_seed(CheckedSeed {
+            inner: seed,
+            path: self.path.clone(),
+            deviations: self.deviations,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct CheckedMap<'a, A> {
+    inner: A,
+    path: String,
+    fields: Option<&'static [&'static str]>,
+    /// Keys read so far.
+    seen: Vec<String>,
+    deviations: &'a RefCell<Deviations>,
+}
+
+impl<A> CheckedMap<'_, A> {
+    /// Path of the value of `key`. Keys of maps other than structs are data
+    /// rather than schema, so they are all the same.
+    fn path(&self, key: &str) -> String {
+        match (self.fields, self.path.is_empty()) {
+            (None, _) => format!("{}.*", self.path),
+            (Some(_), true) => key.to_string(),
+            (Some(_), false) => format!("{}.{key}", self.path),
+        }
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for CheckedMap<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        // Keys of JSON objects are strings, so they can be read as such and
+        // handed on.
+        let Some(key) = self.inner.next_key::<String>()? else {
+            if let Some(fields) = self.fields {
+                let missing = fields
+                    .iter()
+                    .filter(|field| !self.seen.iter().any(|key| key == *field))
+                    .map(|field| self.path(field));
+                self.deviations.borrow_mut().missing.extend(missing);
+            }
+            return Ok(None);
+        };
+        if self
+            .fields
+            .is_some_and(|fields| !fields.contains(&key.as_str()))
+        {
+            let path = self.path(&key);
+            self.deviations.borrow_mut().unknown.insert(path);
+        }
+        let deserializer: StringDeserializer<A::Error> = key.clone().into_deserializer();
+        self.seen.push(key);
+        seed.deserialize(deserializer).map(Some)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        let key = self.seen.last().map(String::as_str).unwrap_or_default();
+        self.inner.next_value_seed(CheckedSeed {
+            inner: seed,
+            path: self.path(key),
+            deviations: self.deviations,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Outer {
+        name: String,
+        inner: Vec<Inner>,
+        map: HashMap<String, Inner>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Inner {
+        count: u64,
+        label: Option<String>,
+    }
+
+    #[test]
+    fn test_deviations() {
+        let body = br#"{
+            "name": "one",
+            "inner": [{"count": 1, "label": "a"}, {"count": 2, "extra": true}],
+            "map": {"x": {"count": 3, "label": null}},
+            "new": 1
+        }"#;
+        let (outer, deviations) = from_slice::<Outer>(body).unwrap();
+        assert_eq!(outer.inner[1].count, 2);
+        assert_eq!(
+            deviations.lines(),
+            [
+                "unknown fields: inner[].extra, new",
+                "missing fields: inner[].label"
+            ]
+        );
+
+        let error = from_slice::<Outer>(br#"{"name": 1}"#).err().unwrap();
+        assert_eq!((error.line(), error.column()), (1, 10));
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::persist;
+
+/// Session tokens of the profiles which log in with a password, see
+/// [`crate::api::Auth::Login`]. They are kept apart from the
+/// [`crate::state::State`], which may be shared with others.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Sessions {
+    /// By profile name.
+    #[serde(default)]
+    pub tokens: HashMap<String, String>,
+}
+
+impl Sessions {
+    /// `$XDG_STATE_HOME/aleph-tui-sessions.toml`, or
+    /// `~/.local/state/aleph-tui-sessions.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = match std::env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home::home_dir()?.join(".local/state"),
+        };
+        Some(dir.join("aleph-tui-sessions.toml"))
+    }
+
+    /// Reads the sessions from `path`, which doesn't have to exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|e| eyre!("Failed to parse sessions file {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        persist::write_private(path, toml::to_string(self)?.as_bytes())
+    }
+}
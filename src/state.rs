@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::persist;
+
+/// State which outlives a session, as opposed to the config it is never
+/// edited by hand.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct State {
+    /// Until when the alerts of a collection are muted, by profile name and
+    /// [`crate::models::StatusResult::key`].
+    #[serde(default)]
+    pub mutes: HashMap<String, HashMap<String, DateTime<Local>>>,
+    /// Local tags of collections, sorted, keyed like [`State::mutes`].
+    #[serde(default)]
+    pub tags: HashMap<String, HashMap<String, Vec<String>>>,
+    /// Lines of the details pane, if it was resized.
+    #[serde(default)]
+    pub details_height: Option<u16>,
+    /// Seconds between fetches, if they were adjusted and
+    /// [`crate::app::Config::remember_fetch_interval`] is set.
+    #[serde(default)]
+    pub fetch_interval: Option<i64>,
+}
+
+impl State {
+    /// `$XDG_STATE_HOME/aleph-tui.toml`, or `~/.local/state/aleph-tui.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = match std::env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home::home_dir()?.join(".local/state"),
+        };
+        Some(dir.join("aleph-tui.toml"))
+    }
+
+    /// Reads the state from `path`, which doesn't have to exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|e| eyre!("Failed to parse state file {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        persist::write_atomic(path, toml::to_string(self)?.as_bytes())
+    }
+
+    /// Until when the collection is muted, if it still is at `now`.
+    pub fn muted_until(
+        &self,
+        profile: &str,
+        key: &str,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        self.mutes
+            .get(profile)?
+            .get(key)
+            .copied()
+            .filter(|until| *until > now)
+    }
+
+    /// Mutes the collection until `until`, or unmutes it with `None`.
+    pub fn mute(&mut self, profile: &str, key: &str, until: Option<DateTime<Local>>) {
+        let mutes = self.mutes.entry(profile.to_string()).or_default();
+        match until {
+            Some(until) => mutes.insert(key.to_string(), until),
+            None => mutes.remove(key),
+        };
+    }
+
+    /// The tags of the collection, empty if it has none.
+    pub fn tags(&self, profile: &str, key: &str) -> &[String] {
+        self.tags
+            .get(profile)
+            .and_then(|tags| tags.get(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the tags of the collection, dropping empty and duplicate
+    /// ones.
+    pub fn set_tags(&mut self, profile: &str, key: &str, tags: impl IntoIterator<Item = String>) {
+        let mut tags: Vec<String> = tags
+            .into_iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        let profile_tags = self.tags.entry(profile.to_string()).or_default();
+        match tags.is_empty() {
+            true => profile_tags.remove(key),
+            false => profile_tags.insert(key.to_string(), tags),
+        };
+        self.tags.retain(|_, tags| !tags.is_empty());
+    }
+
+    /// Forgets about mutes which ended before `now`.
+    pub fn prune(&mut self, now: DateTime<Local>) {
+        for mutes in self.mutes.values_mut() {
+            mutes.retain(|_, until| *until > now);
+        }
+        self.mutes.retain(|_, mutes| !mutes.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_mutes() {
+        let now = Local::now();
+        let mut state = State::default();
+        state.mute("prod", "42", Some(now + Duration::hours(1)));
+        state.mute("prod", "7", Some(now - Duration::hours(1)));
+        assert!(state.muted_until("prod", "42", now).is_some());
+        assert!(state.muted_until("prod", "7", now).is_none());
+        assert!(state.muted_until("staging", "42", now).is_none());
+
+        state.prune(now);
+        let roundtrip: State = toml::from_str(&toml::to_string(&state).unwrap()).unwrap();
+        assert_eq!(roundtrip.mutes["prod"].len(), 1);
+
+        state.mute("prod", "42", None);
+        state.prune(now);
+        assert!(state.mutes.is_empty());
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut state = State::default();
+        let tags = ["q3", " leak ", "q3", ""].map(String::from);
+        state.set_tags("prod", "42", tags);
+        assert_eq!(state.tags("prod", "42"), ["leak", "q3"]);
+        assert!(state.tags("staging", "42").is_empty());
+
+        state.set_tags("prod", "42", []);
+        assert!(state.tags.is_empty());
+    }
+}
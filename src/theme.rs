@@ -0,0 +1,286 @@
+use std::{io::IsTerminal, str::FromStr, time::Duration};
+
+use color_eyre::eyre::{eyre, Error};
+use ratatui::{buffer::Buffer, style::Color};
+
+/// Value of the `--color` flag.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorMode {
+    /// Use colors if the output is a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(eyre!(
+                "Invalid color mode {s}, expected auto, always or never"
+            )),
+        }
+    }
+}
+
+impl ColorMode {
+    /// Whether to use colors, following the `NO_COLOR` (https://no-color.org)
+    /// and `CLICOLOR_FORCE` conventions. An explicit flag takes precedence over
+    /// both.
+    fn use_colors(self, no_color: bool, force_color: bool, is_terminal: bool) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto if force_color => true,
+            ColorMode::Auto => !no_color && is_terminal,
+        }
+    }
+}
+
+/// Whether the locale given by the usual environment variables, in order of
+/// precedence, can display non-ASCII symbols. Without any locale, a modern
+/// terminal is assumed.
+fn is_unicode_locale(lc_all: Option<&str>, lc_ctype: Option<&str>, lang: Option<&str>) -> bool {
+    match [lc_all, lc_ctype, lang]
+        .into_iter()
+        .flatten()
+        .find(|v| !v.is_empty())
+    {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => true,
+    }
+}
+
+/// Brightness of the terminal background. Colors are darkened on light
+/// backgrounds, where yellow and green text is hard to read.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Background {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// How long to wait for the terminal to report its background color.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+impl Background {
+    /// Asks the terminal for its background color, falling back to
+    /// `COLORFGBG` and then to a dark background. Has to be called before the
+    /// terminal is set up for the interface, which reads from it as well.
+    pub fn detect() -> Self {
+        query_background()
+            .or_else(|| from_colorfgbg(std::env::var("COLORFGBG").ok().as_deref()))
+            .unwrap_or_default()
+    }
+}
+
+/// Queries the background color with OSC 11. Every terminal answers the
+/// device attributes query sent after it, so reading the answer stops even if
+/// the terminal doesn't report its background color.
+#[cfg(unix)]
+fn query_background() -> Option<Background> {
+    use std::{
+        fs::OpenOptions,
+        io::{Read, Write},
+        sync::mpsc,
+        thread,
+    };
+
+    if !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let mut reader = tty.try_clone().ok()?;
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let (sender, receiver) = mpsc::channel();
+    if tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").is_ok() && tty.flush().is_ok() {
+        thread::spawn(move || {
+            let mut answer = Vec::new();
+            let mut byte = [0];
+            while reader.read_exact(&mut byte).is_ok() {
+                answer.push(byte[0]);
+                // The device attributes are `ESC [ ? ... c`.
+                let attributes = answer.windows(3).position(|w| w == b"\x1b[?");
+                if attributes.is_some() && byte[0] == b'c' {
+                    break;
+                }
+            }
+            let _ = sender.send(answer);
+        });
+    }
+    let answer = receiver.recv_timeout(QUERY_TIMEOUT).ok();
+    let _ = crossterm::terminal::disable_raw_mode();
+    parse_background(&String::from_utf8_lossy(&answer?))
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<Background> {
+    None
+}
+
+/// Reads the answer to OSC 11, e.g. `ESC ] 11 ; rgb:ffff/ffff/dddd ESC \`.
+fn parse_background(answer: &str) -> Option<Background> {
+    let rgb = answer.split("rgb:").nth(1)?;
+    let channels: Vec<f64> = rgb
+        .split('/')
+        .take(3)
+        .map(|channel| {
+            let hex: String = channel
+                .chars()
+                .take_while(char::is_ascii_hexdigit)
+                .collect();
+            let max = 16f64.powi(hex.len() as i32) - 1.0;
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .map(|value| value as f64 / max)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    match luminance > 0.5 {
+        true => Some(Background::Light),
+        false => Some(Background::Dark),
+    }
+}
+
+/// Reads `COLORFGBG`, e.g. `0;15` for black on white, which some terminals
+/// set.
+fn from_colorfgbg(value: Option<&str>) -> Option<Background> {
+    let background: u8 = value?.rsplit(';').next()?.parse().ok()?;
+    match background {
+        7 | 15 => Some(Background::Light),
+        _ => Some(Background::Dark),
+    }
+}
+
+/// The variant of `color` which is readable on a light background.
+fn for_light_background(color: Color) -> Color {
+    match color {
+        Color::Yellow | Color::LightYellow => Color::Indexed(130),
+        Color::Green | Color::LightGreen => Color::Indexed(28),
+        Color::Cyan | Color::LightCyan => Color::Indexed(30),
+        Color::White | Color::Gray => Color::Black,
+        color => color,
+    }
+}
+
+/// Styling decisions which apply to the whole screen.
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    pub colors: bool,
+    /// Whether symbols like emoji can be used, otherwise ASCII fallbacks are
+    /// shown.
+    pub unicode: bool,
+    pub background: Background,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colors: true,
+            unicode: true,
+            background: Background::Dark,
+        }
+    }
+}
+
+impl Theme {
+    /// Resolves the theme for output to stderr.
+    pub fn new(mode: ColorMode) -> Self {
+        let is_set = |name: &str| std::env::var(name).is_ok_and(|v| !v.is_empty() && v != "0");
+        let no_color = std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+        Self {
+            colors: mode.use_colors(
+                no_color,
+                is_set("CLICOLOR_FORCE"),
+                std::io::stderr().is_terminal(),
+            ),
+            unicode: is_unicode_locale(
+                std::env::var("LC_ALL").ok().as_deref(),
+                std::env::var("LC_CTYPE").ok().as_deref(),
+                std::env::var("LANG").ok().as_deref(),
+            ),
+            background: Background::Dark,
+        }
+    }
+
+    /// Picks the symbol or its ASCII fallback.
+    pub fn symbol<'a>(&self, unicode: &'a str, ascii: &'a str) -> &'a str {
+        match self.unicode {
+            true => unicode,
+            false => ascii,
+        }
+    }
+
+    /// Strips colors from a rendered frame if they are disabled, or darkens
+    /// them on a light background. Modifiers like bold and reversed are kept,
+    /// so highlights stay visible on monochrome terminals.
+    pub fn apply(&self, buffer: &mut Buffer) {
+        if self.colors && self.background == Background::Dark {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            match self.colors {
+                true => {
+                    cell.fg = for_light_background(cell.fg);
+                    cell.bg = for_light_background(cell.bg);
+                }
+                false => {
+                    cell.fg = Color::Reset;
+                    cell.bg = Color::Reset;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_use_colors() {
+        assert!(ColorMode::Auto.use_colors(false, false, true));
+        assert!(!ColorMode::Auto.use_colors(true, false, true));
+        assert!(!ColorMode::Auto.use_colors(false, false, false));
+        assert!(ColorMode::Auto.use_colors(true, true, false));
+        assert!(ColorMode::Always.use_colors(true, false, false));
+        assert!(!ColorMode::Never.use_colors(false, true, true));
+    }
+
+    #[test]
+    fn test_detect_background() {
+        let answer = "\x1b]11;rgb:ffff/ffff/dddd\x1b\\\x1b[?62;22c";
+        assert_eq!(parse_background(answer), Some(Background::Light));
+        assert_eq!(
+            parse_background("\x1b]11;rgb:1e/1e/2e\x07"),
+            Some(Background::Dark)
+        );
+        assert_eq!(parse_background("\x1b[?62;22c"), None);
+        assert_eq!(from_colorfgbg(Some("0;15")), Some(Background::Light));
+        assert_eq!(from_colorfgbg(Some("15;default;0")), Some(Background::Dark));
+        assert_eq!(from_colorfgbg(None), None);
+    }
+
+    #[test]
+    fn test_is_unicode_locale() {
+        assert!(is_unicode_locale(None, None, Some("en_US.UTF-8")));
+        assert!(!is_unicode_locale(Some("C"), None, Some("en_US.UTF-8")));
+        assert!(is_unicode_locale(Some(""), Some("de_DE.utf8"), None));
+        assert!(is_unicode_locale(None, None, None));
+    }
+}
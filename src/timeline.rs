@@ -0,0 +1,194 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+use chrono::{DateTime, Local};
+use color_eyre::Result;
+
+use crate::models::{Status, StatusResult};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    Started,
+    Completed,
+    /// The collection finished processing, but some of its tasks failed.
+    Failed,
+}
+
+impl EventKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::Completed => "completed",
+            EventKind::Failed => "failed",
+        }
+    }
+}
+
+/// A change in the processing state of a collection between two fetches.
+#[derive(Clone, Debug)]
+pub struct CollectionEvent {
+    pub kind: EventKind,
+    pub key: String,
+    pub foreign_id: String,
+    pub label: String,
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+impl CollectionEvent {
+    fn new(kind: EventKind, result: &StatusResult) -> Self {
+        Self {
+            kind,
+            key: result.key().to_string(),
+            foreign_id: result
+                .collection
+                .as_ref()
+                .map(|c| c.foreign_id.clone())
+                .unwrap_or_default(),
+            label: result.label().to_string(),
+            succeeded: result.succeeded,
+            failed: result.failed,
+        }
+    }
+}
+
+/// Compares two consecutive status snapshots. A collection has finished when
+/// it stops processing or disappears from the status altogether.
+pub fn diff(previous: &Status, current: &Status) -> Vec<CollectionEvent> {
+    let find = |status: &Status, key: &str| -> Option<StatusResult> {
+        status.results.iter().find(|r| r.key() == key).cloned()
+    };
+    let finished = |result: &StatusResult| match result.failed {
+        0 => CollectionEvent::new(EventKind::Completed, result),
+        _ => CollectionEvent::new(EventKind::Failed, result),
+    };
+
+    let mut events = Vec::new();
+    for before in &previous.results {
+        match find(current, before.key()) {
+            Some(after) if before.is_processing() && !after.is_processing() => {
+                events.push(finished(&after))
+            }
+            Some(_) => {}
+            None if before.is_processing() => events.push(finished(before)),
+            None => {}
+        }
+    }
+    for after in &current.results {
+        let started = match find(previous, after.key()) {
+            Some(before) => !before.is_processing(),
+            None => true,
+        };
+        if started && after.is_processing() {
+            events.push(CollectionEvent::new(EventKind::Started, after));
+        }
+    }
+    events
+}
+
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Appends the events to the CSV file at `path`, writing a header first if
+/// the file is new.
+pub fn append(
+    path: &Path,
+    time: DateTime<Local>,
+    profile: &str,
+    events: &[CollectionEvent],
+) -> Result<()> {
+    let write_header = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if write_header {
+        writeln!(
+            file,
+            "time,profile,collection_id,foreign_id,label,event,succeeded,failed"
+        )?;
+    }
+    for event in events {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{}",
+            time.to_rfc3339(),
+            csv_field(profile),
+            csv_field(&event.key),
+            csv_field(&event.foreign_id),
+            csv_field(&event.label),
+            event.kind.as_str(),
+            event.succeeded,
+            event.failed,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, todo: u64, failed: u64) -> StatusResult {
+        StatusResult {
+            todo,
+            doing: 0,
+            succeeded: 10,
+            failed,
+            aborted: 0,
+            aborting: 0,
+            cancelled: 0,
+            min_ts: None,
+            max_ts: None,
+            name: name.to_string(),
+            batches: Vec::new(),
+            collection: None,
+            remaining_time: None,
+            took: None,
+            total: 10 + todo + failed,
+            active: todo,
+            finished: 10 + failed,
+            stages: None,
+        }
+    }
+
+    fn status(results: Vec<StatusResult>) -> Status {
+        Status {
+            total: results.len() as u64,
+            results,
+            next: None,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = status(vec![
+            result("done", 5, 0),
+            result("gone", 5, 2),
+            result("busy", 5, 0),
+        ]);
+        let current = status(vec![
+            result("done", 0, 0),
+            result("busy", 3, 0),
+            result("new", 7, 0),
+        ]);
+        let events: Vec<_> = diff(&previous, &current)
+            .into_iter()
+            .map(|e| (e.key, e.kind))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("done".to_string(), EventKind::Completed),
+                ("gone".to_string(), EventKind::Failed),
+                ("new".to_string(), EventKind::Started),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+}
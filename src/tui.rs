@@ -1,8 +1,12 @@
-use std::{io, panic};
+use std::{
+    io::{self, Write},
+    panic,
+};
 
+use base64::prelude::{Engine, BASE64_STANDARD};
 use color_eyre::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
@@ -32,7 +36,12 @@ impl Tui {
     /// It enables the raw mode and sets terminal properties.
     pub fn enter(&mut self) -> Result<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
+        crossterm::execute!(
+            io::stderr(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableFocusChange
+        )?;
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
@@ -53,6 +62,19 @@ impl Tui {
     /// [`rendering`]: crate::ui:render
     pub fn draw(&mut self, app: &mut App) -> Result<()> {
         self.terminal.draw(|frame| ui::render(app, frame))?;
+        if app.ring_bell {
+            app.ring_bell = false;
+            let mut stderr = io::stderr();
+            stderr.write_all(b"\x07")?;
+            stderr.flush()?;
+        }
+        if let Some(text) = app.clipboard.take() {
+            // OSC 52, which works over SSH as well. Terminals which don't
+            // support it ignore it.
+            let mut stderr = io::stderr();
+            write!(stderr, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))?;
+            stderr.flush()?;
+        }
         Ok(())
     }
 
@@ -62,7 +84,12 @@ impl Tui {
     /// the terminal properties if unexpected errors occur.
     fn reset() -> Result<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture)?;
+        crossterm::execute!(
+            io::stderr(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableFocusChange
+        )?;
         Ok(())
     }
 
@@ -1,16 +1,46 @@
-use chrono::Local;
+use chrono::{DateTime, Local};
 use humanize_duration::prelude::DurationExt;
 use humanize_duration::Truncate;
-use num_format::{Locale, ToFormattedString};
+use itertools::Itertools;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::Frame,
     style::{Modifier, Style, Stylize},
-    text::Line,
-    widgets::{Block, Borders, Padding, Paragraph, Row, Table},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Cell, Clear, HighlightSpacing, LineGauge, Padding, Paragraph, Row,
+        Sparkline, Table, TableState, Tabs, Wrap,
+    },
 };
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::app::App;
+use crate::{
+    app::{App, DetailsTab, SortColumn, VersionChange},
+    big_text,
+    compare::MetadataDiff,
+    failures::FailureReport,
+    glossary, keymap,
+    models::{effective, StatusResult},
+    notification::NotificationKind,
+    theme::Theme,
+    upload::Upload,
+};
+
+const HIGHLIGHT_SYMBOL: &str = ">>";
+
+/// Computes the area of every header cell of a table rendered into `area`,
+/// mirroring the column layout `Table` uses internally.
+fn header_cells(area: Rect, widths: &[Constraint]) -> Vec<Rect> {
+    let [_selection, columns] = Layout::horizontal([
+        Constraint::Length(HIGHLIGHT_SYMBOL.len() as u16),
+        Constraint::Fill(0),
+    ])
+    .areas(Rect { height: 1, ..area });
+    Layout::horizontal(widths.to_vec())
+        .spacing(1)
+        .split(columns)
+        .to_vec()
+}
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -35,192 +65,1055 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
-pub fn render(app: &mut App, f: &mut Frame) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(4),
-            Constraint::Min(1),
-            Constraint::Length(9),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .split(f.area());
-    let title_block = Block::default()
+fn kind_style(kind: NotificationKind) -> Style {
+    match kind {
+        NotificationKind::Error => Style::new().red(),
+        NotificationKind::Warning => Style::new().yellow(),
+        NotificationKind::Info => Style::new().green(),
+    }
+}
+
+/// Renders the headline numbers in large digits, see
+/// [`Config::big_counters`](crate::app::Config::big_counters).
+fn render_big_counters(app: &App, f: &mut Frame, area: Rect) {
+    let fill = app.theme.symbol("█", "#").chars().next().unwrap_or('#');
+    let failures = app.failures_last_hour();
+    let counters = [
+        ("Jobs running", app.status.total, Style::new()),
+        (
+            "Failed in the last hour",
+            failures,
+            match failures {
+                0 => Style::new(),
+                _ => kind_style(NotificationKind::Error),
+            },
+        ),
+    ];
+    let areas = Layout::horizontal([Constraint::Ratio(1, 2); 2]).split(area);
+    for ((title, value, style), area) in counters.into_iter().zip(areas.iter()) {
+        let lines = big_text::lines(&value.to_string(), fill).map(Line::from);
+        let counter = Paragraph::new(lines.to_vec())
+            .alignment(Alignment::Center)
+            .style(style)
+            .block(Block::bordered().title(title));
+        f.render_widget(counter, *area);
+    }
+}
+
+/// Renders the toast queue stacked in the top right corner of `area`, newest
+/// toast at the top.
+fn render_toasts(app: &App, f: &mut Frame, area: Rect) {
+    let mut y = area.y + 1;
+    for toast in app.toasts.iter().rev() {
+        let width = (toast.message.width() as u16 + 4).min(area.width / 2);
+        if y + 3 > area.bottom() {
+            break;
+        }
+        let toast_area = Rect::new(area.right().saturating_sub(width + 1), y, width, 3);
+        f.render_widget(Clear, toast_area);
+        f.render_widget(
+            Paragraph::new(toast.message.clone()).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .border_style(kind_style(toast.kind)),
+            ),
+            toast_area,
+        );
+        y += 3;
+    }
+}
+
+/// Shortens `text` to at most `width` terminal columns, marking the cut with an
+/// ellipsis. Widths are measured per character so that wide (e.g. CJK or
+/// emoji) characters don't push the following columns out of alignment.
+fn truncate(text: &str, width: u16) -> String {
+    let width = width as usize;
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += char_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Cuts a `width` columns wide window out of `text`, which starts `step`
+/// characters in and wraps around, so that text too long to fit scrolls by
+/// as `step` increases.
+fn marquee(text: &str, width: u16, step: usize) -> String {
+    if text.width() <= width as usize {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().chain("   ".chars()).collect();
+    let mut window = String::new();
+    let mut used = 0;
+    for c in chars.iter().cycle().skip(step % chars.len()) {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width as usize {
+            break;
+        }
+        window.push(*c);
+        used += char_width;
+    }
+    window
+}
+
+/// Frame of the spinner shown while a fetch started `elapsed` ago is running.
+fn spinner(theme: &Theme, elapsed: chrono::Duration) -> &'static str {
+    const UNICODE: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    const ASCII: [&str; 4] = ["|", "/", "-", "\\"];
+    let frame = elapsed.num_milliseconds().max(0) as usize / 100;
+    match theme.unicode {
+        true => UNICODE[frame % UNICODE.len()],
+        false => ASCII[frame % ASCII.len()],
+    }
+}
+
+/// How long ago something happened, `elapsed` ago. The time is rounded down
+/// to `resolution` seconds, so that the text doesn't change every second.
+fn fetch_age(elapsed: chrono::Duration, resolution: i64) -> String {
+    let resolution = resolution.max(1);
+    let rounded = elapsed.num_seconds() / resolution * resolution;
+    if rounded == 0 && resolution > 1 {
+        return "just now".to_string();
+    }
+    let truncate = match resolution {
+        60.. => Truncate::Minute,
+        _ => Truncate::Second,
+    };
+    format!("{} ago", chrono::Duration::seconds(rounded).human(truncate))
+}
+
+/// Formats a number of bytes with decimal units, like "4.2 MB".
+fn human_bytes(bytes: usize) -> String {
+    match bytes {
+        0..1_000 => format!("{bytes} B"),
+        1_000..1_000_000 => format!("{:.1} kB", bytes as f64 / 1e3),
+        _ => format!("{:.1} MB", bytes as f64 / 1e6),
+    }
+}
+
+fn is_rtl(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{08FF}' // Hebrew, Arabic, Syriac, Thaana, NKo and friends
+        | '\u{FB1D}'..='\u{FDFF}' // Hebrew and Arabic presentation forms A
+        | '\u{FE70}'..='\u{FEFF}' // Arabic presentation forms B
+    )
+}
+
+/// Wraps text containing right-to-left characters in a first-strong isolate,
+/// so that terminals applying the bidi algorithm reorder the text itself, but
+/// not the neighbouring columns. Isolates are zero width, which keeps the
+/// table aligned.
+fn isolate_bidi(text: String) -> String {
+    match text.chars().any(is_rtl) {
+        true => format!("\u{2068}{text}\u{2069}"),
+        false => text,
+    }
+}
+
+/// Builds a table row, truncating every cell to the width of its column.
+fn table_row(cells: Vec<String>, columns: &[Rect]) -> Row<'static> {
+    Row::new(table_cells(cells, columns))
+}
+
+fn table_cells(cells: Vec<String>, columns: &[Rect]) -> Vec<Cell<'static>> {
+    cells
+        .into_iter()
+        .zip(columns)
+        .map(|(cell, column)| Cell::from(isolate_bidi(truncate(&cell, column.width))))
+        .collect()
+}
+
+/// A bar `width` cells wide filled to `percent`, followed by the percentage,
+/// for the Progress column. Unicode bars grow by eighths of a cell.
+fn progress_bar(percent: u64, width: u16, theme: &Theme) -> String {
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    let width = width as usize;
+    let eighths = percent.min(100) as usize * width * 8 / 100;
+    let bar = match theme.unicode {
+        true => "█".repeat(eighths / 8) + EIGHTHS[eighths % 8],
+        false => "#".repeat(eighths / 8),
+    };
+    let padding = width - bar.width();
+    format!("{bar}{} {percent:>3}%", " ".repeat(padding))
+}
+
+/// Shifts from red to green as a collection approaches completion.
+fn progress_style(percent: u64) -> Style {
+    match percent {
+        0..50 => kind_style(NotificationKind::Error),
+        50..90 => kind_style(NotificationKind::Warning),
+        _ => kind_style(NotificationKind::Info),
+    }
+}
+
+fn or_na(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("N/A")
+}
+
+/// Lines of the `tab` of the details pane.
+fn details_lines(app: &App, result: &StatusResult, tab: DetailsTab) -> Vec<Line<'static>> {
+    match tab {
+        DetailsTab::Overview => {
+            let url = match &result.collection {
+                Some(col) => col.links.ui.clone(),
+                None => "N/A".to_string(),
+            };
+            vec![
+                Line::from(format!(
+                    "Total: {} | Active: {} | Finished: {}",
+                    result.total, result.active, result.finished
+                )),
+                Line::from(format!(
+                    "Effective total: {} ({}% done, without aborted and cancelled tasks)",
+                    result.effective_total(),
+                    result.progress()
+                )),
+                Line::from(format!("Remaining time: {}", or_na(&result.remaining_time))),
+                Line::from(format!("Took: {}", or_na(&result.took))),
+                Line::from(format!("URL: {url}")),
+            ]
+        }
+        DetailsTab::Tasks => {
+            let now = Local::now();
+            let mut lines = Vec::new();
+            for batch in &result.batches {
+                lines.push(breakdown_line(
+                    0,
+                    &batch.name,
+                    [batch.todo, batch.doing, batch.succeeded, batch.failed],
+                    [&batch.min_ts, &batch.remaining_time],
+                ));
+                for queue in &batch.queues {
+                    lines.push(breakdown_line(
+                        1,
+                        &queue.name,
+                        [queue.todo, queue.doing, queue.succeeded, queue.failed],
+                        [&queue.min_ts, &queue.remaining_time],
+                    ));
+                    lines.extend(queue.tasks.iter().map(|task| {
+                        let mut line = breakdown_line(
+                            2,
+                            &task.name,
+                            [task.todo, task.doing, task.succeeded, task.failed],
+                            [&task.min_ts, &task.remaining_time],
+                        );
+                        if let Some(stuck) = app.stuck_for(result, (batch, queue, task), now) {
+                            line.push_span(format!(
+                                ", nothing finished for {}, possibly retrying",
+                                stuck.human(Truncate::Minute)
+                            ));
+                            line = line.patch_style(kind_style(NotificationKind::Warning));
+                        }
+                        line
+                    }));
+                }
+            }
+            lines
+        }
+        DetailsTab::Errors => {
+            let mut lines: Vec<Line> = result
+                .batches
+                .iter()
+                .flat_map(|batch| &batch.queues)
+                .flat_map(|queue| &queue.tasks)
+                .filter(|task| task.failed > 0)
+                .map(|task| Line::from(format!("{}: {} failed", task.name, task.failed)).red())
+                .collect();
+            match &app.failure_report {
+                Some(report) if report.key == result.key() => {
+                    lines.push(Line::from(format!(
+                        "Errors of {} out of {} failed documents:",
+                        report.sampled, report.total
+                    )));
+                    lines.extend(
+                        report
+                            .clusters
+                            .iter()
+                            .map(|(error, count)| Line::from(format!("  {error} ×{count}"))),
+                    );
+                }
+                _ => lines.push(Line::from("Press `e` to fetch failed documents")),
+            }
+            lines
+        }
+        DetailsTab::Metadata => match &result.collection {
+            Some(col) => vec![
+                Line::from(format!(
+                    "Foreign ID: {} | Category: {} | Frequency: {}",
+                    col.foreign_id, col.category, col.frequency
+                )),
+                Line::from(format!(
+                    "Countries: {}",
+                    col.countries.as_deref().unwrap_or_default().join(", ")
+                )),
+                Line::from(format!(
+                    "Created: {} | Updated: {}",
+                    col.created_at, col.updated_at
+                )),
+                Line::from(format!(
+                    "Casefile: {} | Secret: {} | Writeable: {}",
+                    col.casefile, col.secret, col.writeable
+                )),
+            ],
+            None => vec![Line::from(format!("Job {} has no collection", result.name))],
+        },
+        DetailsTab::Xref => match &app.xref_summary {
+            Some(summary) if summary.key == result.key() => vec![
+                Line::from(format!("Match candidates: {}", summary.total)),
+                Line::from(format!(
+                    "Decided: {} of the {} most likely",
+                    summary.decided(),
+                    summary.sampled
+                )),
+                Line::from(format!(
+                    "Same: {} | Different: {} | Unsure: {}",
+                    summary.positive, summary.negative, summary.unsure
+                )),
+            ],
+            _ if result.collection.is_none() => {
+                vec![Line::from(format!("Job {} has no collection", result.name))]
+            }
+            _ => vec![Line::from(
+                "Press `x` to fetch the cross-referencing results",
+            )],
+        },
+        DetailsTab::History => match app.version_changes.is_empty() {
+            true => vec![Line::from("No history recorded yet")],
+            false => app
+                .version_changes
+                .iter()
+                .map(|change| {
+                    Line::from(format!(
+                        "{}: Aleph upgraded from {} to {}",
+                        change.time.format("%Y-%m-%d %H:%M:%S"),
+                        change.from,
+                        change.to
+                    ))
+                })
+                .collect(),
+        },
+    }
+}
+
+/// A batch, queue or task of the Tasks tab, indented by `depth`, with its
+/// todo, doing, succeeded and failed `counts` and when it started and the
+/// remaining time, if known.
+fn breakdown_line(
+    depth: usize,
+    name: &str,
+    counts: [u64; 4],
+    [started, remaining]: [&Option<String>; 2],
+) -> Line<'static> {
+    let [todo, doing, succeeded, failed] = counts;
+    let mut text = format!(
+        "{}{name}: {todo} todo, {doing} doing, {succeeded} succeeded, {failed} failed",
+        "  ".repeat(depth)
+    );
+    if let Some(started) = started {
+        text.push_str(&format!(", started {started}"));
+    }
+    if let Some(remaining) = remaining {
+        text.push_str(&format!(", {remaining} left"));
+    }
+    match depth {
+        0 => Line::from(text).bold(),
+        _ => Line::from(text),
+    }
+}
+
+/// Renders the details pane of the selected collection, with a tab bar at
+/// the top. Returns how far its body can be scrolled.
+fn render_details(app: &App, result: &StatusResult, f: &mut Frame, area: Rect) -> u16 {
+    let title = match &result.collection {
+        Some(col) => format!(
+            "Collection {} <{}>",
+            col.collection_id,
+            isolate_bidi(col.label.clone())
+        ),
+        None => "Details".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .padding(Padding::horizontal(1))
         .borders(Borders::ALL)
         .border_type(ratatui::widgets::BorderType::Rounded);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    let text = vec![
-        Line::from(match &app.metadata.app.title {
-            Some(title) => format!(
-                "{} ({}): {} jobs running",
-                title,
-                app.current_profile().name,
-                app.status.total
-            ),
-            None => format!(
-                "({}): {} jobs running",
-                app.current_profile().name,
-                app.status.total
-            ),
-        }),
-        Line::from(
-            match (&app.metadata.app.version, &app.metadata.app.ftm_version) {
-                (Some(aleph), Some(ftm)) => format!("version: {aleph}, followthemoney: {ftm}"),
-                (None, Some(ftm)) => format!("followthemoney: {ftm}"),
-                (Some(aleph), None) => format!("version: {aleph}"),
-                (None, None) => String::default(),
-            },
+    let [tabs_area, body_area] =
+        Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(inner);
+    let selected = DetailsTab::ALL
+        .iter()
+        .position(|tab| *tab == app.details_tab)
+        .unwrap_or_default();
+    let tabs = Tabs::new(DetailsTab::ALL.iter().map(|tab| tab.title()))
+        .select(selected)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    f.render_widget(tabs, tabs_area);
+    let body_area = match app.details_tab {
+        DetailsTab::Overview => {
+            let chart_width = (body_area.width / 3).min(40);
+            let [text_area, chart_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Length(chart_width)])
+                    .spacing(1)
+                    .areas(body_area);
+            render_throughput_per_minute(app, result, f, chart_area);
+            text_area
+        }
+        _ => body_area,
+    };
+    let mut lines = details_lines(app, result, app.details_tab);
+    let mut line_count = lines.len();
+    let details = match app.wrap_details {
+        true => {
+            // The title cuts off long labels as well.
+            if app.details_tab == DetailsTab::Overview {
+                let label = isolate_bidi(result.label().to_string());
+                lines.insert(0, Line::from(format!("Label: {label}")));
+            }
+            // Roughly, since words which don't fit start a new line.
+            let width = usize::from(body_area.width.max(1));
+            line_count = lines
+                .iter()
+                .map(|line| line.width().div_ceil(width).max(1))
+                .sum();
+            Paragraph::new(lines).wrap(Wrap { trim: false })
+        }
+        false => Paragraph::new(lines),
+    };
+    let max_scroll = (line_count as u16).saturating_sub(body_area.height);
+    f.render_widget(
+        details.scroll((app.details_scroll.min(max_scroll), 0)),
+        body_area,
+    );
+    max_scroll
+}
+
+/// Renders a sparkline of the tasks of `result` processed per minute, to tell
+/// whether processing speeds up or stalls.
+fn render_throughput_per_minute(app: &App, result: &StatusResult, f: &mut Frame, area: Rect) {
+    let per_minute = app
+        .collections
+        .get(result.key())
+        .map(|state| state.throughput_per_minute())
+        .unwrap_or_default();
+    let block = Block::default()
+        .title("Processed per minute")
+        .borders(Borders::LEFT)
+        .padding(Padding::left(1));
+    match per_minute.last() {
+        None => f.render_widget(
+            Paragraph::new("Not enough fetches yet")
+                .wrap(Wrap { trim: true })
+                .block(block),
+            area,
+        ),
+        Some((_, last)) => {
+            let [title_area, chart_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+                    .areas(block.inner(area));
+            f.render_widget(block, area);
+            f.render_widget(Line::from(format!("Latest: {last}/min")), title_area);
+            render_sparkline(app, &per_minute, f, chart_area);
+        }
+    }
+}
+
+/// Renders a sparkline of the most recent values of `series` which fit, with
+/// the Aleph upgrades in the meantime marked below it, see [`upgrade_marks`].
+fn render_sparkline(app: &App, series: &[(DateTime<Local>, u64)], f: &mut Frame, area: Rect) {
+    let series = &series[series.len().saturating_sub(area.width as usize)..];
+    let chart_area = match upgrade_marks(&app.version_changes, series) {
+        None => area,
+        Some(marks) => {
+            let [chart_area, marks_area] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+            f.render_widget(
+                Line::styled(marks, kind_style(NotificationKind::Warning)),
+                marks_area,
+            );
+            chart_area
+        }
+    };
+    let data: Vec<u64> = series.iter().map(|(_, value)| *value).collect();
+    f.render_widget(
+        Sparkline::default()
+            .data(&data)
+            .style(kind_style(NotificationKind::Info)),
+        chart_area,
+    );
+}
+
+/// A line to put below a sparkline of `series`, with the version Aleph was
+/// upgraded to starting below the value fetched closest to the upgrade.
+/// `None` if there was no upgrade while `series` was fetched.
+fn upgrade_marks(changes: &[VersionChange], series: &[(DateTime<Local>, u64)]) -> Option<String> {
+    let (first, _) = series.first()?;
+    let (last, _) = series.last()?;
+    let mut marks = vec![' '; series.len()];
+    let upgrades: Vec<_> = changes
+        .iter()
+        .filter(|change| (*first..=*last).contains(&change.time))
+        .collect();
+    for change in &upgrades {
+        let distance = |index: &usize| (series[*index].0 - change.time).abs();
+        let Some(closest) = (0..series.len()).min_by_key(distance) else {
+            continue;
+        };
+        let mark = format!("▲{}", change.to);
+        for (slot, c) in marks[closest..].iter_mut().zip(mark.chars()) {
+            *slot = c;
+        }
+    }
+    (!upgrades.is_empty()).then(|| marks.into_iter().collect())
+}
+
+/// Renders how far the first fetch got, which can take a while on large
+/// instances.
+fn render_loading(app: &App, f: &mut Frame, area: Rect) {
+    let progress = &app.fetch_progress;
+    let received = human_bytes(progress.received() as usize);
+    let text = match (progress.is_parsing(), progress.expected()) {
+        (true, _) => format!("Parsing {received} of status"),
+        (false, _) if progress.received() == 0 => "Waiting for the instance to respond".to_string(),
+        (false, Some(expected)) if expected > 0 => {
+            let percent = progress.received() * 100 / expected;
+            let expected = human_bytes(expected as usize);
+            format!("Received {received} of {expected} ({percent}%)")
+        }
+        (false, _) => format!("Received {received}"),
+    };
+    let block = Block::default()
+        .title("Loading the status")
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL)
+        .border_type(ratatui::widgets::BorderType::Rounded);
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn render_upload(upload: &Upload, f: &mut Frame, area: Rect) {
+    let (files, total_files) = upload.progress.files();
+    let (bytes, total_bytes) = upload.progress.bytes();
+    let label = format!(
+        "Uploading {files}/{total_files} files ({} of {}) to {}",
+        human_bytes(bytes as usize),
+        human_bytes(total_bytes as usize),
+        upload.label
+    );
+    let ratio = match total_bytes {
+        0 => files as f64 / total_files.max(1) as f64,
+        _ => bytes as f64 / total_bytes as f64,
+    };
+    let gauge = LineGauge::default()
+        .label(truncate(&label, area.width.saturating_sub(16)))
+        .ratio(ratio.clamp(0.0, 1.0));
+    f.render_widget(gauge, area);
+}
+
+/// Renders the selected collection full screen, with all tabs of the details
+/// pane and a chart of its recent progress at once.
+fn render_focus(app: &App, result: &StatusResult, f: &mut Frame, area: Rect) {
+    let block = Block::default()
+        .title(format!(
+            "{} (`Z` to go back)",
+            isolate_bidi(result.label().to_string())
+        ))
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL)
+        .border_type(ratatui::widgets::BorderType::Rounded);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let [overview_area, chart_area, lists_area] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Length(7),
+        Constraint::Min(1),
+    ])
+    .areas(inner);
+    let mut overview = details_lines(app, result, DetailsTab::Overview);
+    overview.extend(
+        details_lines(app, result, DetailsTab::Metadata)
+            .into_iter()
+            .next(),
+    );
+    f.render_widget(Paragraph::new(overview), overview_area);
+
+    let throughput = app
+        .collections
+        .get(result.key())
+        .map(|state| state.throughput())
+        .unwrap_or_default();
+    let chart_block = Block::default()
+        .title("Tasks processed per fetch")
+        .borders(Borders::TOP);
+    match throughput.is_empty() {
+        true => f.render_widget(
+            Paragraph::new("Not enough fetches yet").block(chart_block),
+            chart_area,
         ),
+        false => {
+            let inner = chart_block.inner(chart_area);
+            f.render_widget(chart_block, chart_area);
+            render_sparkline(app, &throughput, f, inner);
+        }
+    }
+
+    let [tasks_area, errors_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .spacing(1)
+            .areas(lists_area);
+    for (tab, area) in [
+        (DetailsTab::Tasks, tasks_area),
+        (DetailsTab::Errors, errors_area),
+    ] {
+        let block = Block::default().title(tab.title()).borders(Borders::TOP);
+        f.render_widget(
+            Paragraph::new(details_lines(app, result, tab)).block(block),
+            area,
+        );
+    }
+}
+
+/// Renders one compact panel per watched collection, stacked on top of each
+/// other.
+fn render_watched(app: &App, f: &mut Frame, area: Rect) {
+    let format = &app.config.number_format;
+    let number = |column, value| format.format(column, value);
+    let watched = app.watched_results();
+    let areas = Layout::vertical(vec![Constraint::Length(3); watched.len()]).split(area);
+    for ((key, result), area) in watched.into_iter().zip(areas.iter()) {
+        let (title, line) = match result {
+            Some(result) => {
+                let progress = result.progress();
+                (
+                    result.label().to_string(),
+                    format!(
+                        "{progress}% | {} todo, {} doing, {} succeeded, {} failed | remaining: {}",
+                        number(SortColumn::Todo, result.todo),
+                        number(SortColumn::Doing, result.doing),
+                        number(SortColumn::Succeeded, result.succeeded),
+                        number(SortColumn::Failed, result.failed),
+                        or_na(&result.remaining_time)
+                    ),
+                )
+            }
+            None => (key.to_string(), "No longer processing".to_string()),
+        };
+        let block = Block::default()
+            .title(format!("◉ {}", isolate_bidi(title)))
+            .padding(Padding::horizontal(1))
+            .borders(Borders::ALL)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        f.render_widget(Paragraph::new(line).block(block), *area);
+    }
+}
+
+/// Renders the collection table with its header and totals row.
+fn render_table(app: &mut App, f: &mut Frame, area: Rect) {
+    let format = app.config.number_format.clone();
+    let number = |column, value| format.format(column, value);
+    let widths = [
+        Constraint::Length(15), // Collection ID
+        Constraint::Length(15), // Foreign ID
+        Constraint::Min(20),    // Label
+        Constraint::Length(20), // Start Timestamp
+        Constraint::Length(8),  // Todo
+        Constraint::Length(9),  // Effective Todo
+        Constraint::Length(8),  // Doing
+        Constraint::Length(8),  // Succeeded
+        Constraint::Length(8),  // Failed
+        Constraint::Length(8),  // Aborted
+        Constraint::Length(8),  // Aborting
+        Constraint::Length(8),  // Cancelled
+        Constraint::Length(6),  // Health
+        Constraint::Length(13), // Progress
     ];
-    let title = Paragraph::new(text).block(title_block);
-    f.render_widget(title, chunks[0]);
+    // The header and the totals row live in a table of their own, so that they
+    // stay in place while the collection table scrolls underneath.
+    let [header_area, table_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(area);
+    let columns = header_cells(header_area, &widths);
 
     let mut rows = Vec::new();
 
-    for result in &app.status.results {
+    let now = Local::now();
+    let results = app.visible_results();
+    for result in &results {
         // ROW 1: Collection row
         let collection_id = match &result.collection {
             Some(c) => c.collection_id.clone(),
             None => "-".to_string(),
         };
+        let tasks = app.visible_tasks(result);
+        let indicator = match (result.tasks().next(), tasks.is_empty()) {
+            (None, _) => "  ",
+            (Some(_), true) => app.theme.symbol("▸ ", "+ "),
+            (Some(_), false) => app.theme.symbol("▾ ", "- "),
+        };
+        let collection_id = format!("{indicator}{collection_id}");
         let collection_foreign_id = match &result.collection {
             Some(c) => c.foreign_id.clone(),
             None => "-".to_string(),
         };
-        let collection_label = match &result.collection {
-            Some(c) => c.label.clone(),
-            None => result.name.clone(),
-        };
+        let mut collection_label = String::new();
+        if app.is_watched(result) {
+            collection_label.push_str("◉ ");
+        }
+        if result.is_destructive() {
+            collection_label.push_str(app.theme.symbol("⚠ ", "[!] "));
+        }
+        if app.is_muted(result.key(), now) {
+            collection_label.push_str(app.theme.symbol("🔕 ", "[M] "));
+        }
+        if app.has_stuck_tasks(result, now) {
+            collection_label.push_str(app.theme.symbol("⟳ ", "[R] "));
+        }
+        if app.notify_when_done.contains(result.key()) {
+            collection_label.push_str(app.theme.symbol("🔔 ", "[N] "));
+        }
+        if let Some(collection) = &result.collection {
+            if collection.secret {
+                collection_label.push_str(app.theme.symbol("🔒 ", "[S] "));
+            }
+            if collection.casefile {
+                collection_label.push_str(app.theme.symbol("📁 ", "[C] "));
+            }
+        }
+        collection_label.push_str(result.label());
+        for tag in app.tags(result.key()) {
+            collection_label.push_str(&format!(" #{tag}"));
+        }
         let start_timestamp = result.min_ts.as_ref().unwrap_or(&"-".to_string()).clone();
+        let health = app.health(result, now);
+        let style = match health {
+            // Reversed, so that deleting jobs stand out without colors too.
+            _ if result.is_destructive() => {
+                kind_style(NotificationKind::Warning).add_modifier(Modifier::REVERSED)
+            }
+            0..50 => kind_style(NotificationKind::Error),
+            50..80 => kind_style(NotificationKind::Warning),
+            _ => Style::new(),
+        };
 
-        rows.push(Row::new(vec![
-            collection_id,
-            collection_foreign_id,
-            collection_label,
-            start_timestamp,
-            result.todo.to_formatted_string(&Locale::en),
-            result.doing.to_formatted_string(&Locale::en),
-            result.succeeded.to_formatted_string(&Locale::en),
-            result.failed.to_formatted_string(&Locale::en),
-            result.aborted.to_formatted_string(&Locale::en),
-            result.aborting.to_formatted_string(&Locale::en),
-            result.cancelled.to_formatted_string(&Locale::en),
-        ]).style(Style::new().add_modifier(Modifier::BOLD)));
+        let progress = result.progress();
+        let bar_width = columns[13].width.saturating_sub(5);
+        let mut cells = table_cells(
+            vec![
+                collection_id,
+                collection_foreign_id,
+                collection_label,
+                start_timestamp,
+                number(SortColumn::Todo, result.todo),
+                number(SortColumn::EffectiveTodo, result.effective_todo()),
+                number(SortColumn::Doing, result.doing),
+                number(SortColumn::Succeeded, result.succeeded),
+                number(SortColumn::Failed, result.failed),
+                number(SortColumn::Aborted, result.aborted),
+                number(SortColumn::Aborting, result.aborting),
+                number(SortColumn::Cancelled, result.cancelled),
+                health.to_string(),
+            ],
+            &columns,
+        );
+        cells.push(
+            Cell::from(progress_bar(progress, bar_width, &app.theme))
+                .style(progress_style(progress)),
+        );
+        rows.push(Row::new(cells).style(style.add_modifier(Modifier::BOLD)));
 
         // ROW 2+: Task rows
-        for batch in &result.batches {
-            for queue in &batch.queues {
-                for task in &queue.tasks {
-                    let task_start_timestamp = task.min_ts.as_ref().unwrap_or(&"-".to_string()).clone();
-
-                    rows.push(Row::new(vec![
-                        "".to_string(), // Empty collection ID column
-                        batch.name.clone(), // Batch name in foreign ID column
-                        format!("  {}", task.name), // Indented task name in label column
-                        task_start_timestamp, // Task timestamp in same column as collection timestamp
-                        task.todo.to_formatted_string(&Locale::en),
-                        task.doing.to_formatted_string(&Locale::en),
-                        task.succeeded.to_formatted_string(&Locale::en),
-                        task.failed.to_formatted_string(&Locale::en),
-                        task.aborted.to_formatted_string(&Locale::en),
-                        task.aborting.to_formatted_string(&Locale::en),
-                        task.cancelled.to_formatted_string(&Locale::en),
-                    ]));
-                }
-            }
+        for (batch, queue, task) in tasks {
+            let task_start_timestamp = task.min_ts.as_ref().unwrap_or(&"-".to_string()).clone();
+            let stuck = app.stuck_for(result, (batch, queue, task), now).is_some();
+            let marker = match stuck {
+                true => app.theme.symbol("⟳ ", "[R] "),
+                false => "",
+            };
+
+            let row = table_row(
+                vec![
+                    "".to_string(),                     // Empty collection ID column
+                    batch.name.clone(),                 // Batch name in foreign ID column
+                    format!("  {marker}{}", task.name), // Indented task name in label column
+                    task_start_timestamp, // Task timestamp in same column as collection timestamp
+                    number(SortColumn::Todo, task.todo),
+                    number(
+                        SortColumn::EffectiveTodo,
+                        effective(task.todo, task.aborted, task.cancelled),
+                    ),
+                    number(SortColumn::Doing, task.doing),
+                    number(SortColumn::Succeeded, task.succeeded),
+                    number(SortColumn::Failed, task.failed),
+                    number(SortColumn::Aborted, task.aborted),
+                    number(SortColumn::Aborting, task.aborting),
+                    number(SortColumn::Cancelled, task.cancelled),
+                    "".to_string(),
+                    "".to_string(),
+                ],
+                &columns,
+            );
+            rows.push(match stuck {
+                true => row.style(kind_style(NotificationKind::Warning)),
+                false => row,
+            });
         }
     }
-    let widths = [
-        Constraint::Length(15),  // Collection ID
-        Constraint::Length(15),  // Foreign ID
-        Constraint::Min(20),     // Label
-        Constraint::Length(20),  // Start Timestamp
-        Constraint::Length(8),   // Todo
-        Constraint::Length(8),   // Doing
-        Constraint::Length(8),   // Succeeded
-        Constraint::Length(8),   // Failed
-        Constraint::Length(8),   // Aborted
-        Constraint::Length(8),   // Aborting
-        Constraint::Length(8),   // Cancelled
+    let titles = [
+        "Collection ID",
+        "Foreign ID",
+        "Label/Task Name",
+        "Start Time",
+        "Todo",
+        "Eff. Todo",
+        "Doing",
+        "Success",
+        "Failed",
+        "Aborted",
+        "Aborting",
+        "Cancel",
+        "Health",
+        "Progress",
     ];
+    let header = SortColumn::ALL
+        .iter()
+        .zip(titles)
+        .map(|(column, title)| match app.sort_column {
+            Some(sorted) if sorted == *column => match app.sort_descending {
+                true => format!("{title} ▼"),
+                false => format!("{title} ▲"),
+            },
+            _ if *column == SortColumn::Label && app.group_by_tag => format!("{title} by tag"),
+            _ => title.to_string(),
+        });
+    let total = |column: SortColumn, counter: fn(&StatusResult) -> u64| {
+        let total = results
+            .iter()
+            .map(|result| counter(result))
+            .fold(0u64, u64::saturating_add);
+        number(column, total)
+    };
+    let filters: Vec<String> = app
+        .counter_filter
+        .map(|counter| format!("{} > 0", counter.name()))
+        .into_iter()
+        .chain((!app.search.is_empty()).then(|| format!("matching {:?}", app.search)))
+        .collect();
+    let summary = table_row(
+        vec![
+            "".to_string(),
+            "".to_string(),
+            match filters.is_empty() {
+                false => format!(
+                    "Total: {} of {} ({})",
+                    results.len(),
+                    app.status.results.len(),
+                    filters.join(", ")
+                ),
+                true => format!("Total: {} collections", results.len()),
+            },
+            "".to_string(),
+            total(SortColumn::Todo, |r| r.todo),
+            total(SortColumn::EffectiveTodo, |r| r.effective_todo()),
+            total(SortColumn::Doing, |r| r.doing),
+            total(SortColumn::Succeeded, |r| r.succeeded),
+            total(SortColumn::Failed, |r| r.failed),
+            total(SortColumn::Aborted, |r| r.aborted),
+            total(SortColumn::Aborting, |r| r.aborting),
+            total(SortColumn::Cancelled, |r| r.cancelled),
+            "".to_string(),
+            "".to_string(),
+        ],
+        &columns,
+    )
+    .style(Style::new().add_modifier(Modifier::BOLD | Modifier::ITALIC))
+    .bottom_margin(1);
+    let header_table = Table::new([summary], widths)
+        .header(Row::new(header))
+        .highlight_symbol(HIGHLIGHT_SYMBOL)
+        .highlight_spacing(HighlightSpacing::Always);
+    f.render_widget(header_table, header_area);
+
+    app.header_columns = SortColumn::ALL.into_iter().zip(columns).collect();
     let table = Table::new(rows, widths)
-        .header(
-            Row::new(vec![
-                "Collection ID",
-                "Foreign ID",
-                "Label/Task Name",
-                "Start Time",
-                "Todo",
-                "Doing",
-                "Success",
-                "Failed",
-                "Aborted",
-                "Aborting",
-                "Cancel",
-            ])
-            .bottom_margin(1),
-        )
         .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
-        .highlight_symbol(">>");
-
-    f.render_stateful_widget(table, chunks[1], &mut app.collection_tablestate);
+        .highlight_symbol(HIGHLIGHT_SYMBOL)
+        .highlight_spacing(HighlightSpacing::Always);
 
-    if let Some(index) = app.collection_tablestate.selected() {
-        // Find which result and row type is selected
-        let mut current_row = 0;
-        let mut selected_result = None;
+    f.render_stateful_widget(table, table_area, &mut app.collection_tablestate);
+}
 
-        for result in &app.status.results {
-            if current_row == index {
-                selected_result = Some(result);
-                break;
-            }
-            current_row += 1;
+pub fn render(app: &mut App, f: &mut Frame) {
+    if app.screensaver {
+        return render_screensaver(app, f);
+    }
+    let [main_area, watched_area] = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(3 * app.watched.len() as u16),
+    ])
+    .areas(f.area());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(1),
+            Constraint::Length(app.details_height()),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(main_area);
+    render_watched(app, f, watched_area);
+    let title_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(ratatui::widgets::BorderType::Rounded);
 
-            // Skip task rows for this collection
-            for batch in &result.batches {
-                for queue in &batch.queues {
-                    current_row += queue.tasks.len();
-                }
+    let text = vec![
+        Line::from(format!(
+            "{}{}{}",
+            match &app.metadata.app.title {
+                Some(title) => format!(
+                    "{} ({}): {} jobs running",
+                    title,
+                    app.current_profile().name,
+                    app.status.total
+                ),
+                None => format!(
+                    "({}): {} jobs running",
+                    app.current_profile().name,
+                    app.status.total
+                ),
+            },
+            match app.status_pages() {
+                Some(pages) => format!(", page {} of {pages}", app.status_page + 1),
+                None => String::new(),
+            },
+            match app.throughput_averages() {
+                Some(averages) => format!(
+                    ", tasks per minute over 1, 5, 15 min: {}",
+                    averages
+                        .iter()
+                        .map(|average| format!("{average:.1}"))
+                        .join(", ")
+                ),
+                None => String::new(),
             }
+        )),
+        Line::from(
+            match (&app.metadata.app.version, &app.metadata.app.ftm_version) {
+                (Some(aleph), Some(ftm)) => format!("version: {aleph}, followthemoney: {ftm}"),
+                (None, Some(ftm)) => format!("followthemoney: {ftm}"),
+                (Some(aleph), None) => format!("version: {aleph}"),
+                (None, None) => String::default(),
+            } + &match app.version_changes.last() {
+                Some(change) => format!(
+                    " (changed from {} at {})",
+                    change.from,
+                    change.time.format("%H:%M")
+                ),
+                None => String::default(),
+            },
+        ),
+    ];
+    let banner = match (app.metadata.maintenance, &app.metadata.app.banner) {
+        (true, Some(banner)) => Some(format!("Instance in maintenance: {banner}")),
+        (true, None) => Some("Instance in maintenance".to_string()),
+        (false, banner) => banner.clone(),
+    };
+    // Fetch errors aren't shown during maintenance, the border makes up for
+    // them.
+    let title_block = match app.metadata.maintenance {
+        true => title_block.border_style(kind_style(NotificationKind::Warning)),
+        false => title_block,
+    };
+    let title_block = match banner {
+        Some(banner) => {
+            let banner = banner.split_whitespace().collect::<Vec<_>>().join(" ");
+            let width = chunks[0].width.saturating_sub(4);
+            let step = (Local::now().timestamp_millis() / 250) as usize;
+            title_block.title_bottom(
+                Line::from(format!(" {} ", marquee(&banner, width, step)))
+                    .style(kind_style(NotificationKind::Warning)),
+            )
         }
+        None => title_block,
+    };
+    let title = Paragraph::new(text).block(title_block);
+    f.render_widget(title, chunks[0]);
 
-        if let Some(result) = selected_result {
-            let title = match &result.collection {
-                Some(col) => format!("Collection {} <{}>", col.collection_id, col.label),
-                None => "Details".to_string(),
-            };
-
-            let url = match &result.collection {
-                Some(col) => col.links.ui.clone(),
-                None => "N/A".to_string(),
-            };
-
-            let body = format!(
-                "Total: {} | Active: {} | Finished: {}\nRemaining time: {}\nTook: {}\nURL: {}",
-                result.total,
-                result.active,
-                result.finished,
-                result.remaining_time.as_ref().unwrap_or(&"N/A".to_string()),
-                result.took.as_ref().unwrap_or(&"N/A".to_string()),
-                url
-            );
+    let counters_height = match app.config.big_counters {
+        true => big_text::HEIGHT as u16 + 2,
+        false => 0,
+    };
+    let [counters_area, table_area] =
+        Layout::vertical([Constraint::Length(counters_height), Constraint::Min(1)])
+            .areas(chunks[1]);
+    if app.config.big_counters {
+        render_big_counters(app, f, counters_area);
+    }
 
-            let info_block = Block::default()
-                .title(title)
-                .padding(Padding::new(1, 1, 1, 1))
-                .borders(Borders::ALL)
-                .border_type(ratatui::widgets::BorderType::Rounded);
-            let info_block = Paragraph::new(body).block(info_block);
-            f.render_widget(info_block, chunks[2]);
+    if app.is_zoomed() {
+        if let Some(result) = app.selected_result() {
+            render_focus(app, result, f, table_area.union(chunks[2]));
+        }
+    } else {
+        render_table(app, f, table_area);
+        if let Some(result) = app.selected_result() {
+            app.details_max_scroll = render_details(app, result, f, chunks[2]);
+        } else if app.is_fetching && app.status.results.is_empty() {
+            render_loading(app, f, chunks[2]);
         }
     }
 
-    f.render_widget(
-        Paragraph::new(app.error_message.to_string()).style(Style::new().red()),
-        chunks[3],
-    );
+    let notifications = app.notifications.iter().enumerate().flat_map(|(i, n)| {
+        let style = kind_style(n.kind);
+        let separator = match i {
+            0 => Span::raw(""),
+            _ => Span::raw(" | "),
+        };
+        [separator, Span::styled(n.message.clone(), style)]
+    });
+    let prompt = match &app.tag_editor {
+        _ if app.login_prompt.is_some() => app.login_prompt.as_ref().map(|prompt| {
+            let profile = &app.config.profiles[prompt.profile].name;
+            let password = "*".repeat(prompt.password.chars().count());
+            format!("Password for {profile}: {password}")
+        }),
+        _ if app.upload_prompt.is_some() => app
+            .upload_prompt
+            .as_ref()
+            .map(|prompt| format!("Upload to {}: {}", prompt.collection.label, prompt.path)),
+        _ if app.is_searching => Some(format!("/{}", app.search)),
+        Some(editor) => Some(format!("Tags of {}: {}", editor.label, editor.input)),
+        None => None,
+    };
+    match prompt {
+        Some(prompt) => {
+            f.set_cursor_position((chunks[3].x + prompt.width() as u16, chunks[3].y));
+            f.render_widget(Paragraph::new(prompt), chunks[3]);
+        }
+        None => {
+            let mut area = chunks[3];
+            if let Some(upload) = &app.upload {
+                let [gauge, rest] =
+                    Layout::horizontal([Constraint::Length(80), Constraint::Fill(1)]).areas(area);
+                render_upload(upload, f, gauge);
+                area = rest;
+            }
+            f.render_widget(Paragraph::new(Line::from_iter(notifications)), area)
+        }
+    }
 
     let status_bar_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -230,25 +1123,61 @@ pub fn render(app: &mut App, f: &mut Frame) {
         Block::default().title(format!("aleph-tui version {}", app.version)),
         status_bar_chunks[0],
     );
+    // Fetches run in the background, so the spinner keeps turning while
+    // the instance is responding.
     let fetching_icon = match app.is_fetching {
-        true => "🔄",
+        true => spinner(&app.theme, Local::now() - app.last_fetch),
         false => "",
     };
-    let last_fetch = Local::now() - app.last_fetch;
-    let last_fetch = last_fetch.human(Truncate::Second);
-    let last_fetch_text = format!(
-        "{} fetching every {}s - last fetch {} ago",
-        fetching_icon, app.config.fetch_interval, last_fetch,
+    let last_fetch = fetch_age(
+        Local::now() - app.last_fetch,
+        app.config.fetch_age_resolution,
     );
+    let keep_awake_text = match app.sleep_inhibitor.is_active() {
+        true => " - keeping awake",
+        false => "",
+    };
+    let schedule = match app.rate_limited_for(Local::now()) {
+        Some(remaining) => Span::styled(
+            format!(
+                "rate limited, retrying in {}s",
+                (remaining.num_milliseconds() + 999) / 1000
+            ),
+            kind_style(NotificationKind::Warning),
+        ),
+        None if app.is_fetch_interval_adjusted() => Span::styled(
+            format!("fetching every {}s (adjusted)", app.fetch_interval()),
+            kind_style(NotificationKind::Info),
+        ),
+        None => Span::raw(format!("fetching every {}s", app.fetch_interval())),
+    };
+    let mut last_fetch_line = match &app.status_file {
+        Some(path) => Line::from(format!(" loaded from {}", path.display())),
+        None => Line::from_iter([
+            Span::raw(format!("{fetching_icon} ")),
+            schedule,
+            Span::raw(format!(" - last fetch {last_fetch}{keep_awake_text}")),
+        ]),
+    };
+    if app.config.profiles[app.current_profile].has_fallbacks() {
+        last_fetch_line.push_span(format!(" via {}", app.url(app.current_profile)));
+    }
+    if let Some(size) = app.status_size {
+        let style = match app.is_status_large() {
+            true => kind_style(NotificationKind::Warning),
+            false => Style::default(),
+        };
+        last_fetch_line.push_span(Span::styled(format!(" ({})", human_bytes(size)), style));
+    }
     f.render_widget(
         Block::default()
-            .title(last_fetch_text)
+            .title(last_fetch_line)
             .title_alignment(Alignment::Left),
         status_bar_chunks[1],
     );
     f.render_widget(
         Block::default()
-            .title("Shortcuts: `q`, `^C`, `Esc` - quit, `p` - select profile")
+            .title(keymap::hints(app.key_context()))
             .title_alignment(Alignment::Right),
         status_bar_chunks[2],
     );
@@ -263,7 +1192,11 @@ pub fn render(app: &mut App, f: &mut Frame) {
 
         let mut rows = Vec::new();
         for (idx, profile) in app.config.profiles.clone().into_iter().enumerate() {
-            rows.push(Row::new([profile.name.to_string()]));
+            let name = match app.compare_with == Some(profile.index) {
+                true => format!("{} (comparing)", profile.name),
+                false => profile.name.to_string(),
+            };
+            rows.push(Row::new([name]));
             if app.current_profile == profile.index {
                 app.profile_tablestate.select(Some(idx))
             }
@@ -277,4 +1210,380 @@ pub fn render(app: &mut App, f: &mut Frame) {
             &mut app.profile_tablestate,
         );
     }
+
+    if app.show_request_log {
+        render_request_log(app, f);
+    }
+    if app.show_help {
+        render_help(app, f);
+    }
+    if let Some(diff) = &app.metadata_diff {
+        render_metadata_diff(diff, f);
+    }
+    if let (Some(report), Some(state)) = (&app.failure_report, &mut app.failed_documents) {
+        render_failed_documents(report, state, f);
+    }
+    if app.shows_links() {
+        let links: Vec<(&str, String)> = app
+            .links()
+            .into_iter()
+            .map(|(name, url)| (name, url.to_string()))
+            .collect();
+        let label = app.selected_result().map(|r| r.label().to_string());
+        if let Some(state) = &mut app.link_list {
+            render_links(&label.unwrap_or_default(), &links, state, f);
+        }
+    }
+
+    render_toasts(app, f, f.area());
+    if app.is_flashing() {
+        let area = f.area();
+        f.buffer_mut()
+            .set_style(area, Style::new().add_modifier(Modifier::REVERSED));
+    }
+    app.theme.apply(f.buffer_mut());
+}
+
+/// A blank screen instead of the data, see
+/// [`Config::screensaver_after`](crate::app::Config::screensaver_after).
+fn render_screensaver(app: &App, f: &mut Frame) {
+    let text = match app.config.screensaver_confirm_profile {
+        true => "Press any key to choose the profile",
+        false => "Press any key to continue",
+    };
+    let [_, area, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])
+    .areas(f.area());
+    f.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::new().add_modifier(Modifier::DIM)),
+        area,
+    );
+    app.theme.apply(f.buffer_mut());
+}
+
+/// Overlay explaining the columns and the stages of the selected collection,
+/// highlighting the stage of the selected task.
+fn render_help(app: &App, f: &mut Frame) {
+    let area = centered_rect(80, 70, f.area());
+    let block = Block::default()
+        .title("Help (`F1` to close)")
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL);
+    let entry = |term: &str, text: &str| {
+        Line::from(vec![
+            Span::from(format!("{term}: ")).bold(),
+            Span::from(text.to_string()),
+        ])
+    };
+
+    let mut lines = vec![Line::from("Columns").underlined()];
+    lines.extend(glossary::COLUMNS.iter().map(|e| entry(e.term, e.text)));
+    if let Some(result) = app.selected_result() {
+        lines.push(Line::default());
+        lines.push(
+            Line::from(format!(
+                "Stages of {}",
+                isolate_bidi(result.label().to_string())
+            ))
+            .underlined(),
+        );
+        let selected = app.selected_task().map(|task| task.name.as_str());
+        let names = result
+            .tasks()
+            .map(|(_, _, task)| task.name.as_str())
+            .unique();
+        for name in names {
+            let text =
+                glossary::stage(name).map_or("No explanation for this stage yet", |e| e.text);
+            let line = entry(name, text);
+            lines.push(match Some(name) == selected {
+                true => line.reversed(),
+                false => line,
+            });
+        }
+    }
+    lines.push(Line::default());
+    lines.push(Line::from("About this build").underlined());
+    lines.extend(
+        app.about()
+            .into_iter()
+            .map(|(name, value)| entry(name, &value)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Overlay with the metadata of two profiles side by side, highlighting the
+/// settings which differ.
+fn render_metadata_diff(diff: &MetadataDiff, f: &mut Frame) {
+    let area = centered_rect(80, 70, f.area());
+    let [first, second] = &diff.profiles;
+    let block = Block::default()
+        .title(format!("Metadata of {first} and {second} (`c` to close)"))
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL);
+    let rows = diff.rows.iter().map(|row| {
+        let [a, b] = row.values.clone().map(|value| value.unwrap_or_default());
+        let style = match row.differs() {
+            true => kind_style(NotificationKind::Warning),
+            false => Style::default(),
+        };
+        Row::new([row.key.clone(), a, b]).style(style)
+    });
+    let widths = [
+        Constraint::Percentage(30),
+        Constraint::Percentage(35),
+        Constraint::Percentage(35),
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(["", first.as_str(), second.as_str()])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(block);
+    f.render_widget(Clear, area);
+    f.render_widget(table, area);
+}
+
+/// The documents of a failure report, with the source and error of the
+/// selected one in full below.
+fn render_failed_documents(report: &FailureReport, state: &mut TableState, f: &mut Frame) {
+    let area = centered_rect(80, 70, f.area());
+    let block = Block::default()
+        .title(format!(
+            "{} out of {} failed documents (`E` to close)",
+            report.sampled, report.total
+        ))
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let [types, table_area, selected] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Fill(1),
+        Constraint::Length(4),
+    ])
+    .areas(inner);
+
+    let mime_types = report
+        .mime_types()
+        .iter()
+        .map(|(mime_type, count)| format!("{mime_type} ×{count}"))
+        .join(", ");
+    f.render_widget(Line::from(format!("By type: {mime_types}")), types);
+
+    let rows = report.documents.iter().map(|document| {
+        Row::new([
+            isolate_bidi(document.name.clone()),
+            document.mime_type.clone().unwrap_or_default(),
+            document
+                .size
+                .map(|size| human_bytes(size as usize))
+                .unwrap_or_default(),
+            document
+                .error
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        ])
+    });
+    let widths = [
+        Constraint::Percentage(35),
+        Constraint::Percentage(20),
+        Constraint::Length(9),
+        Constraint::Fill(1),
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(["Name", "Type", "Size", "Error"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
+    f.render_stateful_widget(table, table_area, state);
+
+    let Some(document) = state
+        .selected()
+        .and_then(|index| report.documents.get(index))
+    else {
+        return;
+    };
+    let source = document.source.as_deref().unwrap_or("unknown");
+    let mut lines = vec![Line::from(format!("Source: {source}"))];
+    lines.extend(
+        format!("Error: {}", document.error)
+            .lines()
+            .map(|line| Line::styled(line.to_string(), kind_style(NotificationKind::Error))),
+    );
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), selected);
+}
+
+fn render_links(label: &str, links: &[(&str, String)], state: &mut TableState, f: &mut Frame) {
+    let area = centered_rect(80, 70, f.area());
+    let block = Block::default()
+        .title(format!(
+            "Links of {} (`l` to close)",
+            isolate_bidi(label.to_string())
+        ))
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL);
+    let rows = links
+        .iter()
+        .map(|(name, url)| Row::new([name.to_string(), url.clone()]));
+    let widths = [Constraint::Length(25), Constraint::Fill(1)];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["Link", "URL"]).style(Style::new().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(HIGHLIGHT_SYMBOL)
+        .block(block);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(table, area, state);
+}
+
+/// Debug overlay listing the most recent requests, newest at the bottom.
+fn render_request_log(app: &App, f: &mut Frame) {
+    let area = centered_rect(80, 60, f.area());
+    let block = Block::default()
+        .title("Requests (`d` to close)")
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let last = app.request_log.last_with_body();
+    let [list, response] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Percentage(if last.is_some() { 50 } else { 0 }),
+    ])
+    .areas(inner);
+    let height = list.height as usize;
+    let entries = app.request_log.iter();
+    let lines: Vec<Line> = entries
+        .flat_map(|entry| {
+            let style = match entry.status {
+                Ok(code) if code < 400 => Style::default(),
+                _ => kind_style(NotificationKind::Error),
+            };
+            let line = Line::styled(
+                format!("{} {}", entry.time.format("%H:%M:%S"), entry.line()),
+                style,
+            );
+            let deviations = entry.deviations.iter().map(|deviation| {
+                Line::styled(
+                    format!("  {deviation}"),
+                    kind_style(NotificationKind::Warning),
+                )
+            });
+            std::iter::once(line).chain(deviations)
+        })
+        .collect();
+    let lines = lines[lines.len().saturating_sub(height)..].to_vec();
+    f.render_widget(Paragraph::new(lines), list);
+    if let Some(entry) = last {
+        let block = Block::default()
+            .title(format!("Response of {}", entry.url))
+            .borders(Borders::TOP);
+        let body = Paragraph::new(entry.body.as_deref().unwrap_or_default())
+            .wrap(Wrap { trim: false })
+            .block(block);
+        f.render_widget(body, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_wide_characters() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdefgh", 5), "abcd…");
+        // Each of these characters is two columns wide.
+        assert_eq!(truncate("データセット", 7), "データ…");
+        assert_eq!(truncate("🔥🔥🔥", 4), "🔥…");
+        assert!(truncate("データセット", 7).width() <= 7);
+    }
+
+    #[test]
+    fn test_upgrade_marks() {
+        let start = Local::now();
+        let series: Vec<_> = (0..12)
+            .map(|minutes| (start + chrono::Duration::minutes(minutes), 0))
+            .collect();
+        let change = |seconds, to: &str| VersionChange {
+            time: start + chrono::Duration::seconds(seconds),
+            from: "3.15.5".to_string(),
+            to: to.to_string(),
+        };
+        assert_eq!(upgrade_marks(&[], &series), None);
+        // Before the series, or too close to its end to be written in full.
+        let changes = [
+            change(-60, "3.16.0"),
+            change(130, "3.17.0"),
+            change(590, "4.0.0"),
+        ];
+        assert_eq!(
+            upgrade_marks(&changes, &series).as_deref(),
+            Some("  ▲3.17.0 ▲4")
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let mut theme = Theme {
+            unicode: true,
+            ..Theme::default()
+        };
+        assert_eq!(progress_bar(0, 8, &theme), "           0%");
+        assert_eq!(progress_bar(57, 8, &theme), "████▌     57%");
+        assert_eq!(progress_bar(100, 8, &theme), "████████ 100%");
+        theme.unicode = false;
+        assert_eq!(progress_bar(57, 8, &theme), "####      57%");
+    }
+
+    #[test]
+    fn test_fetch_age() {
+        let seconds = chrono::Duration::seconds;
+        assert_eq!(fetch_age(seconds(3), 1), "3s ago");
+        assert_eq!(fetch_age(seconds(3), 5), "just now");
+        assert_eq!(fetch_age(seconds(12), 5), "10s ago");
+        assert_eq!(fetch_age(seconds(150), 60), "2m ago");
+    }
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(999), "999 B");
+        assert_eq!(human_bytes(12_345), "12.3 kB");
+        assert_eq!(human_bytes(4_200_000), "4.2 MB");
+    }
+
+    #[test]
+    fn test_marquee() {
+        assert_eq!(marquee("short", 10, 3), "short");
+        assert_eq!(marquee("downtime", 4, 0), "down");
+        assert_eq!(marquee("downtime", 4, 6), "me  ");
+        assert_eq!(marquee("downtime", 4, 10), " dow");
+    }
+
+    #[test]
+    fn test_isolate_bidi() {
+        assert_eq!(isolate_bidi("Panama Papers".to_string()), "Panama Papers");
+        let isolated = isolate_bidi("وثائق بنما 2016".to_string());
+        assert!(isolated.starts_with('\u{2068}') && isolated.ends_with('\u{2069}'));
+        assert_eq!(isolated.width(), "وثائق بنما 2016".width());
+    }
 }
@@ -1,17 +1,111 @@
-use chrono::Local;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use chrono::{Duration, Local};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
+use tokio::sync::mpsc::UnboundedSender;
 
-use crate::app::{App, CurrentView};
+use crate::{
+    api::{self, Progress, Snapshot},
+    app::{App, Counter, CurrentView, Reply},
+    keymap::KeyContext,
+    notification::NotificationKind,
+};
 
-pub async fn update(app: &mut App, key_event: KeyEvent) {
+/// Handles a key press. Requests it starts run in the background, their
+/// replies are sent to `replies`.
+pub fn update(app: &mut App, key_event: KeyEvent, replies: &UnboundedSender<Reply>) {
+    if app.record_input(Local::now()) {
+        return;
+    }
+    if app.is_searching {
+        return search(app, key_event);
+    }
+    if app.tag_editor.is_some() {
+        return edit_tags(app, key_event);
+    }
+    if app.login_prompt.is_some() {
+        return enter_password(app, key_event, replies);
+    }
+    if app.upload_prompt.is_some() {
+        return enter_upload_path(app, key_event);
+    }
     match key_event.code {
+        // Esc closes what is shown on top, in the order of `key_context`,
+        // and only quits once there is nothing left to close.
+        KeyCode::Esc if app.show_help => app.toggle_help(),
+        KeyCode::Esc if app.metadata_diff.is_some() => app.metadata_diff = None,
+        KeyCode::Esc if app.shown_failure_report().is_some() => app.toggle_failed_documents(),
+        KeyCode::Esc if app.shows_links() => app.toggle_links(),
+        KeyCode::Esc if app.is_zoomed() => app.toggle_zoom(),
+        KeyCode::Esc if !app.search.is_empty() => app.set_search(String::new()),
         KeyCode::Esc | KeyCode::Char('q') => app.quit(),
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit()
-            }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
         }
+        KeyCode::Char('c') if app.metadata_diff.is_some() => app.metadata_diff = None,
+        KeyCode::Char('c') if app.show_profile_selector() => app.compare_profiles(replies),
         KeyCode::Char('p') => app.toggle_profile_selector(),
+        KeyCode::Char('w') if !app.show_profile_selector() => app.toggle_watch(),
+        KeyCode::Char('e') if !app.show_profile_selector() => app.fetch_failures(replies),
+        KeyCode::Char('E') if !app.show_profile_selector() => app.toggle_failed_documents(),
+        KeyCode::Up | KeyCode::Char('k') if app.shown_failure_report().is_some() => {
+            app.failed_document_up()
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.shown_failure_report().is_some() => {
+            app.failed_document_down()
+        }
+        KeyCode::Char('l') if !app.show_profile_selector() => app.toggle_links(),
+        KeyCode::Up | KeyCode::Char('k') if app.shows_links() => app.link_up(),
+        KeyCode::Down | KeyCode::Char('j') if app.shows_links() => app.link_down(),
+        KeyCode::Enter | KeyCode::Char('o') if app.shows_links() => app.open_link(),
+        KeyCode::Char('y') if app.shows_links() => app.copy_link(),
+        KeyCode::Char('x') if !app.show_profile_selector() => app.fetch_xref(replies),
+        KeyCode::Char('m') if !app.show_profile_selector() => app.cycle_mute(),
+        KeyCode::Char('n') if !app.show_profile_selector() => app.toggle_notify(),
+        KeyCode::Char('d') => app.toggle_request_log(),
+        KeyCode::Char('+') => app.adjust_fetch_interval(true),
+        KeyCode::Char('-') => app.adjust_fetch_interval(false),
+        KeyCode::F(1) => app.toggle_help(),
+        KeyCode::Char('a') if app.toasts.has_sticky() => app.toasts.dismiss_sticky(),
+        KeyCode::Char('b') if !app.show_profile_selector() => app.write_bug_report(),
+        KeyCode::Char('H') if !app.show_profile_selector() => app.export_history(),
+        KeyCode::Char('/') if !app.show_profile_selector() => app.start_search(),
+        KeyCode::Char('t') if !app.show_profile_selector() => app.start_editing_tags(),
+        KeyCode::Char('g') if !app.show_profile_selector() => app.toggle_group_by_tag(),
+        KeyCode::Char('s') if !app.show_profile_selector() => app.cycle_sort(),
+        KeyCode::Char('S') if !app.show_profile_selector() => app.reverse_sort(),
+        KeyCode::Char('L') if !app.show_profile_selector() => app.start_login(),
+        KeyCode::Char('U') if !app.show_profile_selector() => app.start_upload(),
+        KeyCode::Char('Z') if !app.show_profile_selector() => app.toggle_zoom(),
+        KeyCode::Char('W') if !app.show_profile_selector() => app.toggle_wrap_details(),
+        KeyCode::Char('y') if !app.show_profile_selector() => app.copy_url(),
+        KeyCode::Left | KeyCode::Right
+            if key_event.modifiers == KeyModifiers::CONTROL && !app.show_profile_selector() =>
+        {
+            app.cycle_profile(key_event.code == KeyCode::Right);
+            let message = format!("Switched to profile {}", app.current_profile().name);
+            app.toast(NotificationKind::Info, message);
+        }
+        KeyCode::Up | KeyCode::Down
+            if key_event.modifiers == KeyModifiers::CONTROL && !app.show_profile_selector() =>
+        {
+            app.resize_details(key_event.code == KeyCode::Up)
+        }
+        KeyCode::PageDown if !app.show_profile_selector() => app.turn_status_page(true),
+        KeyCode::PageUp if !app.show_profile_selector() => app.turn_status_page(false),
+        KeyCode::Tab if !app.show_profile_selector() => app.toggle_details_focus(),
+        KeyCode::Up | KeyCode::Char('k') if app.key_context() == KeyContext::Details => {
+            app.scroll_details(false)
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.key_context() == KeyContext::Details => {
+            app.scroll_details(true)
+        }
+        KeyCode::Char('[') => app.cycle_details_tab(false),
+        KeyCode::Char(']') => app.cycle_details_tab(true),
+        KeyCode::Char('0') => app.clear_counter_filter(),
+        KeyCode::Char(c @ '1'..='7') => {
+            let index = c as usize - '1' as usize;
+            app.toggle_counter_filter(Counter::ALL[index]);
+        }
         KeyCode::Up | KeyCode::Char('k') => match app.show_profile_selector() {
             true => app.profile_up(),
             false => app.collection_up(),
@@ -20,22 +114,168 @@ pub async fn update(app: &mut App, key_event: KeyEvent) {
             true => app.profile_down(),
             false => app.collection_down(),
         },
-        KeyCode::Enter => {
-            if app.current_view == CurrentView::ProfileSwitcher {
-                app.toggle_profile_selector();
-            }
+        KeyCode::Enter if app.current_view == CurrentView::ProfileSwitcher => {
+            app.toggle_profile_selector();
+            let message = format!("Switched to profile {}", app.current_profile().name);
+            app.toast(NotificationKind::Info, message);
         }
+        KeyCode::Enter | KeyCode::Char(' ') => app.toggle_expanded(),
         _ => {}
     };
 }
 
-pub(crate) async fn fetch(app: &mut App) {
-    let elapsed = Local::now() - app.last_fetch;
-    if elapsed.num_seconds() > app.config.fetch_interval {
-        app.error_message = match app.fetch().await {
-            Ok(()) => String::default(),
-            Err(e) => e.to_string(),
-        };
-        app.last_fetch = Local::now();
+/// Handles key presses while the search is being typed.
+fn search(app: &mut App, key_event: KeyEvent) {
+    let mut search = app.search.clone();
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            return app.quit();
+        }
+        KeyCode::Enter => return app.stop_search(!search.is_empty()),
+        KeyCode::Esc => return app.stop_search(false),
+        KeyCode::Backspace => {
+            search.pop();
+        }
+        KeyCode::Char(c) => search.push(c),
+        _ => return,
+    }
+    app.set_search(search);
+}
+
+/// Handles key presses while tags are being typed.
+fn edit_tags(app: &mut App, key_event: KeyEvent) {
+    let Some(editor) = &mut app.tag_editor else {
+        return;
+    };
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Enter => app.stop_editing_tags(true),
+        KeyCode::Esc => app.stop_editing_tags(false),
+        KeyCode::Backspace => {
+            editor.input.pop();
+        }
+        KeyCode::Char(c) => editor.input.push(c),
+        _ => {}
+    }
+}
+
+/// Handles key presses while a password is being typed.
+fn enter_password(app: &mut App, key_event: KeyEvent, replies: &UnboundedSender<Reply>) {
+    let Some(prompt) = &mut app.login_prompt else {
+        return;
+    };
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Enter => app.stop_login(true, replies),
+        KeyCode::Esc => app.stop_login(false, replies),
+        KeyCode::Backspace => {
+            prompt.password.pop();
+        }
+        KeyCode::Char(c) => prompt.password.push(c),
+        _ => {}
+    }
+}
+
+/// Handles key presses while the path of an upload is being typed.
+fn enter_upload_path(app: &mut App, key_event: KeyEvent) {
+    let Some(prompt) = &mut app.upload_prompt else {
+        return;
+    };
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit()
+        }
+        KeyCode::Enter => app.stop_upload(true),
+        KeyCode::Esc => app.stop_upload(false),
+        KeyCode::Backspace => {
+            prompt.path.pop();
+        }
+        KeyCode::Char(c) => prompt.path.push(c),
+        _ => {}
+    }
+}
+
+pub fn mouse(app: &mut App, mouse_event: MouseEvent) {
+    if mouse_event.kind != MouseEventKind::Down(MouseButton::Left)
+        || app.record_input(Local::now())
+        || app.show_profile_selector()
+        || app.is_zoomed()
+    {
+        return;
+    }
+    let position = Position::new(mouse_event.column, mouse_event.row);
+    let clicked = app
+        .header_columns
+        .iter()
+        .find(|(_, area)| area.contains(position))
+        .map(|(column, _)| *column);
+    if let Some(column) = clicked {
+        app.toggle_sort(column);
+    }
+}
+
+pub fn tick(app: &mut App, snapshots: &UnboundedSender<Snapshot>) {
+    app.check_ticks(Local::now());
+    app.check_idle(Local::now());
+    app.notifications.expire(Local::now());
+    app.toasts.expire(Local::now());
+    app.check_upload();
+    // A status loaded from a file stays as it is.
+    if app.status_file.is_some() {
+        return;
+    }
+    fetch(app, snapshots);
+    if app.config.fetch_all_profiles {
+        fetch_background(app, snapshots);
+    }
+}
+
+/// Starts fetching a snapshot in the background once the fetch interval has
+/// passed. The snapshot is sent to `snapshots` when it is complete.
+pub(crate) fn fetch(app: &mut App, snapshots: &UnboundedSender<Snapshot>) {
+    let now = Local::now();
+    let elapsed = now - app.last_fetch;
+    let due = app.fetch_requested || elapsed.num_seconds() > app.fetch_interval();
+    if !due || app.is_fetching || app.rate_limited_for(now).is_some() {
+        return;
+    }
+    app.is_fetching = true;
+    app.fetch_requested = false;
+    app.last_fetch = Local::now();
+    app.fetch_progress = Progress::default();
+    let api = app.api().with_progress(app.fetch_progress.clone());
+    let profile = app.current_profile;
+    let paging = app.status_paging();
+    let validators = app.status_validators();
+    // Not fetched again right after switching away from it.
+    let interval = Duration::seconds(app.fetch_interval());
+    app.scheduler.postpone(profile, now + interval);
+    let snapshots = snapshots.clone();
+    let task = tokio::spawn(async move {
+        // The receiver only goes away when the app quits.
+        let _ = snapshots.send(api::fetch_snapshot(api, profile, paging, validators).await);
+    });
+    app.fetch_task = Some(task.abort_handle());
+}
+
+/// Starts fetching the profiles other than the current one which are due,
+/// see [`Scheduler`](crate::schedule::Scheduler). They are fetched without
+/// validators since their last status is shown once switched to.
+fn fetch_background(app: &mut App, snapshots: &UnboundedSender<Snapshot>) {
+    let interval = Duration::seconds(app.fetch_interval());
+    let due = app
+        .scheduler
+        .start(Local::now(), interval, app.current_profile, app.is_fetching);
+    for profile in due {
+        let api = app.api_for(profile);
+        let paging = app.background_paging();
+        let snapshots = snapshots.clone();
+        tokio::spawn(async move {
+            let _ = snapshots.send(api::fetch_snapshot(api, profile, paging, None).await);
+        });
     }
 }
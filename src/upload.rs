@@ -0,0 +1,367 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use reqwest::header::CONTENT_TYPE;
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::{
+    api::Api,
+    models::{Collection, IngestResponse},
+    request_log::RequestLogEntry,
+};
+
+/// How far an upload got. It is shared with the task uploading, so that the
+/// interface can show it.
+#[derive(Clone, Debug, Default)]
+pub struct UploadProgress(Arc<UploadCounters>);
+
+#[derive(Debug, Default)]
+struct UploadCounters {
+    files: AtomicU64,
+    uploaded_files: AtomicU64,
+    bytes: AtomicU64,
+    uploaded_bytes: AtomicU64,
+}
+
+impl UploadProgress {
+    fn uploaded(&self, entry: &Entry) {
+        if entry.size.is_some() {
+            self.0.uploaded_files.fetch_add(1, Ordering::Relaxed);
+        }
+        let size = entry.size.unwrap_or_default();
+        self.0.uploaded_bytes.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Files uploaded so far, and in total.
+    pub fn files(&self) -> (u64, u64) {
+        let uploaded = self.0.uploaded_files.load(Ordering::Relaxed);
+        (uploaded, self.0.files.load(Ordering::Relaxed))
+    }
+
+    /// Bytes uploaded so far, and in total.
+    pub fn bytes(&self) -> (u64, u64) {
+        let uploaded = self.0.uploaded_bytes.load(Ordering::Relaxed);
+        (uploaded, self.0.bytes.load(Ordering::Relaxed))
+    }
+}
+
+/// A file or directory to upload.
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    path: PathBuf,
+    name: String,
+    /// Index of the directory the entry is in, if it isn't at the top.
+    parent: Option<usize>,
+    /// Size of a file, `None` for a directory.
+    size: Option<u64>,
+}
+
+/// The files to upload from `path`, which is either a file or a directory
+/// whose contents are uploaded, every directory before the entries in it.
+/// Symbolic links to directories aren't followed.
+fn entries(path: &Path) -> Result<Vec<Entry>> {
+    let metadata = fs::metadata(path)?;
+    if metadata.is_file() {
+        return Ok(vec![Entry {
+            path: path.to_path_buf(),
+            name: file_name(path),
+            parent: None,
+            size: Some(metadata.len()),
+        }]);
+    }
+    let mut entries = Vec::new();
+    add_entries(path, None, &mut entries)?;
+    Ok(entries)
+}
+
+fn add_entries(dir: &Path, parent: Option<usize>, entries: &mut Vec<Entry>) -> Result<()> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+    for path in paths {
+        let is_link = fs::symlink_metadata(&path)?.is_symlink();
+        let metadata = fs::metadata(&path)?;
+        let mut entry = Entry {
+            name: file_name(&path),
+            path,
+            parent,
+            size: None,
+        };
+        if metadata.is_file() {
+            entry.size = Some(metadata.len());
+            entries.push(entry);
+        } else if metadata.is_dir() && !is_link {
+            let path = entry.path.clone();
+            entries.push(entry);
+            add_entries(&path, Some(entries.len() - 1), entries)?;
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Builds a `multipart/form-data` body with the `meta` of an entry and, for
+/// files, its `contents`. Returns the boundary separating the parts along
+/// with the body.
+fn multipart(meta: &serde_json::Value, file: Option<(&str, &[u8])>) -> (String, Vec<u8>) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos());
+    let mut boundary = format!("aleph-tui-{nanos:x}");
+    // The boundary may not occur in the parts.
+    while file.is_some_and(|(_, contents)| {
+        contents
+            .windows(boundary.len())
+            .any(|window| window == boundary.as_bytes())
+    }) {
+        boundary.push('x');
+    }
+    let mut body =
+        format!("--{boundary}\r\nContent-Disposition: form-data; name=\"meta\"\r\n\r\n{meta}\r\n")
+            .into_bytes();
+    if let Some((name, contents)) = file {
+        let name = name
+            .replace('"', "%22")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A");
+        body.extend(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\n\
+                 Content-Type: application/octet-stream\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend(contents);
+        body.extend(b"\r\n");
+    }
+    body.extend(format!("--{boundary}--\r\n").as_bytes());
+    (boundary, body)
+}
+
+/// How an upload ended.
+#[derive(Debug)]
+pub struct UploadOutcome {
+    /// Number of files uploaded, or why the upload stopped.
+    pub result: Result<u64>,
+    pub requests: Vec<RequestLogEntry>,
+}
+
+/// Files being uploaded to a collection with Aleph's ingest API, in the
+/// background.
+#[derive(Debug)]
+pub struct Upload {
+    /// Label of the collection uploaded to.
+    pub label: String,
+    pub progress: UploadProgress,
+    outcome: oneshot::Receiver<UploadOutcome>,
+}
+
+impl Upload {
+    /// Starts uploading `path`, a file or a directory, to `collection`.
+    /// Directories become folders in the collection.
+    pub fn start(api: Api, collection: &Collection, path: &Path) -> Result<Self> {
+        let entries = entries(path).map_err(|e| eyre!("Can't read {}: {e}", path.display()))?;
+        if !entries.iter().any(|entry| entry.size.is_some()) {
+            return Err(eyre!("There are no files in {}", path.display()));
+        }
+        let progress = UploadProgress::default();
+        let files = entries.iter().filter(|entry| entry.size.is_some()).count();
+        let bytes = entries.iter().filter_map(|entry| entry.size).sum();
+        progress.0.files.store(files as u64, Ordering::Relaxed);
+        progress.0.bytes.store(bytes, Ordering::Relaxed);
+
+        let (sender, outcome) = oneshot::channel();
+        let collection_id = collection.collection_id.clone();
+        let task_progress = progress.clone();
+        tokio::spawn(async move {
+            let mut requests = Vec::new();
+            let result = upload(
+                &api,
+                &collection_id,
+                &entries,
+                &task_progress,
+                &mut requests,
+            )
+            .await;
+            // The receiver only goes away when the app quits.
+            let _ = sender.send(UploadOutcome { result, requests });
+        });
+        Ok(Self {
+            label: collection.label.clone(),
+            progress,
+            outcome,
+        })
+    }
+
+    /// How the upload ended, once it did.
+    pub fn finished(&mut self) -> Option<UploadOutcome> {
+        match self.outcome.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Closed) => Some(UploadOutcome {
+                result: Err(eyre!("the upload stopped unexpectedly")),
+                requests: Vec::new(),
+            }),
+        }
+    }
+}
+
+/// Uploads the entries one by one, stopping at the first which fails. Not
+/// retried, since a retry could ingest a file twice.
+async fn upload(
+    api: &Api,
+    collection_id: &str,
+    entries: &[Entry],
+    progress: &UploadProgress,
+    requests: &mut Vec<RequestLogEntry>,
+) -> Result<u64> {
+    let path = format!("collections/{collection_id}/ingest");
+    let mut ids: Vec<String> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let mut meta = serde_json::json!({ "file_name": entry.name });
+        if let Some(parent) = entry.parent {
+            meta["parent"] = serde_json::json!({ "id": ids[parent] });
+        }
+        let contents = match entry.size {
+            Some(_) => Some(
+                fs::read(&entry.path)
+                    .map_err(|e| eyre!("Can't read {}: {e}", entry.path.display()))?,
+            ),
+            None => None,
+        };
+        let file = contents
+            .as_deref()
+            .map(|contents| (entry.name.as_str(), contents));
+        let (boundary, body) = multipart(&meta, file);
+        let request = api
+            .post(&path)
+            .header(
+                CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body);
+        let response = api
+            .send(request, requests)
+            .await
+            .map_err(|e| api.error(e))?;
+        let response: IngestResponse = crate::api::check_status(response)?
+            .json()
+            .await
+            .map_err(|e| api.error(e))?;
+        ids.push(response.id);
+        progress.uploaded(entry);
+    }
+    Ok(progress.files().0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::{
+        api::Auth,
+        models::Status,
+        test_server::{Response, Server},
+    };
+
+    #[test]
+    fn test_entries() {
+        let dir = std::env::temp_dir().join(format!("aleph-tui-upload-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("b/c.txt"), "cc").unwrap();
+        let entries = entries(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.parent, entry.size))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("a.txt", None, Some(1)),
+                ("b", None, None),
+                ("c.txt", Some(1), Some(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multipart() {
+        let meta = serde_json::json!({ "file_name": "a\"b.txt" });
+        let (boundary, body) = multipart(&meta, Some(("a\"b.txt", b"contents")));
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(&format!("--{boundary}\r\n")));
+        assert!(body.contains("filename=\"a%22b.txt\"\r\n"));
+        assert!(body.contains("\r\n\r\ncontents\r\n"));
+        assert!(body.ends_with(&format!("--{boundary}--\r\n")));
+    }
+
+    #[tokio::test]
+    async fn test_upload() {
+        let ingested = AtomicUsize::new(0);
+        let server = Server::start(move |_| {
+            let id = ingested.fetch_add(1, Ordering::Relaxed) + 1;
+            Response::json(&format!(r#"{{"id": "doc{id}", "status": "ok"}}"#))
+        })
+        .await;
+        let api = Api::new(&server.url, Auth::Token("token".to_string()), "test");
+        let raw = fs::read_to_string("testdata/status.json").unwrap();
+        let status: Status = serde_json::from_str(&raw).unwrap();
+        let collection = status.results[0].collection.clone().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("aleph-tui-ingest-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("b/c.txt"), "cc").unwrap();
+        let mut upload = Upload::start(api, &collection, &dir).unwrap();
+        let outcome = loop {
+            match upload.finished() {
+                Some(outcome) => break outcome,
+                None => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(outcome.result.unwrap(), 2);
+        assert_eq!(upload.progress.files(), (2, 2));
+        assert_eq!(upload.progress.bytes(), (3, 3));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        let path = format!("/api/2/collections/{}/ingest", collection.collection_id);
+        for request in &requests {
+            assert_eq!(
+                (request.method.as_str(), request.path.as_str()),
+                ("POST", path.as_str())
+            );
+            let content_type = request.header("Content-Type").unwrap();
+            assert!(content_type.starts_with("multipart/form-data; boundary="));
+        }
+        let bodies: Vec<_> = requests
+            .iter()
+            .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+            .collect();
+        assert!(bodies[0].contains(r#"{"file_name":"a.txt"}"#));
+        assert!(bodies[0].contains("\r\n\r\na\r\n"));
+        // The directory has no contents, the file in it refers to it.
+        assert!(bodies[1].contains(r#"{"file_name":"b"}"#));
+        assert!(!bodies[1].contains("name=\"file\""));
+        assert!(bodies[2].contains(r#""parent":{"id":"doc2"}"#));
+        assert!(bodies[2].contains("\r\n\r\ncc\r\n"));
+    }
+}
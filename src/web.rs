@@ -0,0 +1,306 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local};
+use color_eyre::Result;
+use serde::Serialize;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    task::JoinHandle,
+};
+
+use crate::{
+    app::{App, SortColumn},
+    health::MAX_STALL,
+    notification::NotificationKind,
+};
+
+/// Seconds after which the page reloads itself.
+const REFRESH_INTERVAL: u64 = 5;
+/// The request line and headers are only read up to this size, anything
+/// after is ignored.
+const MAX_REQUEST_SIZE: usize = 8192;
+/// Number of fetches the processing rate is averaged over.
+const RATE_WINDOW: usize = 10;
+
+/// What the mirror serves, rendered whenever the app publishes.
+#[derive(Clone, Debug, Default)]
+struct Pages {
+    html: String,
+    /// The metrics aleph-tui derives per collection, see [`metrics`].
+    json: String,
+}
+
+/// Read-only HTML page mirroring the table and alerts of the app, served on
+/// [`Config::web_mirror`](crate::app::Config::web_mirror). The metrics behind
+/// it are served as JSON on `/api/collections`.
+#[derive(Debug)]
+pub struct Mirror {
+    pages: watch::Sender<Pages>,
+    server: JoinHandle<()>,
+}
+
+impl Mirror {
+    /// Starts serving on `address`, e.g. `127.0.0.1:8686`.
+    pub async fn start(address: &str) -> Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let (pages, receiver) = watch::channel(Pages::default());
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let pages = receiver.borrow().clone();
+                // A client going away while being served is none of our
+                // business.
+                tokio::spawn(async move {
+                    let _ = serve(stream, pages).await;
+                });
+            }
+        });
+        Ok(Self { pages, server })
+    }
+
+    /// Updates the pages to the state of `app`.
+    pub fn publish(&self, app: &App) {
+        self.pages.send_replace(Pages {
+            html: render(app),
+            // Serializing plain structs and numbers can't fail.
+            json: serde_json::to_string(&metrics(app, Local::now())).unwrap_or_default(),
+        });
+    }
+}
+
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Reads the request line and headers, which may take more than one read.
+/// Only GET is served, so there is no body to read.
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    while head.len() < MAX_REQUEST_SIZE && !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+async fn serve(mut stream: TcpStream, pages: Pages) -> Result<()> {
+    let request = read_head(&mut stream).await?;
+    let text = "text/plain; charset=utf-8";
+    let (status, content_type, body) =
+        match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+            ["GET", "/"] => ("200 OK", "text/html; charset=utf-8", pages.html),
+            ["GET", "/api/collections"] => ("200 OK", "application/json", pages.json),
+            ["GET", _] => ("404 Not Found", text, "Not found\n".to_string()),
+            _ => (
+                "405 Method Not Allowed",
+                text,
+                "Only GET is supported\n".to_string(),
+            ),
+        };
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-store\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Metrics of the status shown, as served on `/api/collections`.
+#[derive(Debug, Serialize)]
+pub struct Metrics<'a> {
+    pub profile: String,
+    pub last_fetch: DateTime<Local>,
+    pub collections: Vec<CollectionMetrics<'a>>,
+}
+
+/// What aleph-tui derives for a collection, on top of the counters Aleph
+/// reports.
+#[derive(Debug, Serialize)]
+pub struct CollectionMetrics<'a> {
+    /// See [`StatusResult::key`](crate::models::StatusResult::key).
+    pub key: &'a str,
+    pub label: &'a str,
+    pub collection_id: Option<&'a str>,
+    pub foreign_id: Option<&'a str>,
+    pub todo: u64,
+    pub effective_todo: u64,
+    pub doing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// See [`health::score`](crate::health::score).
+    pub health: u8,
+    /// Tasks finished per minute over the last fetches.
+    pub tasks_per_minute: f64,
+    /// Seconds until the effective todo is processed at `tasks_per_minute`.
+    pub eta_secs: Option<i64>,
+    /// Seconds the collection has been processing without progress.
+    pub stalled_secs: i64,
+    /// Whether it has been stalled long enough to count fully against the
+    /// health score.
+    pub stalled: bool,
+    /// Whether a delete or flush job is running.
+    pub destructive: bool,
+    pub muted: bool,
+}
+
+/// The metrics of the collections shown by `app` at `now`.
+pub fn metrics(app: &App, now: DateTime<Local>) -> Metrics<'_> {
+    let collections = app
+        .visible_results()
+        .into_iter()
+        .map(|result| {
+            let tasks_per_minute = app
+                .collections
+                .get(result.key())
+                .and_then(|state| state.recent_throughput(RATE_WINDOW))
+                .unwrap_or_default();
+            let eta_secs = (tasks_per_minute > 0.0)
+                .then(|| (result.effective_todo() as f64 / tasks_per_minute * 60.0) as i64);
+            let stalled = app.stalled_for(result, now);
+            CollectionMetrics {
+                key: result.key(),
+                label: result.label(),
+                collection_id: result.collection.as_ref().map(|c| c.collection_id.as_str()),
+                foreign_id: result.collection.as_ref().map(|c| c.foreign_id.as_str()),
+                todo: result.todo,
+                effective_todo: result.effective_todo(),
+                doing: result.doing,
+                succeeded: result.succeeded,
+                failed: result.failed,
+                health: app.health(result, now),
+                tasks_per_minute,
+                eta_secs,
+                stalled_secs: stalled.num_seconds(),
+                stalled: stalled >= MAX_STALL,
+                destructive: result.is_destructive(),
+                muted: app.is_muted(result.key(), now),
+            }
+        })
+        .collect();
+    Metrics {
+        profile: app.current_profile().name,
+        last_fetch: app.last_fetch,
+        collections,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The page for the current state of `app`. Doesn't contain the URL or the
+/// token of the profile.
+pub fn render(app: &App) -> String {
+    let now = Local::now();
+    let number = |column, value| app.config.number_format.format(column, value);
+    let mut alerts = String::new();
+    let messages = app
+        .notifications
+        .iter()
+        .map(|n| (n.kind, &n.message))
+        .chain(app.toasts.iter().map(|t| (t.kind, &t.message)));
+    for (kind, message) in messages {
+        let class = match kind {
+            NotificationKind::Error => "error",
+            NotificationKind::Warning => "warning",
+            NotificationKind::Info => "info",
+        };
+        let _ = writeln!(alerts, "<li class=\"{class}\">{}</li>", escape(message));
+    }
+    let mut rows = String::new();
+    for result in app.visible_results() {
+        let health = app.health(result, now);
+        let class = match health {
+            _ if result.is_destructive() => "destructive",
+            0..50 => "error",
+            50..80 => "warning",
+            _ => "",
+        };
+        let _ = writeln!(
+            rows,
+            "<tr class=\"{class}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(result.label()),
+            escape(result.min_ts.as_deref().unwrap_or("-")),
+            number(SortColumn::Todo, result.todo),
+            number(SortColumn::EffectiveTodo, result.effective_todo()),
+            number(SortColumn::Doing, result.doing),
+            number(SortColumn::Succeeded, result.succeeded),
+            number(SortColumn::Failed, result.failed),
+            number(SortColumn::Aborted, result.aborted),
+            number(SortColumn::Cancelled, result.cancelled),
+            health,
+        );
+    }
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_INTERVAL}\">\n\
+         <title>aleph-tui - {profile}</title>\n\
+         <style>\n\
+         body {{ font-family: monospace; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         td, th {{ padding: 0 0.5em; text-align: right; }}\n\
+         td:first-child, th:first-child {{ text-align: left; }}\n\
+         .error {{ color: #c00; }}\n\
+         .warning {{ color: #a60; }}\n\
+         .destructive {{ background: #fd0; }}\n\
+         </style>\n</head>\n<body>\n\
+         <h1>{profile}</h1>\n\
+         <p>Last fetch at {last_fetch}, updates every {REFRESH_INTERVAL}s.</p>\n\
+         <ul>\n{alerts}</ul>\n\
+         <table>\n\
+         <tr><th>Label</th><th>Start Time</th><th>Todo</th><th>Eff. Todo</th><th>Doing</th>\
+         <th>Success</th><th>Failed</th><th>Aborted</th><th>Cancel</th><th>Health</th></tr>\n\
+         {rows}</table>\n</body>\n</html>\n",
+        profile = escape(&app.current_profile().name),
+        last_fetch = app.last_fetch.format("%H:%M:%S"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_split_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let pages = Pages {
+            json: "[]".to_string(),
+            ..Pages::default()
+        };
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve(stream, pages).await.unwrap();
+        });
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(b"GET /api/coll").await.unwrap();
+        stream.flush().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        stream
+            .write_all(b"ections HTTP/1.1\r\nHost: x\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        server.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\n[]"));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::models::XrefMatch;
+
+/// Cross-referencing results of a collection, by how they were judged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XrefSummary {
+    /// Key of the collection, see [`crate::models::StatusResult::key`].
+    pub key: String,
+    /// Number of match candidates xref found.
+    pub total: u64,
+    /// Number of candidates the judgements were counted from, the most
+    /// likely ones first.
+    pub sampled: usize,
+    /// Candidates judged to be the same entity.
+    pub positive: usize,
+    /// Candidates judged to be different entities.
+    pub negative: usize,
+    pub unsure: usize,
+}
+
+impl XrefSummary {
+    pub fn new(key: String, matches: &[XrefMatch], total: u64) -> Self {
+        let count = |judgement: &str| {
+            matches
+                .iter()
+                .filter(|m| m.judgement.as_deref() == Some(judgement))
+                .count()
+        };
+        Self {
+            key,
+            total,
+            sampled: matches.len(),
+            positive: count("positive"),
+            negative: count("negative"),
+            unsure: count("unsure"),
+        }
+    }
+
+    /// Candidates which were judged either way, including unsure ones.
+    pub fn decided(&self) -> usize {
+        self.positive + self.negative + self.unsure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let matches: Vec<XrefMatch> = serde_json::from_str(
+            r#"[
+                {"score": 0.9, "judgement": "positive"},
+                {"score": 0.8, "judgement": "negative"},
+                {"score": 0.7, "judgement": "no_judgement"},
+                {"score": 0.6}
+            ]"#,
+        )
+        .unwrap();
+        let summary = XrefSummary::new("94".to_string(), &matches, 120);
+        assert_eq!(
+            (summary.positive, summary.negative, summary.unsure),
+            (1, 1, 0)
+        );
+        assert_eq!(
+            (summary.decided(), summary.sampled, summary.total),
+            (2, 4, 120)
+        );
+    }
+}